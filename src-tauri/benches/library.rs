@@ -0,0 +1,223 @@
+//! 在 1 万条提示词的库上比较常用查询在迁移 7（索引）前后的耗时
+//!
+//! 运行：`cargo bench --manifest-path src-tauri/Cargo.toml --bench library`
+
+use rusqlite::{params, Connection};
+use std::time::{Duration, Instant};
+
+const PROMPT_COUNT: usize = 10_000;
+const TAG_COUNT: usize = 50;
+/// 每条提示词最多的标签数
+const MAX_TAGS_PER_PROMPT: u64 = 3;
+
+/// 迁移 1-6，与 migrations 目录中的脚本一致
+const BASE_MIGRATIONS: [&str; 6] = [
+    include_str!("../migrations/0001_create_initial_tables.up.sql"),
+    include_str!("../migrations/0002_create_prompt_versions.up.sql"),
+    include_str!("../migrations/0003_add_soft_delete.up.sql"),
+    include_str!("../migrations/0004_create_snippets.up.sql"),
+    include_str!("../migrations/0005_add_prompt_use_count.up.sql"),
+    include_str!("../migrations/0006_create_prompt_usage.up.sql"),
+];
+const INDEX_MIGRATION: &str = include_str!("../migrations/0007_add_indexes.up.sql");
+
+/// 固定种子的伪随机数，保证每次生成的库相同
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+fn timestamp(seconds_ago: u64) -> String {
+    let time = chrono::Utc::now() - chrono::Duration::seconds(seconds_ago as i64);
+    time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+/// 生成提示词库：约 5% 在回收站，10% 收藏，三分之一用过
+fn build_library() -> rusqlite::Result<Connection> {
+    let mut conn = Connection::open_in_memory()?;
+    conn.pragma_update(None, "foreign_keys", true)?;
+    for sql in BASE_MIGRATIONS {
+        conn.execute_batch(sql)?;
+    }
+
+    let mut rng = Rng(0x5eed_1234_abcd_ef01);
+    let tx = conn.transaction()?;
+    for tag in 0..TAG_COUNT {
+        tx.execute(
+            "INSERT INTO tags (id, name, color) VALUES (?1, ?2, '#3b82f6')",
+            params![format!("tag-{}", tag), format!("标签{}", tag)],
+        )?;
+    }
+    for prompt in 0..PROMPT_COUNT {
+        let id = format!("prompt-{}", prompt);
+        let year = 365 * 24 * 3600;
+        let updated_at = timestamp(rng.below(year));
+        let last_used_at = if rng.below(3) == 0 {
+            timestamp(rng.below(year))
+        } else {
+            updated_at.clone()
+        };
+        let deleted_at = (rng.below(20) == 0).then(|| timestamp(rng.below(30 * 24 * 3600)));
+        tx.execute(
+            "INSERT INTO prompts (id, title, content, is_favorite, created_at, updated_at, last_used_at, deleted_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5, ?6, ?7)",
+            params![
+                id,
+                format!("提示词 {}", prompt),
+                format!("请根据 {{{{topic}}}} 写一段内容，编号 {}", prompt),
+                rng.below(10) == 0,
+                updated_at,
+                last_used_at,
+                deleted_at
+            ],
+        )?;
+        for _ in 0..rng.below(MAX_TAGS_PER_PROMPT + 1) {
+            tx.execute(
+                "INSERT OR IGNORE INTO prompt_tags (prompt_id, tag_id) VALUES (?1, ?2)",
+                params![id, format!("tag-{}", rng.below(TAG_COUNT as u64))],
+            )?;
+        }
+    }
+    tx.commit()?;
+    conn.execute_batch("ANALYZE")?;
+    Ok(conn)
+}
+
+/// 执行多次取平均耗时
+fn measure<F>(iterations: u32, mut run: F) -> rusqlite::Result<Duration>
+where
+    F: FnMut() -> rusqlite::Result<()>,
+{
+    // 先执行一次预热语句缓存
+    run()?;
+    let start = Instant::now();
+    for _ in 0..iterations {
+        run()?;
+    }
+    Ok(start.elapsed() / iterations)
+}
+
+fn count_rows(conn: &Connection, sql: &str, params: impl rusqlite::Params) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare_cached(sql)?;
+    let mut rows = stmt.query(params)?;
+    while rows.next()?.is_some() {}
+    Ok(())
+}
+
+/// 与应用中对应位置相同的查询：(名称, 耗时)
+fn run_queries(conn: &mut Connection) -> rusqlite::Result<Vec<(&'static str, Duration)>> {
+    let mut results = Vec::new();
+
+    // 前端 getAllPrompts
+    results.push((
+        "主窗口列表（按修改时间）",
+        measure(20, || {
+            count_rows(
+                conn,
+                "SELECT * FROM prompts WHERE deleted_at IS NULL ORDER BY updated_at DESC",
+                [],
+            )
+        })?,
+    ));
+
+    // tray::load_menu_data
+    results.push((
+        "托盘最近使用",
+        measure(200, || {
+            count_rows(
+                conn,
+                "SELECT id, title FROM prompts WHERE deleted_at IS NULL
+                 ORDER BY last_used_at DESC LIMIT ?1",
+                params![10],
+            )
+        })?,
+    ));
+    results.push((
+        "托盘标签子菜单",
+        measure(20, || {
+            for tag in 0..TAG_COUNT {
+                count_rows(
+                    conn,
+                    "SELECT p.id, p.title FROM prompts p
+                     JOIN prompt_tags pt ON pt.prompt_id = p.id
+                     WHERE pt.tag_id = ?1 AND p.deleted_at IS NULL
+                     ORDER BY p.last_used_at DESC LIMIT ?2",
+                    params![format!("tag-{}", tag), 5],
+                )?;
+            }
+            Ok(())
+        })?,
+    ));
+
+    // store::ensure_tag 和导入时按名称查找标签
+    results.push((
+        "按名称查找标签 ×50",
+        measure(200, || {
+            for tag in 0..TAG_COUNT {
+                count_rows(
+                    conn,
+                    "SELECT id FROM tags WHERE name = ?1",
+                    params![format!("标签{}", tag)],
+                )?;
+            }
+            Ok(())
+        })?,
+    ));
+
+    // 删除标签时由外键级联删除 prompt_tags，测完回滚
+    results.push((
+        "删除标签（级联）",
+        measure(20, || {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM tags WHERE id = ?1", params!["tag-7"])?;
+            tx.rollback()
+        })?,
+    ));
+
+    Ok(results)
+}
+
+fn main() -> rusqlite::Result<()> {
+    let start = Instant::now();
+    let mut conn = build_library()?;
+    let prompt_tags: i64 =
+        conn.query_row("SELECT COUNT(*) FROM prompt_tags", [], |row| row.get(0))?;
+    println!(
+        "生成 {} 条提示词、{} 个标签、{} 条标签关联，用时 {:.1?}",
+        PROMPT_COUNT,
+        TAG_COUNT,
+        prompt_tags,
+        start.elapsed()
+    );
+
+    let before = run_queries(&mut conn)?;
+    conn.execute_batch(INDEX_MIGRATION)?;
+    conn.execute_batch("ANALYZE")?;
+    let after = run_queries(&mut conn)?;
+
+    println!();
+    println!(
+        "{:<24}{:>14}{:>14}{:>10}",
+        "查询", "无索引", "有索引", "倍数"
+    );
+    for ((name, before), (_, after)) in before.iter().zip(&after) {
+        println!(
+            "{:<24}{:>14.2?}{:>14.2?}{:>9.1}x",
+            name,
+            before,
+            after,
+            before.as_secs_f64() / after.as_secs_f64()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS prompt_tags;
+DROP TABLE IF EXISTS tags;
+DROP TABLE IF EXISTS prompts;
@@ -0,0 +1,4 @@
+DROP TRIGGER IF EXISTS prompt_versions_after_delete;
+DROP TRIGGER IF EXISTS prompt_versions_after_update;
+DROP TRIGGER IF EXISTS prompt_versions_after_insert;
+DROP TABLE IF EXISTS prompt_versions;
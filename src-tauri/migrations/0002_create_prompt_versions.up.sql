@@ -0,0 +1,49 @@
+CREATE TABLE IF NOT EXISTS prompt_versions (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  prompt_id TEXT NOT NULL,
+  version INTEGER NOT NULL,
+  title TEXT NOT NULL,
+  content TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  UNIQUE (prompt_id, version),
+  FOREIGN KEY (prompt_id) REFERENCES prompts(id) ON DELETE CASCADE
+);
+
+-- 已有的提示词记录为第 1 个版本
+INSERT INTO prompt_versions (prompt_id, version, title, content, created_at)
+SELECT id, 1, title, content, updated_at FROM prompts;
+
+-- 版本由触发器写入，前端编辑、同步更新和导入都会经过这里
+CREATE TRIGGER IF NOT EXISTS prompt_versions_after_insert
+AFTER INSERT ON prompts
+BEGIN
+  INSERT INTO prompt_versions (prompt_id, version, title, content, created_at)
+  VALUES (
+    NEW.id,
+    COALESCE((SELECT MAX(version) FROM prompt_versions WHERE prompt_id = NEW.id), 0) + 1,
+    NEW.title,
+    NEW.content,
+    strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+  );
+END;
+
+CREATE TRIGGER IF NOT EXISTS prompt_versions_after_update
+AFTER UPDATE OF title, content ON prompts
+WHEN OLD.title IS NOT NEW.title OR OLD.content IS NOT NEW.content
+BEGIN
+  INSERT INTO prompt_versions (prompt_id, version, title, content, created_at)
+  VALUES (
+    NEW.id,
+    COALESCE((SELECT MAX(version) FROM prompt_versions WHERE prompt_id = NEW.id), 0) + 1,
+    NEW.title,
+    NEW.content,
+    strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+  );
+END;
+
+-- 提示词被删除时，prompt_tags 依赖外键级联；这里显式清理版本，不依赖 PRAGMA foreign_keys
+CREATE TRIGGER IF NOT EXISTS prompt_versions_after_delete
+AFTER DELETE ON prompts
+BEGIN
+  DELETE FROM prompt_versions WHERE prompt_id = OLD.id;
+END;
@@ -0,0 +1,3 @@
+-- 回收站中的提示词在回退后无法区分，先彻底删除
+DELETE FROM prompts WHERE deleted_at IS NOT NULL;
+ALTER TABLE prompts DROP COLUMN deleted_at;
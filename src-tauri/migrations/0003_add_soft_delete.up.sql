@@ -0,0 +1,2 @@
+-- 删除提示词时只记录删除时间，由回收站负责恢复或彻底删除
+ALTER TABLE prompts ADD COLUMN deleted_at TEXT;
@@ -0,0 +1,9 @@
+-- 可复用片段，在提示词中通过 {{> 名称}} 引用
+CREATE TABLE IF NOT EXISTS snippets (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL UNIQUE,
+  content TEXT NOT NULL,
+  description TEXT NOT NULL DEFAULT '',
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
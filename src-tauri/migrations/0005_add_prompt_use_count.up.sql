@@ -0,0 +1,2 @@
+-- 记录提示词的使用次数，用于托盘"最常使用"排序
+ALTER TABLE prompts ADD COLUMN use_count INTEGER NOT NULL DEFAULT 0;
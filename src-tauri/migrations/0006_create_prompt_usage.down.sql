@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS prompts_after_delete_usage;
+DROP TRIGGER IF EXISTS prompt_usage_after_insert;
+DROP TABLE IF EXISTS prompt_usage;
@@ -0,0 +1,28 @@
+-- 提示词使用记录：每次复制、渲染或运行都会记录一条
+CREATE TABLE IF NOT EXISTS prompt_usage (
+  id TEXT PRIMARY KEY,
+  prompt_id TEXT NOT NULL,
+  action TEXT NOT NULL,  -- copy / render / run
+  source TEXT NOT NULL,  -- tray / palette / main / cli
+  used_at TEXT NOT NULL,
+  FOREIGN KEY (prompt_id) REFERENCES prompts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_prompt_usage_prompt ON prompt_usage(prompt_id, used_at);
+CREATE INDEX IF NOT EXISTS idx_prompt_usage_used_at ON prompt_usage(used_at);
+
+-- last_used_at 和 use_count 由使用记录统一维护
+CREATE TRIGGER IF NOT EXISTS prompt_usage_after_insert
+AFTER INSERT ON prompt_usage
+BEGIN
+  UPDATE prompts
+  SET last_used_at = NEW.used_at, use_count = use_count + 1
+  WHERE id = NEW.prompt_id;
+END;
+
+-- 彻底删除提示词时显式清理使用记录，不依赖 PRAGMA foreign_keys
+CREATE TRIGGER IF NOT EXISTS prompts_after_delete_usage
+AFTER DELETE ON prompts
+BEGIN
+  DELETE FROM prompt_usage WHERE prompt_id = OLD.id;
+END;
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_tags_name;
+DROP INDEX IF EXISTS idx_prompt_tags_tag_id;
+DROP INDEX IF EXISTS idx_prompts_updated_at;
+DROP INDEX IF EXISTS idx_prompts_last_used_at;
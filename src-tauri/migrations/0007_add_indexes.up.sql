@@ -0,0 +1,9 @@
+-- 托盘和列表按最近使用、最近修改排序
+CREATE INDEX IF NOT EXISTS idx_prompts_last_used_at ON prompts(last_used_at);
+CREATE INDEX IF NOT EXISTS idx_prompts_updated_at ON prompts(updated_at);
+
+-- prompt_tags 的主键以 prompt_id 开头，按标签筛选和级联删除标签需要单独的索引
+CREATE INDEX IF NOT EXISTS idx_prompt_tags_tag_id ON prompt_tags(tag_id);
+
+-- 标签按名称合并，同名标签已在执行这个脚本前合并
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tags_name ON tags(name);
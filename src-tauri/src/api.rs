@@ -0,0 +1,404 @@
+use axum::extract::{Path, Query, Request, State as ApiState};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, TcpListener};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::builtins::BuiltinKind;
+use crate::feishu_sync::{self, SyncResult};
+use crate::palette::{self, PaletteItem};
+use crate::settings::{load_app_settings, AppSettings};
+use crate::store::{self, PromptFilter, PromptStore, PromptWithTags, StoreError};
+use crate::template::{self, TemplateVariable};
+use crate::usage::{self, UsageAction, UsageSource};
+
+/// 本地 API 设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiSettings {
+    /// 默认关闭，只监听 127.0.0.1
+    pub enabled: bool,
+    pub port: u16,
+    /// 访问令牌，请求需携带 `Authorization: Bearer <token>`
+    pub token: String,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        // 首次读取设置时生成令牌，用户在设置页启用并保存后写入文件
+        Self {
+            enabled: false,
+            port: 17321,
+            token: generate_token(),
+        }
+    }
+}
+
+fn generate_token() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+/// 正在运行的本地 API 服务，修改设置时先停止旧的
+#[derive(Default)]
+pub struct ApiServer(Mutex<Option<RunningServer>>);
+
+struct RunningServer {
+    settings: ApiSettings,
+    shutdown: oneshot::Sender<()>,
+}
+
+struct ApiContext<R: Runtime> {
+    app_handle: AppHandle<R>,
+    token: Arc<str>,
+}
+
+impl<R: Runtime> Clone for ApiContext<R> {
+    fn clone(&self) -> Self {
+        Self {
+            app_handle: self.app_handle.clone(),
+            token: self.token.clone(),
+        }
+    }
+}
+
+/// 接口错误，以 `{"error": "..."}` 返回
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<StoreError> for ApiError {
+    fn from(e: StoreError) -> Self {
+        let status = match e {
+            StoreError::PromptNotFound(_) | StoreError::VersionNotFound { .. } => StatusCode::NOT_FOUND,
+            StoreError::SqliteError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self::new(status, e.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = Json(serde_json::json!({ "error": self.message }));
+        (self.status, body).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// 按设置启动或停止本地 API（启动时和保存设置后调用）
+pub fn restart_server<R: Runtime>(app_handle: &AppHandle<R>) -> Result<(), String> {
+    let settings = load_app_settings(app_handle).api;
+    let state = app_handle.state::<ApiServer>();
+    let mut current = state.0.lock().map_err(|e| e.to_string())?;
+
+    if current.as_ref().is_some_and(|running| running.settings == settings) {
+        return Ok(());
+    }
+    let restarting_same_port = current
+        .as_ref()
+        .is_some_and(|running| running.settings.port == settings.port);
+    if let Some(running) = current.take() {
+        let _ = running.shutdown.send(());
+        println!("本地 API 已停止");
+    }
+    if !settings.enabled {
+        return Ok(());
+    }
+    if settings.token.trim().is_empty() {
+        return Err("本地 API 的访问令牌不能为空".to_string());
+    }
+
+    let listener = bind(settings.port, restarting_same_port)?;
+    let context = ApiContext {
+        app_handle: app_handle.clone(),
+        token: Arc::from(settings.token.trim()),
+    };
+    let router = router(context);
+    let (shutdown, shutdown_signal) = oneshot::channel::<()>();
+    tauri::async_runtime::spawn(async move {
+        let listener = match tokio::net::TcpListener::from_std(listener) {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("本地 API 启动失败: {}", e);
+                return;
+            }
+        };
+        let server = axum::serve(listener, router).with_graceful_shutdown(async {
+            let _ = shutdown_signal.await;
+        });
+        if let Err(e) = server.await {
+            eprintln!("本地 API 运行出错: {}", e);
+        }
+    });
+
+    println!("本地 API 已启动: http://127.0.0.1:{}/api", settings.port);
+    *current = Some(RunningServer { settings, shutdown });
+    Ok(())
+}
+
+/// 监听本机端口，重启时旧服务可能还没释放端口，稍等后重试
+fn bind(port: u16, retry: bool) -> Result<TcpListener, String> {
+    let attempts = if retry { 20 } else { 1 };
+    let mut last_error = None;
+    for _ in 0..attempts {
+        match TcpListener::bind((Ipv4Addr::LOCALHOST, port)) {
+            Ok(listener) => {
+                listener
+                    .set_nonblocking(true)
+                    .map_err(|e| format!("本地 API 启动失败: {}", e))?;
+                return Ok(listener);
+            }
+            Err(e) => {
+                last_error = Some(e);
+                std::thread::sleep(Duration::from_millis(100));
+            }
+        }
+    }
+    Err(format!(
+        "本地 API 监听端口 {} 失败: {}",
+        port,
+        last_error.map(|e| e.to_string()).unwrap_or_default()
+    ))
+}
+
+fn router<R: Runtime>(context: ApiContext<R>) -> Router {
+    Router::new()
+        .route("/api/prompts", get(list_prompts::<R>).post(create_prompt::<R>))
+        .route("/api/prompts/:id", get(get_prompt::<R>))
+        .route("/api/prompts/:id/render", post(render_prompt::<R>))
+        .route("/api/search", get(search_prompts::<R>))
+        .route("/api/sync", post(sync::<R>))
+        .route("/mcp", post(mcp_endpoint::<R>))
+        .route_layer(middleware::from_fn_with_state(context.clone(), require_token::<R>))
+        .with_state(context)
+}
+
+/// 校验访问令牌，支持 `Authorization: Bearer <token>` 和 `X-PromptGenie-Token`
+async fn require_token<R: Runtime>(
+    ApiState(context): ApiState<ApiContext<R>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if provided_token(request.headers()).is_some_and(|token| token_matches(token, &context.token)) {
+        return next.run(request).await;
+    }
+    ApiError::new(StatusCode::UNAUTHORIZED, "访问令牌无效").into_response()
+}
+
+fn provided_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| {
+            headers
+                .get("x-promptgenie-token")
+                .and_then(|value| value.to_str().ok())
+        })
+        .map(str::trim)
+}
+
+/// 逐字节比较全部内容，耗时不随匹配位置变化
+fn token_matches(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn open_store<R: Runtime>(context: &ApiContext<R>) -> Result<rusqlite::Connection, ApiError> {
+    let store = context.app_handle.state::<PromptStore>();
+    Ok(store.open()?)
+}
+
+/// 获取未删除的提示词，回收站中的视为不存在
+fn active_prompt(conn: &rusqlite::Connection, id: &str) -> Result<PromptWithTags, ApiError> {
+    let prompt = store::get_prompt(conn, id)?;
+    if prompt.deleted_at.is_some() {
+        return Err(StoreError::PromptNotFound(id.to_string()).into());
+    }
+    let tags = store::prompt_tag_names(conn, id)?;
+    Ok(PromptWithTags { prompt, tags })
+}
+
+/// GET /api/prompts?tag=&favorites=&limit=
+async fn list_prompts<R: Runtime>(
+    ApiState(context): ApiState<ApiContext<R>>,
+    Query(filter): Query<PromptFilter>,
+) -> ApiResult<Vec<PromptWithTags>> {
+    let conn = open_store(&context)?;
+    Ok(Json(store::list_prompts(&conn, &filter)?))
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchQuery {
+    q: String,
+    limit: Option<usize>,
+}
+
+/// GET /api/search?q=&limit=
+async fn search_prompts<R: Runtime>(
+    ApiState(context): ApiState<ApiContext<R>>,
+    Query(query): Query<SearchQuery>,
+) -> ApiResult<Vec<PaletteItem>> {
+    let conn = open_store(&context)?;
+    Ok(Json(palette::search(&conn, &query.q, query.limit.unwrap_or(20))?))
+}
+
+/// 提示词详情，包含展开引用后的模板变量
+#[derive(Debug, Serialize)]
+struct PromptDetail {
+    #[serde(flatten)]
+    prompt: PromptWithTags,
+    variables: Vec<TemplateVariable>,
+}
+
+/// GET /api/prompts/:id
+async fn get_prompt<R: Runtime>(
+    ApiState(context): ApiState<ApiContext<R>>,
+    Path(id): Path<String>,
+) -> ApiResult<PromptDetail> {
+    let conn = open_store(&context)?;
+    let prompt = active_prompt(&conn, &id)?;
+    let content = crate::snippets::expand(&conn, &prompt.prompt.content)
+        .map_err(|e| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+    Ok(Json(PromptDetail {
+        variables: template::variables(&content),
+        prompt,
+    }))
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RenderRequest {
+    values: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+struct RenderResponse {
+    id: String,
+    title: String,
+    text: String,
+}
+
+/// POST /api/prompts/:id/render，请求体为 `{"values": {...}}`
+async fn render_prompt<R: Runtime>(
+    ApiState(context): ApiState<ApiContext<R>>,
+    Path(id): Path<String>,
+    body: Option<Json<RenderRequest>>,
+) -> ApiResult<RenderResponse> {
+    let request = body.map(|Json(request)| request).unwrap_or_default();
+    // selection_file 会读取本地文件，只能由填写窗口的拖放提供
+    let selection_file = BuiltinKind::SelectionFile.name();
+    if request.values.contains_key(selection_file) {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!("不支持通过接口提供 {}", selection_file),
+        ));
+    }
+    let conn = open_store(&context)?;
+    let prompt = active_prompt(&conn, &id)?;
+    let content = crate::snippets::expand(&conn, &prompt.prompt.content)
+        .map_err(|e| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+    if template::uses_builtin(&content, BuiltinKind::SelectionFile) {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!("提示词使用了 {}，只能在应用的填写窗口中拖入文件后复制", selection_file),
+        ));
+    }
+    let text = template::render_stored_prompt(&context.app_handle, &conn, &id, &request.values)
+        .map_err(|e| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, e))?;
+    usage::record_usage(&conn, &id, UsageAction::Render, UsageSource::Api)?;
+    drop(conn);
+
+    crate::notify_prompts_changed(&context.app_handle, &[id]);
+    Ok(Json(RenderResponse {
+        id: prompt.prompt.id,
+        title: prompt.prompt.title,
+        text,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct NewPrompt {
+    title: String,
+    content: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// POST /api/prompts，请求体为 `{"title", "content", "tags"}`
+async fn create_prompt<R: Runtime>(
+    ApiState(context): ApiState<ApiContext<R>>,
+    Json(request): Json<NewPrompt>,
+) -> Result<(StatusCode, Json<PromptWithTags>), ApiError> {
+    let title = request.title.trim();
+    if title.is_empty() {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "标题不能为空"));
+    }
+    let conn = open_store(&context)?;
+    let prompt = store::create_prompt(&conn, title, &request.content, &request.tags)?;
+    let created = PromptWithTags {
+        tags: store::prompt_tag_names(&conn, &prompt.id)?,
+        prompt,
+    };
+    drop(conn);
+
+    crate::notify_prompts_changed(&context.app_handle, std::slice::from_ref(&created.prompt.id));
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+/// POST /api/sync
+async fn sync<R: Runtime>(ApiState(context): ApiState<ApiContext<R>>) -> ApiResult<SyncResult> {
+    feishu_sync::sync_now(&context.app_handle)
+        .await
+        .map(Json)
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))
+}
+
+/// POST /mcp，MCP streamable HTTP 传输，每个请求直接返回 JSON 响应
+async fn mcp_endpoint<R: Runtime>(
+    ApiState(context): ApiState<ApiContext<R>>,
+    Json(message): Json<serde_json::Value>,
+) -> Response {
+    let store = context.app_handle.state::<PromptStore>();
+    let app_handle = context.app_handle.clone();
+    let on_change = move |ids: &[String]| crate::notify_prompts_changed(&app_handle, ids);
+    match crate::mcp::handle_message(&store, message, &on_change) {
+        Some(response) => Json(response).into_response(),
+        // 通知和响应消息不需要回复
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
+/// 重新生成访问令牌并保存，返回新令牌
+#[tauri::command]
+pub async fn regenerate_api_token<R: Runtime>(app_handle: AppHandle<R>) -> Result<String, String> {
+    let path = crate::settings::settings_path(&app_handle)?;
+    let mut settings = AppSettings::load_from(&path)?;
+    settings.api.token = generate_token();
+    settings.save_to(&path)?;
+    restart_server(&app_handle)?;
+    Ok(settings.api.token)
+}
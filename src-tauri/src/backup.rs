@@ -0,0 +1,678 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone};
+use rusqlite::backup::Progress;
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+use thiserror::Error;
+
+use crate::migrations;
+use crate::settings::{load_app_settings, AppSettings, SETTINGS_FILE};
+use crate::store::PromptStore;
+
+/// 备份目录，位于数据库所在的配置目录下
+const BACKUP_DIR: &str = "backups";
+const FILE_PREFIX: &str = "promptgenie-";
+const FILE_EXTENSION: &str = "db";
+/// 文件名中的时间格式，精确到毫秒避免同一秒内的备份重名
+const FILE_TIME_FORMAT: &str = "%Y%m%d-%H%M%S-%3f";
+/// 自动同步可能很频繁，距离上次备份不到这个时间时不再备份
+const SYNC_MIN_INTERVAL_MINUTES: i64 = 10;
+/// 定时备份的间隔和检查频率
+const DAILY_INTERVAL_HOURS: i64 = 24;
+const SCHEDULE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+/// 迁移、恢复、修复前的备份单独保留的份数
+const SAFETY_KEEP: usize = 5;
+
+#[derive(Debug, Error)]
+pub enum BackupError {
+    #[error("文件读写失败: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("数据库操作失败: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("无效的备份文件名: {0}")]
+    InvalidName(String),
+    #[error("备份不存在: {0}")]
+    NotFound(String),
+    #[error("备份已损坏，不能用于恢复: {0}")]
+    Corrupted(String),
+    #[error("备份的数据库版本 {version} 高于应用支持的版本 {latest}，请升级 PromptGenie 后再恢复")]
+    TooNew { version: i64, latest: i64 },
+    #[error("已恢复备份，但升级数据库结构失败: {0}")]
+    Migration(String),
+}
+
+/// 备份保留策略
+///
+/// 最近的若干份全部保留，更早的每天和每周各保留最新的一份。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    pub enabled: bool,
+    /// 保留最近的备份数，至少保留 1 份
+    pub keep_recent: u32,
+    /// 最近多少天每天保留一份
+    pub keep_daily: u32,
+    /// 最近多少周每周保留一份
+    pub keep_weekly: u32,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            keep_recent: 10,
+            keep_daily: 7,
+            keep_weekly: 4,
+        }
+    }
+}
+
+/// 触发备份的原因，会写入文件名
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupReason {
+    Startup,
+    Daily,
+    Sync,
+    Import,
+    Manual,
+    /// 恢复备份前对当前数据库的备份
+    PreRestore,
+    /// 升级数据库结构前的备份
+    PreMigration,
+    /// 修复数据库前的备份
+    PreRepair,
+}
+
+impl BackupReason {
+    const ALL: [BackupReason; 8] = [
+        BackupReason::Startup,
+        BackupReason::Daily,
+        BackupReason::Sync,
+        BackupReason::Import,
+        BackupReason::Manual,
+        BackupReason::PreRestore,
+        BackupReason::PreMigration,
+        BackupReason::PreRepair,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            BackupReason::Startup => "startup",
+            BackupReason::Daily => "daily",
+            BackupReason::Sync => "sync",
+            BackupReason::Import => "import",
+            BackupReason::Manual => "manual",
+            BackupReason::PreRestore => "pre-restore",
+            BackupReason::PreMigration => "pre-migration",
+            BackupReason::PreRepair => "pre-repair",
+        }
+    }
+
+    /// 迁移、恢复、修复前的备份，出错时会提示用户从它恢复，不参与常规的轮换
+    fn is_safety(self) -> bool {
+        matches!(
+            self,
+            BackupReason::PreRestore | BackupReason::PreMigration | BackupReason::PreRepair
+        )
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|reason| reason.as_str() == value)
+    }
+}
+
+/// 一份备份
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub reason: BackupReason,
+    pub created_at: String,
+    pub size: u64,
+    #[serde(skip)]
+    pub path: PathBuf,
+    #[serde(skip)]
+    time: DateTime<Local>,
+}
+
+/// 备份的校验结果
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupVerification {
+    pub file_name: String,
+    pub ok: bool,
+    /// integrity_check 报告的问题
+    pub problems: Vec<String>,
+    /// 备份中未删除的提示词数量
+    pub prompts: Option<i64>,
+}
+
+/// 数据库所在目录下的备份目录
+pub fn backup_dir(db_path: &Path) -> PathBuf {
+    db_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(BACKUP_DIR)
+}
+
+/// 从文件名解析备份，例如 promptgenie-20250101-093000-123-startup.db
+fn parse_file(path: &Path) -> Option<BackupInfo> {
+    let file_name = path.file_name()?.to_str()?;
+    let stem = file_name
+        .strip_prefix(FILE_PREFIX)?
+        .strip_suffix(FILE_EXTENSION)?
+        .strip_suffix('.')?;
+    // 时间部分固定为 19 个字符
+    let (time, reason) = (stem.get(..19)?, stem.get(20..)?);
+    let time = NaiveDateTime::parse_from_str(time, FILE_TIME_FORMAT).ok()?;
+    let time = Local.from_local_datetime(&time).earliest()?;
+    Some(BackupInfo {
+        file_name: file_name.to_string(),
+        reason: BackupReason::parse(reason)?,
+        created_at: time.to_rfc3339(),
+        size: std::fs::metadata(path).ok()?.len(),
+        path: path.to_path_buf(),
+        time,
+    })
+}
+
+/// 列出备份，最新的在前
+pub fn list(dir: &Path) -> Result<Vec<BackupInfo>, BackupError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        if let Some(info) = parse_file(&entry?.path()) {
+            backups.push(info);
+        }
+    }
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.time));
+    Ok(backups)
+}
+
+/// 按文件名查找备份，只允许备份目录中的文件
+pub fn find(dir: &Path, file_name: &str) -> Result<BackupInfo, BackupError> {
+    if file_name.contains(['/', '\\']) || file_name.starts_with('.') {
+        return Err(BackupError::InvalidName(file_name.to_string()));
+    }
+    let path = dir.join(file_name);
+    if !path.exists() {
+        return Err(BackupError::NotFound(file_name.to_string()));
+    }
+    parse_file(&path).ok_or_else(|| BackupError::InvalidName(file_name.to_string()))
+}
+
+/// 使用 SQLite 在线备份接口生成快照，数据库文件不存在时返回 None
+///
+/// 先写入临时文件再重命名，中途失败不会留下不完整的备份。
+/// 备份切换为 DELETE 日志模式，单个文件即可完整恢复。
+pub fn snapshot(
+    db_path: &Path,
+    dir: &Path,
+    reason: BackupReason,
+) -> Result<Option<BackupInfo>, BackupError> {
+    if !db_path.exists() {
+        return Ok(None);
+    }
+    std::fs::create_dir_all(dir)?;
+    let file_name = format!(
+        "{}{}-{}.{}",
+        FILE_PREFIX,
+        Local::now().format(FILE_TIME_FORMAT),
+        reason.as_str(),
+        FILE_EXTENSION
+    );
+    let path = dir.join(&file_name);
+    let partial = dir.join(format!(".{}.partial", file_name));
+
+    let result = (|| {
+        let source = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        source.busy_timeout(std::time::Duration::from_secs(5))?;
+        source.backup(DatabaseName::Main, &partial, None::<fn(Progress)>)?;
+        let copy = Connection::open(&partial)?;
+        copy.pragma_update_and_check(None, "journal_mode", "DELETE", |row| {
+            row.get::<_, String>(0)
+        })?;
+        Ok::<_, BackupError>(())
+    })();
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e);
+    }
+    std::fs::rename(&partial, &path)?;
+    Ok(parse_file(&path))
+}
+
+/// 按保留策略删除多余的备份，返回删除的数量
+pub fn prune(dir: &Path, settings: &BackupSettings) -> Result<usize, BackupError> {
+    let backups = list(dir)?;
+    let expired = expired(&backups, settings, Local::now().date_naive());
+    for backup in &expired {
+        std::fs::remove_file(&backup.path)?;
+    }
+    Ok(expired.len())
+}
+
+/// 选出超出保留策略的备份，backups 按从新到旧排列
+///
+/// 迁移、恢复、修复前的备份只保留最近的 5 份，不占用常规备份的名额，
+/// 避免被频繁的同步备份挤掉。
+fn expired<'a>(
+    backups: &'a [BackupInfo],
+    settings: &BackupSettings,
+    today: NaiveDate,
+) -> Vec<&'a BackupInfo> {
+    let this_week = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let oldest_day = today - Duration::days(settings.keep_daily as i64 - 1);
+    let oldest_week = this_week - Duration::weeks(settings.keep_weekly as i64 - 1);
+
+    let (safety, routine): (Vec<_>, Vec<_>) = backups
+        .iter()
+        .partition(|backup| backup.reason.is_safety());
+    let mut expired: Vec<&BackupInfo> = safety.into_iter().skip(SAFETY_KEEP).collect();
+
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+    for (index, backup) in routine.into_iter().enumerate() {
+        let day = backup.time.date_naive();
+        let week = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+        // 按从新到旧的顺序，每天和每周第一次出现的就是最新的一份
+        let keep_recent = index < settings.keep_recent.max(1) as usize;
+        let keep_day = settings.keep_daily > 0 && day >= oldest_day && days.insert(day);
+        let keep_week = settings.keep_weekly > 0 && week >= oldest_week && weeks.insert(week);
+        if !(keep_recent || keep_day || keep_week) {
+            expired.push(backup);
+        }
+    }
+    expired
+}
+
+/// 检查备份能否打开、结构是否完整
+pub fn verify(backup: &BackupInfo) -> Result<BackupVerification, BackupError> {
+    let mut verification = BackupVerification {
+        file_name: backup.file_name.clone(),
+        ok: false,
+        problems: Vec::new(),
+        prompts: None,
+    };
+    let conn = Connection::open_with_flags(&backup.path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    // 不是 SQLite 文件时 integrity_check 本身会失败，作为问题报告
+    match integrity_problems(&conn) {
+        Ok(problems) => verification.problems = problems,
+        Err(e) => verification.problems.push(e.to_string()),
+    }
+    if verification.problems.is_empty() {
+        verification.prompts = conn
+            .query_row(
+                "SELECT COUNT(*) FROM prompts WHERE deleted_at IS NULL",
+                [],
+                |row| row.get(0),
+            )
+            .ok();
+        if verification.prompts.is_none() {
+            verification.problems.push("找不到提示词表".to_string());
+        }
+    }
+    verification.ok = verification.problems.is_empty();
+    Ok(verification)
+}
+
+/// PRAGMA integrity_check 报告的问题，没有问题时为空
+pub fn integrity_problems(conn: &Connection) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    Ok(rows
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|row| row != "ok")
+        .collect())
+}
+
+/// 用备份覆盖数据库，恢复前先校验备份
+///
+/// 通过在线备份接口写回，应用中其他打开的连接会直接看到恢复后的数据。
+/// 备份的数据库版本高于应用支持的版本时拒绝恢复，低于时恢复后升级到最新版本，
+/// 返回升级时执行的迁移版本。
+pub fn restore(db_path: &Path, backup: &BackupInfo) -> Result<Vec<i64>, BackupError> {
+    let verification = verify(backup)?;
+    if !verification.ok {
+        return Err(BackupError::Corrupted(verification.problems.join("; ")));
+    }
+    let version = {
+        let conn = Connection::open_with_flags(&backup.path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        migrations::stored_version(&conn)?
+    };
+    let latest = migrations::latest_version();
+    if version > latest {
+        return Err(BackupError::TooNew { version, latest });
+    }
+
+    let mut conn = Connection::open(db_path)?;
+    conn.busy_timeout(std::time::Duration::from_secs(5))?;
+    conn.restore(DatabaseName::Main, &backup.path, None::<fn(Progress)>)?;
+    if version == latest {
+        return Ok(Vec::new());
+    }
+    migrations::migrate_to(&mut conn, latest).map_err(|e| BackupError::Migration(e.to_string()))
+}
+
+/// 读取数据库所在配置目录中的备份设置，不依赖 Tauri，命令行同步时也能使用
+fn settings_for(db_path: &Path) -> BackupSettings {
+    let Some(config_dir) = db_path.parent() else {
+        return BackupSettings::default();
+    };
+    match AppSettings::load_from(&config_dir.join(SETTINGS_FILE)) {
+        Ok(settings) => settings.backup,
+        Err(e) => {
+            eprintln!("加载备份设置失败，使用默认设置: {}", e);
+            BackupSettings::default()
+        }
+    }
+}
+
+/// 备份并按保留策略清理，未开启备份时返回 None
+fn backup_with_settings(
+    store: &PromptStore,
+    settings: &BackupSettings,
+    reason: BackupReason,
+) -> Result<Option<BackupInfo>, BackupError> {
+    if !settings.enabled {
+        return Ok(None);
+    }
+    let dir = backup_dir(store.db_path());
+    let backup = snapshot(store.db_path(), &dir, reason)?;
+    if let Some(backup) = &backup {
+        println!("已备份数据库: {}", backup.path.display());
+    }
+    match prune(&dir, settings) {
+        Ok(removed) if removed > 0 => println!("已清理 {} 份旧备份", removed),
+        Ok(_) => {}
+        Err(e) => eprintln!("清理旧备份失败: {}", e),
+    }
+    Ok(backup)
+}
+
+/// 同步或导入前备份，失败时返回错误，调用方应取消后续操作
+///
+/// 自动同步可能很频繁，距离上次备份不到 10 分钟时跳过。
+pub fn before_change(store: &PromptStore, reason: BackupReason) -> Result<(), String> {
+    let settings = settings_for(store.db_path());
+    if reason == BackupReason::Sync && settings.enabled {
+        let latest = list(&backup_dir(store.db_path()))
+            .ok()
+            .and_then(|backups| backups.into_iter().next());
+        if latest.is_some_and(|latest| {
+            Local::now() - latest.time < Duration::minutes(SYNC_MIN_INTERVAL_MINUTES)
+        }) {
+            return Ok(());
+        }
+    }
+    backup_with_settings(store, &settings, reason)
+        .map(|_| ())
+        .map_err(|e| format!("备份数据库失败，已取消操作: {}", e))
+}
+
+/// 启动时备份一次，之后每天备份一次
+pub fn start<R: Runtime>(app_handle: &AppHandle<R>) {
+    let settings = load_app_settings(app_handle).backup;
+    let store = app_handle.state::<PromptStore>();
+    if let Err(e) = backup_with_settings(&store, &settings, BackupReason::Startup) {
+        eprintln!("启动时备份数据库失败: {}", e);
+    }
+
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SCHEDULE_CHECK_INTERVAL);
+
+        let settings = load_app_settings(&app_handle).backup;
+        let store = app_handle.state::<PromptStore>();
+        let latest = list(&backup_dir(store.db_path()))
+            .ok()
+            .and_then(|backups| backups.into_iter().next());
+        if latest.is_some_and(|latest| {
+            Local::now() - latest.time < Duration::hours(DAILY_INTERVAL_HOURS)
+        }) {
+            continue;
+        }
+        if let Err(e) = backup_with_settings(&store, &settings, BackupReason::Daily) {
+            eprintln!("定时备份数据库失败: {}", e);
+        }
+    });
+}
+
+/// 按保存后的设置清理旧备份
+pub fn prune_with_settings<R: Runtime>(app_handle: &AppHandle<R>) {
+    let settings = load_app_settings(app_handle).backup;
+    let store = app_handle.state::<PromptStore>();
+    match prune(&backup_dir(store.db_path()), &settings) {
+        Ok(removed) if removed > 0 => println!("已清理 {} 份旧备份", removed),
+        Ok(_) => {}
+        Err(e) => eprintln!("清理旧备份失败: {}", e),
+    }
+}
+
+/// 获取备份列表，最新的在前
+#[tauri::command]
+pub async fn list_backups<R: Runtime>(app_handle: AppHandle<R>) -> Result<Vec<BackupInfo>, String> {
+    let store = app_handle.state::<PromptStore>();
+    list(&backup_dir(store.db_path())).map_err(|e| format!("获取备份列表失败: {}", e))
+}
+
+/// 立即备份，不受“启用自动备份”设置影响
+#[tauri::command]
+pub async fn create_backup<R: Runtime>(app_handle: AppHandle<R>) -> Result<BackupInfo, String> {
+    let store = app_handle.state::<PromptStore>();
+    let settings = BackupSettings {
+        enabled: true,
+        ..load_app_settings(&app_handle).backup
+    };
+    backup_with_settings(&store, &settings, BackupReason::Manual)
+        .map_err(|e| format!("备份失败: {}", e))?
+        .ok_or_else(|| "数据库文件不存在".to_string())
+}
+
+/// 校验备份
+#[tauri::command]
+pub async fn verify_backup<R: Runtime>(
+    app_handle: AppHandle<R>,
+    file_name: String,
+) -> Result<BackupVerification, String> {
+    let store = app_handle.state::<PromptStore>();
+    let backup = find(&backup_dir(store.db_path()), &file_name)
+        .map_err(|e| format!("校验备份失败: {}", e))?;
+    verify(&backup).map_err(|e| format!("校验备份失败: {}", e))
+}
+
+/// 从备份恢复，恢复前会先备份当前数据库，返回这份恢复前的备份
+#[tauri::command]
+pub async fn restore_backup<R: Runtime>(
+    app_handle: AppHandle<R>,
+    file_name: String,
+) -> Result<Option<BackupInfo>, String> {
+    let store = app_handle.state::<PromptStore>();
+    let dir = backup_dir(store.db_path());
+    let backup = find(&dir, &file_name).map_err(|e| format!("恢复备份失败: {}", e))?;
+    let previous = snapshot(store.db_path(), &dir, BackupReason::PreRestore)
+        .map_err(|e| format!("恢复前备份当前数据库失败，已取消恢复: {}", e))?;
+
+    let applied = restore(store.db_path(), &backup).map_err(|e| format!("恢复备份失败: {}", e))?;
+    println!("已从备份恢复数据库: {}", backup.path.display());
+    if let Some(to) = applied.last() {
+        println!("恢复的数据库已升级到版本 {}", to);
+    }
+    crate::notify_prompts_changed(&app_handle, &[]);
+    Ok(previous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backup(reason: BackupReason, year: i32, month: u32, day: u32, hour: u32) -> BackupInfo {
+        let time = Local
+            .with_ymd_and_hms(year, month, day, hour, 0, 0)
+            .earliest()
+            .unwrap();
+        let file_name = format!(
+            "{}{}-{}.{}",
+            FILE_PREFIX,
+            time.format(FILE_TIME_FORMAT),
+            reason.as_str(),
+            FILE_EXTENSION
+        );
+        BackupInfo {
+            path: PathBuf::from(&file_name),
+            file_name,
+            reason,
+            created_at: time.to_rfc3339(),
+            size: 0,
+            time,
+        }
+    }
+
+    fn settings(keep_recent: u32, keep_daily: u32, keep_weekly: u32) -> BackupSettings {
+        BackupSettings {
+            enabled: true,
+            keep_recent,
+            keep_daily,
+            keep_weekly,
+        }
+    }
+
+    fn expired_names(backups: &[BackupInfo], settings: &BackupSettings) -> Vec<String> {
+        // 2025-01-15 是星期三，本周从 2025-01-13 开始
+        let today = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        expired(backups, settings, today)
+            .into_iter()
+            .map(|backup| backup.file_name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn keeps_most_recent_backups() {
+        let backups: Vec<_> = (0..4)
+            .map(|hour| backup(BackupReason::Sync, 2025, 1, 15, 12 - hour))
+            .collect();
+        assert_eq!(
+            expired_names(&backups, &settings(2, 0, 0)),
+            vec![backups[2].file_name.clone(), backups[3].file_name.clone()]
+        );
+    }
+
+    #[test]
+    fn keeps_newest_backup_of_each_recent_day() {
+        let backups = vec![
+            backup(BackupReason::Sync, 2025, 1, 15, 12),
+            backup(BackupReason::Sync, 2025, 1, 14, 18),
+            backup(BackupReason::Sync, 2025, 1, 14, 9),
+            backup(BackupReason::Daily, 2025, 1, 13, 12),
+            // 超出最近 3 天
+            backup(BackupReason::Daily, 2025, 1, 12, 12),
+        ];
+        assert_eq!(
+            expired_names(&backups, &settings(1, 3, 0)),
+            vec![backups[2].file_name.clone(), backups[4].file_name.clone()]
+        );
+    }
+
+    #[test]
+    fn keeps_newest_backup_of_each_recent_week() {
+        let backups = vec![
+            backup(BackupReason::Daily, 2025, 1, 15, 12),
+            backup(BackupReason::Daily, 2025, 1, 14, 12),
+            // 上一周（从 2025-01-06 开始）
+            backup(BackupReason::Daily, 2025, 1, 8, 12),
+            backup(BackupReason::Daily, 2025, 1, 6, 12),
+            // 超出最近 2 周
+            backup(BackupReason::Daily, 2024, 12, 31, 12),
+        ];
+        assert_eq!(
+            expired_names(&backups, &settings(1, 0, 2)),
+            vec![
+                backups[1].file_name.clone(),
+                backups[3].file_name.clone(),
+                backups[4].file_name.clone(),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_only_recent_backups_without_daily_and_weekly() {
+        let backups = vec![
+            backup(BackupReason::Daily, 2025, 1, 15, 12),
+            backup(BackupReason::Daily, 2025, 1, 14, 12),
+            backup(BackupReason::Daily, 2025, 1, 13, 12),
+        ];
+        assert_eq!(
+            expired_names(&backups, &settings(0, 0, 0)),
+            vec![backups[1].file_name.clone(), backups[2].file_name.clone()]
+        );
+    }
+
+    #[test]
+    fn safety_backups_are_not_rotated_by_sync_backups() {
+        let mut backups: Vec<_> = (0..12)
+            .map(|hour| backup(BackupReason::Sync, 2025, 1, 15, 23 - hour))
+            .collect();
+        backups.push(backup(BackupReason::PreMigration, 2025, 1, 15, 1));
+        let expired = expired_names(&backups, &settings(10, 0, 0));
+        assert_eq!(expired.len(), 2);
+        assert!(!expired.contains(&backups[12].file_name));
+
+        let safety: Vec<_> = (0..7)
+            .map(|day| backup(BackupReason::PreRepair, 2025, 1, 15 - day, 12))
+            .collect();
+        assert_eq!(
+            expired_names(&safety, &settings(10, 7, 4)),
+            vec![safety[5].file_name.clone(), safety[6].file_name.clone()]
+        );
+    }
+
+    #[test]
+    fn restore_rejects_newer_schema() {
+        let dir = std::env::temp_dir().join(format!("promptgenie-test-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("promptgenie.db");
+        Connection::open(&db_path)
+            .unwrap()
+            .execute_batch("CREATE TABLE prompts (id TEXT PRIMARY KEY, deleted_at TEXT)")
+            .unwrap();
+
+        let latest = migrations::latest_version();
+        let backup_path = dir.join("promptgenie-20250101-120000-000-manual.db");
+        let conn = Connection::open(&backup_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE prompts (id TEXT PRIMARY KEY, deleted_at TEXT);
+             CREATE TABLE schema_version (
+               version INTEGER PRIMARY KEY,
+               description TEXT NOT NULL,
+               applied_at TEXT NOT NULL
+             );
+             INSERT INTO prompts (id) VALUES ('from-backup');",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, 'future', '')",
+            [latest + 1],
+        )
+        .unwrap();
+        drop(conn);
+
+        let info = find(&dir, "promptgenie-20250101-120000-000-manual.db").unwrap();
+        let result = restore(&db_path, &info);
+        assert!(matches!(
+            result,
+            Err(BackupError::TooNew { version, latest: l }) if version == latest + 1 && l == latest
+        ));
+        let count: i64 = Connection::open(&db_path)
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM prompts", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,4 @@
+// 不启动 Tauri 的命令行工具，供服务器和 CI 使用
+fn main() {
+    std::process::exit(prompt_genie_lib::headless::run())
+}
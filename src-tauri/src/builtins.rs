@@ -0,0 +1,192 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::template::TemplateError;
+
+// 内置动态变量，渲染时自动填充，冒号后为格式参数：
+//
+//   {{date}} {{date:%Y年%m月%d日}}       当前日期（chrono 格式，默认 %Y-%m-%d）
+//   {{time}} {{time:%H:%M}}              当前时间（默认 %H:%M:%S）
+//   {{clipboard}} {{clipboard:code}}     当前剪贴板文本
+//   {{selection_file}} {{selection_file:name}} 拖入的文件内容
+//   {{uuid}} {{uuid:v4}}                 新生成的 UUID（默认 v7）
+//
+// 剪贴板和文件支持的格式：trim、upper、lower、oneline、quote、code、code(语言)，
+// 文件额外支持 name（文件名）和 path（完整路径）。
+
+/// 拖入文件的最大读取大小
+const MAX_SELECTION_FILE_SIZE: u64 = 1024 * 1024;
+
+/// 内置变量类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinKind {
+    Date,
+    Time,
+    Clipboard,
+    SelectionFile,
+    Uuid,
+}
+
+impl BuiltinKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "date" => Some(Self::Date),
+            "time" => Some(Self::Time),
+            "clipboard" => Some(Self::Clipboard),
+            "selection_file" => Some(Self::SelectionFile),
+            "uuid" => Some(Self::Uuid),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Date => "date",
+            Self::Time => "time",
+            Self::Clipboard => "clipboard",
+            Self::SelectionFile => "selection_file",
+            Self::Uuid => "uuid",
+        }
+    }
+}
+
+/// 模板中的内置变量
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuiltinVariable {
+    pub kind: BuiltinKind,
+    pub format: Option<String>,
+    pub default: Option<String>,
+}
+
+/// 渲染内置变量时使用的外部数据
+#[derive(Debug, Clone)]
+pub struct BuiltinContext {
+    pub now: DateTime<Local>,
+    pub clipboard: Option<String>,
+    /// 只来自填写窗口的拖放，不能取自调用方提交的变量值
+    pub selection_file: Option<String>,
+}
+
+impl Default for BuiltinContext {
+    fn default() -> Self {
+        Self {
+            now: Local::now(),
+            clipboard: None,
+            selection_file: None,
+        }
+    }
+}
+
+/// 计算内置变量的值，无法取得时使用默认值
+pub fn evaluate(
+    variable: &BuiltinVariable,
+    context: &BuiltinContext,
+) -> Result<String, TemplateError> {
+    let name = variable.kind.name();
+    let format = variable.format.as_deref().map(str::trim).filter(|f| !f.is_empty());
+
+    let value = match variable.kind {
+        BuiltinKind::Date => Some(format_time(context, name, format.unwrap_or("%Y-%m-%d"))?),
+        BuiltinKind::Time => Some(format_time(context, name, format.unwrap_or("%H:%M:%S"))?),
+        BuiltinKind::Uuid => Some(format_uuid(name, format)?),
+        BuiltinKind::Clipboard => match context.clipboard.as_deref().filter(|t| !t.is_empty()) {
+            Some(text) => Some(format_text(name, text, format)?),
+            None => None,
+        },
+        BuiltinKind::SelectionFile => match context.selection_file.as_deref() {
+            Some(path) => Some(format_file(name, Path::new(path), format)?),
+            None => None,
+        },
+    };
+
+    value
+        .or_else(|| variable.default.clone())
+        .ok_or_else(|| TemplateError::BuiltinUnavailable(name.to_string()))
+}
+
+fn format_time(
+    context: &BuiltinContext,
+    name: &str,
+    format: &str,
+) -> Result<String, TemplateError> {
+    // 非法的格式说明符在 Display 时会 panic，这里提前检查
+    if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        return Err(invalid_format(name, format));
+    }
+    Ok(context.now.format(format).to_string())
+}
+
+fn format_uuid(name: &str, format: Option<&str>) -> Result<String, TemplateError> {
+    let uuid = match format {
+        None | Some("v7") => Uuid::now_v7().to_string(),
+        Some("v4") => Uuid::new_v4().to_string(),
+        Some("simple") => Uuid::now_v7().simple().to_string(),
+        Some("upper") => Uuid::now_v7().to_string().to_uppercase(),
+        Some(other) => return Err(invalid_format(name, other)),
+    };
+    Ok(uuid)
+}
+
+fn format_text(name: &str, text: &str, format: Option<&str>) -> Result<String, TemplateError> {
+    let Some(format) = format else {
+        return Ok(text.to_string());
+    };
+
+    let formatted = match format {
+        "trim" => text.trim().to_string(),
+        "upper" => text.to_uppercase(),
+        "lower" => text.to_lowercase(),
+        "oneline" => text.split_whitespace().collect::<Vec<_>>().join(" "),
+        "quote" => text
+            .lines()
+            .map(|line| format!("> {}", line))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        "code" => format!("```\n{}\n```", text.trim_end_matches('\n')),
+        _ => match format
+            .strip_prefix("code(")
+            .and_then(|lang| lang.strip_suffix(')'))
+        {
+            Some(lang) => format!("```{}\n{}\n```", lang.trim(), text.trim_end_matches('\n')),
+            None => return Err(invalid_format(name, format)),
+        },
+    };
+    Ok(formatted)
+}
+
+fn format_file(name: &str, path: &Path, format: Option<&str>) -> Result<String, TemplateError> {
+    match format {
+        Some("name") => {
+            return Ok(path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default())
+        }
+        Some("path") => return Ok(path.to_string_lossy().to_string()),
+        _ => {}
+    }
+
+    let read_failed = |reason: String| TemplateError::ReadFileFailed {
+        path: path.to_string_lossy().to_string(),
+        reason,
+    };
+    let metadata = std::fs::metadata(path).map_err(|e| read_failed(e.to_string()))?;
+    if metadata.len() > MAX_SELECTION_FILE_SIZE {
+        return Err(read_failed(format!(
+            "文件超过 {} KB",
+            MAX_SELECTION_FILE_SIZE / 1024
+        )));
+    }
+    let bytes = std::fs::read(path).map_err(|e| read_failed(e.to_string()))?;
+    let text = String::from_utf8(bytes).map_err(|_| read_failed("不是 UTF-8 文本文件".to_string()))?;
+    format_text(name, &text, format)
+}
+
+fn invalid_format(name: &str, format: &str) -> TemplateError {
+    TemplateError::InvalidFormat {
+        name: name.to_string(),
+        format: format.to_string(),
+    }
+}
@@ -0,0 +1,771 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_dialog::DialogExt;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::backup::{self, BackupReason};
+use crate::library::{self, DraftPrompt, ImportReport, ImportStrategy, LibraryError};
+use crate::markdown;
+use crate::snippets;
+use crate::store::{self, now_timestamp, PromptStore, StoreError};
+use crate::template::{self, TemplateVariable};
+
+/// 分享文件的扩展名，与 tauri.conf.json 中 bundle.fileAssociations 一致
+pub const BUNDLE_EXTENSION: &str = "pgenie";
+/// 分享文件的格式标识和版本，结构变化时递增版本并在导入时兼容旧版本
+pub const BUNDLE_FORMAT: &str = "promptgenie-bundle";
+pub const BUNDLE_VERSION: u32 = 1;
+/// 本机签名密钥，保存在配置目录中
+const SIGNING_KEY_FILE: &str = "bundle_signing_key";
+/// 解压后允许的最大字节数，避免构造的压缩包占满内存
+const MAX_BUNDLE_BYTES: u64 = 16 * 1024 * 1024;
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+#[derive(Debug, Error)]
+pub enum BundleError {
+    #[error("文件读写失败: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON 格式错误: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{0}")]
+    Store(#[from] StoreError),
+    #[error("数据库操作失败: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("{0}")]
+    Library(#[from] LibraryError),
+    #[error("不是有效的 .pgenie 文件")]
+    NotBundle,
+    #[error("不是 PromptGenie 分享文件（format: {0}）")]
+    UnknownFormat(String),
+    #[error("分享文件版本 {0} 高于当前支持的版本 {BUNDLE_VERSION}，请升级应用")]
+    UnsupportedVersion(u32),
+    #[error("分享文件解压后超过 {} MB", MAX_BUNDLE_BYTES / 1024 / 1024)]
+    TooLarge,
+    #[error("签名校验失败，文件可能被修改过")]
+    InvalidSignature,
+    #[error("签名密钥不可用: {0}")]
+    SigningKey(String),
+    #[error("分享文件中没有提示词")]
+    Empty,
+}
+
+/// .pgenie 文件的外层结构，整体使用 gzip 压缩
+#[derive(Debug, Serialize, Deserialize)]
+struct BundleFile {
+    format: String,
+    version: u32,
+    /// BundleContent 的 JSON 文本，签名针对这段文本的字节
+    payload: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<BundleSignature>,
+}
+
+/// Ed25519 签名，公钥和签名都使用 base64 编码
+#[derive(Debug, Serialize, Deserialize)]
+struct BundleSignature {
+    public_key: String,
+    signature: String,
+}
+
+/// 分享的内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleContent {
+    pub created_at: String,
+    pub prompts: Vec<BundlePrompt>,
+    /// 提示词通过 {{> 名称}} 引用的片段，包括间接引用的
+    #[serde(default)]
+    pub snippets: Vec<BundleSnippet>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundlePrompt {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 展开片段后的变量定义，供导入前预览
+    #[serde(default)]
+    pub variables: Vec<TemplateVariable>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleSnippet {
+    pub name: String,
+    pub content: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// 解码后的分享文件
+#[derive(Debug, Clone)]
+pub struct DecodedBundle {
+    pub content: BundleContent,
+    /// 签名者的公钥，未签名时为 None
+    pub signer: Option<VerifyingKey>,
+}
+
+/// 片段与本地的对比
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnippetStatus {
+    /// 本地没有，导入时新建
+    New,
+    /// 本地有同名且内容相同的
+    Same,
+    /// 本地有同名但内容不同的，导入时保留本地的
+    Conflict,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SnippetPreview {
+    pub name: String,
+    pub status: SnippetStatus,
+}
+
+/// 导入前的预览
+#[derive(Debug, Clone, Serialize)]
+pub struct BundlePreview {
+    pub path: String,
+    pub created_at: String,
+    pub prompts: Vec<BundlePrompt>,
+    /// 本地已存在的提示词数量，按导入策略处理
+    pub existing: usize,
+    pub snippets: Vec<SnippetPreview>,
+    /// 签名者的指纹，未签名时为 None
+    ///
+    /// 公钥随文件附带，指纹未经验证，只能由用户自行与发送者核对。
+    pub signer: Option<String>,
+    /// 是否由本机签名
+    pub signed_by_me: bool,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleImportReport {
+    #[serde(flatten)]
+    pub prompts: ImportReport,
+    pub snippets_created: u32,
+    /// 与本地同名但内容不同、保留了本地版本的片段
+    pub snippets_kept: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleExportSummary {
+    pub path: String,
+    pub prompts: usize,
+    pub snippets: usize,
+    pub signed: bool,
+}
+
+/// 等待前端加载完成后预览的分享文件（双击文件启动应用时前端还没有监听事件）
+#[derive(Default)]
+pub struct PendingBundle(Mutex<Option<String>>);
+
+/// 公钥指纹，取前 8 个字节，例如 1A2B-3C4D-5E6F-7A8B
+pub fn fingerprint(key: &VerifyingKey) -> String {
+    key.as_bytes()[..8]
+        .chunks(2)
+        .map(|pair| format!("{:02X}{:02X}", pair[0], pair[1]))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// 按名称查找引用：优先使用片段，找不到时把引用的提示词作为同名片段带上
+fn find_snippet(conn: &Connection, name: &str) -> Result<Option<BundleSnippet>, StoreError> {
+    let snippet = conn
+        .query_row(
+            "SELECT content, description FROM snippets WHERE name = ?1",
+            params![name],
+            |row| {
+                Ok(BundleSnippet {
+                    name: name.to_string(),
+                    content: row.get(0)?,
+                    description: row.get(1)?,
+                })
+            },
+        )
+        .optional()?;
+    if snippet.is_some() {
+        return Ok(snippet);
+    }
+    Ok(snippets::resolve(conn, name)?.map(|content| BundleSnippet {
+        name: name.to_string(),
+        content,
+        description: String::new(),
+    }))
+}
+
+/// 收集提示词和它们引用的片段，回收站中的提示词不能分享
+pub fn build_content(
+    conn: &Connection,
+    prompt_ids: &[String],
+) -> Result<BundleContent, BundleError> {
+    let mut prompts = Vec::with_capacity(prompt_ids.len());
+    for id in prompt_ids {
+        let prompt = store::get_prompt(conn, id)?;
+        if prompt.deleted_at.is_some() {
+            return Err(StoreError::PromptNotFound(id.clone()).into());
+        }
+        // 引用缺失或循环时仍然可以分享，只是变量定义取自未展开的内容
+        let expanded =
+            snippets::expand(conn, &prompt.content).unwrap_or_else(|_| prompt.content.clone());
+        prompts.push(BundlePrompt {
+            tags: store::prompt_tag_names(conn, id)?,
+            variables: template::variables(&expanded),
+            id: prompt.id,
+            title: prompt.title,
+            content: prompt.content,
+            created_at: prompt.created_at,
+            updated_at: prompt.updated_at,
+        });
+    }
+    if prompts.is_empty() {
+        return Err(BundleError::Empty);
+    }
+
+    // 引用同一文件中的提示词时不需要另外带上
+    let mut seen: HashSet<String> = prompts
+        .iter()
+        .flat_map(|prompt| [prompt.id.clone(), prompt.title.clone()])
+        .collect();
+    let mut pending: Vec<String> = prompts
+        .iter()
+        .flat_map(|prompt| template::includes(&prompt.content))
+        .collect();
+    let mut bundled = Vec::new();
+    while let Some(name) = pending.pop() {
+        if !seen.insert(name.clone()) {
+            continue;
+        }
+        if let Some(snippet) = find_snippet(conn, &name)? {
+            pending.extend(template::includes(&snippet.content));
+            bundled.push(snippet);
+        }
+    }
+    bundled.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(BundleContent {
+        created_at: now_timestamp(),
+        prompts,
+        snippets: bundled,
+    })
+}
+
+/// 生成 .pgenie 文件内容，提供密钥时附带签名
+pub fn encode(content: &BundleContent, key: Option<&SigningKey>) -> Result<Vec<u8>, BundleError> {
+    let payload = serde_json::to_string(content)?;
+    let signature = key.map(|key| BundleSignature {
+        public_key: STANDARD.encode(key.verifying_key().as_bytes()),
+        signature: STANDARD.encode(key.sign(payload.as_bytes()).to_bytes()),
+    });
+    let file = BundleFile {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        payload,
+        signature,
+    };
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, &file)?;
+    Ok(encoder.finish()?)
+}
+
+fn verify(payload: &str, signature: &BundleSignature) -> Result<VerifyingKey, BundleError> {
+    let public_key: [u8; 32] = STANDARD
+        .decode(&signature.public_key)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(BundleError::InvalidSignature)?;
+    let signature: [u8; 64] = STANDARD
+        .decode(&signature.signature)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(BundleError::InvalidSignature)?;
+    let key = VerifyingKey::from_bytes(&public_key).map_err(|_| BundleError::InvalidSignature)?;
+    key.verify(payload.as_bytes(), &Signature::from_bytes(&signature))
+        .map_err(|_| BundleError::InvalidSignature)?;
+    Ok(key)
+}
+
+/// 解压并校验 .pgenie 文件，签名无效时拒绝导入
+pub fn decode(bytes: &[u8]) -> Result<DecodedBundle, BundleError> {
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return Err(BundleError::NotBundle);
+    }
+    let mut json = Vec::new();
+    GzDecoder::new(bytes)
+        .take(MAX_BUNDLE_BYTES + 1)
+        .read_to_end(&mut json)
+        .map_err(|_| BundleError::NotBundle)?;
+    if json.len() as u64 > MAX_BUNDLE_BYTES {
+        return Err(BundleError::TooLarge);
+    }
+
+    let file: BundleFile = serde_json::from_slice(&json)?;
+    if file.format != BUNDLE_FORMAT {
+        return Err(BundleError::UnknownFormat(file.format));
+    }
+    if file.version > BUNDLE_VERSION {
+        return Err(BundleError::UnsupportedVersion(file.version));
+    }
+    let signer = file
+        .signature
+        .as_ref()
+        .map(|signature| verify(&file.payload, signature))
+        .transpose()?;
+
+    let content: BundleContent = serde_json::from_str(&file.payload)?;
+    if content.prompts.is_empty() {
+        return Err(BundleError::Empty);
+    }
+    Ok(DecodedBundle { content, signer })
+}
+
+fn local_snippet(conn: &Connection, name: &str) -> Result<Option<String>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT content FROM snippets WHERE name = ?1",
+        params![name],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+fn snippet_status(
+    conn: &Connection,
+    snippet: &BundleSnippet,
+) -> Result<SnippetStatus, BundleError> {
+    Ok(match local_snippet(conn, &snippet.name)? {
+        None => SnippetStatus::New,
+        Some(content) if content == snippet.content => SnippetStatus::Same,
+        Some(_) => SnippetStatus::Conflict,
+    })
+}
+
+/// 对比本地数据生成预览，不修改数据库
+pub fn preview(
+    conn: &Connection,
+    path: &Path,
+    bundle: DecodedBundle,
+    own_key: Option<&VerifyingKey>,
+) -> Result<BundlePreview, BundleError> {
+    let mut existing = 0;
+    for prompt in &bundle.content.prompts {
+        let found: Option<i64> = conn
+            .query_row(
+                "SELECT 1 FROM prompts WHERE id = ?1",
+                params![prompt.id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        existing += usize::from(found.is_some());
+    }
+    let snippets = bundle
+        .content
+        .snippets
+        .iter()
+        .map(|snippet| {
+            Ok(SnippetPreview {
+                name: snippet.name.clone(),
+                status: snippet_status(conn, snippet)?,
+            })
+        })
+        .collect::<Result<Vec<_>, BundleError>>()?;
+
+    Ok(BundlePreview {
+        path: path.to_string_lossy().into_owned(),
+        created_at: bundle.content.created_at,
+        prompts: bundle.content.prompts,
+        existing,
+        snippets,
+        signed_by_me: bundle.signer.is_some() && bundle.signer.as_ref() == own_key,
+        signer: bundle.signer.as_ref().map(fingerprint),
+    })
+}
+
+/// 导入分享的内容：片段只新建，同名的保留本地版本；提示词按 JSON 导入的规则合并
+///
+/// 片段和提示词在同一个事务中导入，任何一步失败都不会留下部分数据。
+pub fn import_content(
+    conn: &Connection,
+    content: &BundleContent,
+    strategy: ImportStrategy,
+) -> Result<BundleImportReport, BundleError> {
+    let mut snippets_created = 0;
+    let mut snippets_kept = Vec::new();
+    let tx = conn.unchecked_transaction()?;
+    let now = now_timestamp();
+    for snippet in &content.snippets {
+        match snippet_status(&tx, snippet)? {
+            SnippetStatus::New => {
+                tx.execute(
+                    "INSERT INTO snippets (id, name, content, description, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+                    params![
+                        Uuid::now_v7().to_string(),
+                        snippet.name,
+                        snippet.content,
+                        snippet.description,
+                        now
+                    ],
+                )?;
+                snippets_created += 1;
+            }
+            SnippetStatus::Same => {}
+            SnippetStatus::Conflict => snippets_kept.push(snippet.name.clone()),
+        }
+    }
+
+    let drafts: Vec<DraftPrompt> = content
+        .prompts
+        .iter()
+        .map(|prompt| DraftPrompt {
+            id: Some(prompt.id.clone()),
+            title: prompt.title.clone(),
+            content: prompt.content.clone(),
+            tags: prompt.tags.clone(),
+            is_favorite: false,
+            created_at: Some(prompt.created_at.clone()),
+            updated_at: Some(prompt.updated_at.clone()),
+        })
+        .collect();
+    let document = library::document_from_drafts(&drafts);
+    let prompts = library::merge_document(&tx, &document, strategy)?;
+    tx.commit()?;
+
+    Ok(BundleImportReport {
+        prompts,
+        snippets_created,
+        snippets_kept,
+    })
+}
+
+/// 读取已有的签名密钥，还没有生成过时返回 None
+fn read_signing_key(path: &Path) -> Result<Option<SigningKey>, BundleError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let seed: [u8; 32] = STANDARD
+        .decode(std::fs::read_to_string(path)?.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| BundleError::SigningKey(format!("{} 内容无效", path.display())))?;
+    Ok(Some(SigningKey::from_bytes(&seed)))
+}
+
+/// 读取本机的签名密钥，第一次使用时生成
+///
+/// 密钥文件只允许当前用户读写，避免同一台电脑上的其他用户冒用签名。
+fn load_signing_key(path: &Path) -> Result<SigningKey, BundleError> {
+    if let Some(key) = read_signing_key(path)? {
+        return Ok(key);
+    }
+
+    let mut seed = [0u8; 32];
+    getrandom::getrandom(&mut seed).map_err(|e| BundleError::SigningKey(e.to_string()))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)?
+        .write_all(STANDARD.encode(seed).as_bytes())?;
+    println!("已生成分享签名密钥: {}", path.display());
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+fn signing_key_path<R: Runtime>(app_handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    let config_dir = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("获取配置目录失败: {}", e))?;
+    Ok(config_dir.join(SIGNING_KEY_FILE))
+}
+
+fn signing_key<R: Runtime>(app_handle: &AppHandle<R>) -> Result<SigningKey, String> {
+    load_signing_key(&signing_key_path(app_handle)?).map_err(|e| e.to_string())
+}
+
+/// 从命令行参数中找出 .pgenie 文件，相对路径按启动时的工作目录解析
+pub fn path_from_args(argv: &[String], cwd: &Path) -> Option<PathBuf> {
+    argv.iter()
+        .skip(1)
+        .map(Path::new)
+        .find(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case(BUNDLE_EXTENSION))
+        })
+        .map(|path| cwd.join(path))
+}
+
+/// 打开 .pgenie 文件：显示主窗口，由前端预览后确认导入
+pub fn open_file<R: Runtime>(app_handle: &AppHandle<R>, path: &Path) {
+    println!("打开分享文件: {}", path.display());
+    let Some(window) = app_handle.get_webview_window("main") else {
+        eprintln!("找不到主窗口");
+        return;
+    };
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+
+    // 前端收到事件后通过 take_pending_bundle 取出，还没加载完成时则在加载后取出
+    let path = path.to_string_lossy().into_owned();
+    *app_handle.state::<PendingBundle>().0.lock().unwrap() = Some(path.clone());
+    if let Err(e) = window.emit("open-bundle", path) {
+        eprintln!("打开分享文件失败: {}", e);
+    }
+}
+
+/// 导出文件的默认名称：单条时使用标题，多条时使用日期
+fn default_file_name(content: &BundleContent) -> String {
+    match content.prompts.as_slice() {
+        [prompt] => format!(
+            "{}.{}",
+            markdown::slugify_title(&prompt.title),
+            BUNDLE_EXTENSION
+        ),
+        _ => format!(
+            "promptgenie-{}.{}",
+            chrono::Local::now().format("%Y%m%d"),
+            BUNDLE_EXTENSION
+        ),
+    }
+}
+
+/// 把一条或多条提示词导出为 .pgenie 文件，未指定路径时弹出保存对话框，取消时返回 None
+#[tauri::command]
+pub async fn export_bundle<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+    prompt_ids: Vec<String>,
+    path: Option<String>,
+    sign: bool,
+) -> Result<Option<BundleExportSummary>, String> {
+    let content = {
+        let conn = store.open().map_err(|e| e.to_string())?;
+        build_content(&conn, &prompt_ids).map_err(|e| format!("导出失败: {}", e))?
+    };
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => match app_handle
+            .dialog()
+            .file()
+            .add_filter("PromptGenie", &[BUNDLE_EXTENSION])
+            .set_file_name(default_file_name(&content))
+            .blocking_save_file()
+            .and_then(|path| path.into_path().ok())
+        {
+            Some(path) => path,
+            None => return Ok(None),
+        },
+    };
+
+    let key = if sign {
+        Some(signing_key(&app_handle)?)
+    } else {
+        None
+    };
+    let bytes = encode(&content, key.as_ref()).map_err(|e| format!("导出失败: {}", e))?;
+    std::fs::write(&path, bytes).map_err(|e| format!("导出失败: {}", e))?;
+    println!(
+        "已导出 {} 条提示词到 {}",
+        content.prompts.len(),
+        path.display()
+    );
+    Ok(Some(BundleExportSummary {
+        path: path.to_string_lossy().into_owned(),
+        prompts: content.prompts.len(),
+        snippets: content.snippets.len(),
+        signed: sign,
+    }))
+}
+
+/// 预览 .pgenie 文件，未指定路径时弹出打开对话框，取消时返回 None
+#[tauri::command]
+pub async fn preview_bundle<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+    path: Option<String>,
+) -> Result<Option<BundlePreview>, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => match app_handle
+            .dialog()
+            .file()
+            .add_filter("PromptGenie", &[BUNDLE_EXTENSION])
+            .blocking_pick_file()
+            .and_then(|path| path.into_path().ok())
+        {
+            Some(path) => path,
+            None => return Ok(None),
+        },
+    };
+
+    let bytes = std::fs::read(&path).map_err(|e| format!("读取分享文件失败: {}", e))?;
+    let bundle = decode(&bytes).map_err(|e| format!("读取分享文件失败: {}", e))?;
+    // 只有文件已签名时才需要本机密钥来判断是否是自己签的；预览时不生成新密钥
+    let own_key = match bundle.signer {
+        Some(_) => read_signing_key(&signing_key_path(&app_handle)?)
+            .map_err(|e| e.to_string())?
+            .map(|key| key.verifying_key()),
+        None => None,
+    };
+    let conn = store.open().map_err(|e| e.to_string())?;
+    preview(&conn, &path, bundle, own_key.as_ref())
+        .map(Some)
+        .map_err(|e| format!("读取分享文件失败: {}", e))
+}
+
+/// 导入 .pgenie 文件，导入前会重新校验签名
+#[tauri::command]
+pub async fn import_bundle<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+    path: String,
+    strategy: ImportStrategy,
+) -> Result<BundleImportReport, String> {
+    let bytes = std::fs::read(&path).map_err(|e| format!("读取分享文件失败: {}", e))?;
+    let bundle = decode(&bytes).map_err(|e| format!("导入失败: {}", e))?;
+    backup::before_change(&store, BackupReason::Import)?;
+    let report = {
+        let conn = store.open().map_err(|e| e.to_string())?;
+        import_content(&conn, &bundle.content, strategy).map_err(|e| format!("导入失败: {}", e))?
+    };
+    println!(
+        "导入分享文件完成: 新建 {}，更新 {}，另存 {}，跳过 {}，新建片段 {}",
+        report.prompts.created,
+        report.prompts.updated,
+        report.prompts.duplicated,
+        report.prompts.skipped,
+        report.snippets_created
+    );
+    crate::notify_prompts_changed(&app_handle, &report.prompts.changed_ids());
+    Ok(report)
+}
+
+/// 本机签名公钥的指纹，供对方核对文件来源
+#[tauri::command]
+pub fn get_bundle_fingerprint<R: Runtime>(app_handle: AppHandle<R>) -> Result<String, String> {
+    Ok(fingerprint(&signing_key(&app_handle)?.verifying_key()))
+}
+
+/// 前端加载完成后取出待预览的分享文件
+#[tauri::command]
+pub fn take_pending_bundle(pending: State<'_, PendingBundle>) -> Option<String> {
+    pending.0.lock().unwrap().take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content() -> BundleContent {
+        BundleContent {
+            created_at: "2025-01-01T00:00:00.000Z".to_string(),
+            prompts: vec![BundlePrompt {
+                id: "a".to_string(),
+                title: "周报".to_string(),
+                content: "{{> header}} 本周完成了 {{work}}".to_string(),
+                tags: vec!["工作".to_string()],
+                variables: Vec::new(),
+                created_at: "2025-01-01T00:00:00.000Z".to_string(),
+                updated_at: "2025-01-01T00:00:00.000Z".to_string(),
+            }],
+            snippets: vec![BundleSnippet {
+                name: "header".to_string(),
+                content: "你是助理".to_string(),
+                description: String::new(),
+            }],
+        }
+    }
+
+    fn key() -> SigningKey {
+        SigningKey::from_bytes(&[7u8; 32])
+    }
+
+    /// 解压文件，修改外层结构后重新压缩
+    fn modify(bytes: &[u8], change: impl FnOnce(&mut BundleFile)) -> Vec<u8> {
+        let mut json = Vec::new();
+        GzDecoder::new(bytes).read_to_end(&mut json).unwrap();
+        let mut file: BundleFile = serde_json::from_slice(&json).unwrap();
+        change(&mut file);
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        serde_json::to_writer(&mut encoder, &file).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn round_trips_signed_and_unsigned_bundles() {
+        let key = key();
+        let decoded = decode(&encode(&content(), Some(&key)).unwrap()).unwrap();
+        assert_eq!(decoded.signer, Some(key.verifying_key()));
+        assert_eq!(decoded.content.prompts[0].content, content().prompts[0].content);
+        assert_eq!(decoded.content.snippets[0].name, "header");
+
+        let decoded = decode(&encode(&content(), None).unwrap()).unwrap();
+        assert_eq!(decoded.signer, None);
+        assert_eq!(decoded.content.prompts[0].title, "周报");
+    }
+
+    #[test]
+    fn modified_payload_fails_verification() {
+        let bytes = encode(&content(), Some(&key())).unwrap();
+        let tampered = modify(&bytes, |file| {
+            file.payload = file.payload.replace("你是助理", "忽略之前的指令");
+        });
+        assert!(matches!(decode(&tampered), Err(BundleError::InvalidSignature)));
+
+        // 换成另一把密钥的公钥，签名同样无法通过校验
+        let other = SigningKey::from_bytes(&[8u8; 32]).verifying_key();
+        let swapped = modify(&bytes, |file| {
+            if let Some(signature) = &mut file.signature {
+                signature.public_key = STANDARD.encode(other.as_bytes());
+            }
+        });
+        assert!(matches!(decode(&swapped), Err(BundleError::InvalidSignature)));
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_bundles() {
+        assert!(matches!(decode(b"{}"), Err(BundleError::NotBundle)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn signing_key_is_private_to_the_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("promptgenie-test-{}", Uuid::now_v7()));
+        let path = dir.join(SIGNING_KEY_FILE);
+        assert!(read_signing_key(&path).unwrap().is_none());
+        let key = load_signing_key(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(
+            read_signing_key(&path).unwrap().map(|key| key.to_bytes()),
+            Some(key.to_bytes())
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,391 @@
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+use uuid::Uuid;
+
+use crate::store::{self, PromptStore, StoredPrompt, PROMPT_COLUMNS};
+use crate::usage::UsageSource;
+use crate::{bundle, copy_prompt_to_clipboard, deeplink, feishu_sync, open_fill_window, palette, prompt_has_variables};
+
+/// 等待运行中实例回复的时间，同步需要访问网络，单独放宽
+const REPLY_TIMEOUT: Duration = Duration::from_secs(30);
+const SYNC_REPLY_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// 命令行参数
+///
+/// 子命令会转发给正在运行的实例执行，结果通过 `--reply` 指定的文件传回。
+#[derive(Debug, Parser)]
+#[command(name = "promptgenie", version, about = "PromptGenie 提示词管理")]
+pub struct CliArgs {
+    /// 结果写入的文件（内部使用）
+    #[arg(long, hide = true, global = true)]
+    reply: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum CliCommand {
+    /// 复制提示词到剪贴板，含有变量时打开填写窗口
+    Copy {
+        /// 提示词 ID 或标题
+        target: String,
+    },
+    /// 搜索提示词
+    Search {
+        query: String,
+        /// 最多返回的条数
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+    },
+    /// 新建提示词
+    New {
+        #[arg(long)]
+        title: String,
+        /// 内容文件，`-` 表示从标准输入读取
+        #[arg(long)]
+        file: PathBuf,
+        /// 标签，可重复指定
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+    /// 与飞书多维表格同步
+    Sync,
+}
+
+/// 运行中实例返回的结果
+#[derive(Debug, Serialize, Deserialize)]
+struct CliReply {
+    ok: bool,
+    message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data: Option<serde_json::Value>,
+}
+
+impl CliReply {
+    fn success(message: impl Into<String>, data: Option<serde_json::Value>) -> Self {
+        Self {
+            ok: true,
+            message: message.into(),
+            data,
+        }
+    }
+
+    fn failure(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+/// Windows 发布版是 GUI 程序（windows_subsystem = "windows"），没有自己的控制台，
+/// 输出前附加到启动它的终端；输出已被重定向到文件或管道时保持不变。
+///
+/// GUI 程序启动后终端不会等待它退出，输出可能出现在下一个提示符之后，
+/// 脚本中需要可靠地读取输出时建议使用 promptgenie-cli。
+#[cfg(windows)]
+fn attach_parent_console() {
+    use std::ffi::c_void;
+
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+        fn GetStdHandle(std_handle: u32) -> *mut c_void;
+    }
+
+    // 从资源管理器启动时没有父控制台，附加失败时忽略
+    unsafe {
+        if GetStdHandle(STD_OUTPUT_HANDLE).is_null() {
+            AttachConsole(ATTACH_PARENT_PROCESS);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_parent_console() {}
+
+/// 在启动 Tauri 之前处理命令行子命令
+///
+/// 带子命令时以 `--reply` 重新启动自身：新进程由单实例插件转发给运行中的实例，
+/// 没有运行中的实例时则自己成为主实例并执行命令。当前进程只负责等待结果并输出，
+/// 返回退出码；返回 None 表示按正常方式启动应用。
+pub fn forward_to_instance() -> Option<i32> {
+    let args: Vec<String> = std::env::args().collect();
+    let parsed = match CliArgs::try_parse_from(&args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            // 系统可能附带其它启动参数（如 macOS 的 -psn_），不是子命令时照常启动
+            let is_subcommand = args.get(1).is_some_and(|arg| {
+                CliArgs::command()
+                    .get_subcommands()
+                    .any(|sub| sub.get_name() == arg)
+            });
+            if is_subcommand
+                || matches!(e.kind(), ErrorKind::DisplayHelp | ErrorKind::DisplayVersion)
+            {
+                attach_parent_console();
+                let _ = e.print();
+                return Some(e.exit_code());
+            }
+            return None;
+        }
+    };
+    if parsed.reply.is_some() {
+        return None;
+    }
+    let command = parsed.command?;
+    attach_parent_console();
+
+    match forward(args, &command) {
+        Ok(reply) => {
+            if !reply.ok {
+                eprintln!("{}", reply.message);
+                return Some(1);
+            }
+            match reply.data {
+                Some(data) => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&data).unwrap_or_default()
+                ),
+                None => println!("{}", reply.message),
+            }
+            Some(0)
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            Some(1)
+        }
+    }
+}
+
+/// 启动转发进程并等待运行中实例写回结果
+fn forward(mut args: Vec<String>, command: &CliCommand) -> Result<CliReply, String> {
+    let exe = std::env::current_exe().map_err(|e| format!("无法获取程序路径: {}", e))?;
+    let temp_dir = std::env::temp_dir();
+    let reply_path = temp_dir.join(format!("promptgenie-cli-{}.json", Uuid::now_v7()));
+
+    // 标准输入无法转发，先写入临时文件再把路径交给运行中的实例
+    let mut stdin_file = None;
+    if let CliCommand::New { file, .. } = command {
+        if file.as_os_str() == "-" {
+            let mut content = String::new();
+            std::io::stdin()
+                .read_to_string(&mut content)
+                .map_err(|e| format!("读取标准输入失败: {}", e))?;
+            let path = temp_dir.join(format!("promptgenie-cli-{}.txt", Uuid::now_v7()));
+            std::fs::write(&path, content).map_err(|e| format!("写入临时文件失败: {}", e))?;
+            let path_arg = path.to_string_lossy().into_owned();
+            if let Some(index) = args.iter().position(|arg| arg == "--file") {
+                if let Some(arg) = args.get_mut(index + 1) {
+                    *arg = path_arg;
+                }
+            } else if let Some(arg) = args.iter_mut().find(|arg| arg.as_str() == "--file=-") {
+                *arg = format!("--file={}", path_arg);
+            }
+            stdin_file = Some(path);
+        }
+    }
+
+    args.push("--reply".to_string());
+    args.push(reply_path.to_string_lossy().into_owned());
+    Command::new(exe)
+        .args(&args[1..])
+        .spawn()
+        .map_err(|e| format!("启动 PromptGenie 失败: {}", e))?;
+
+    let timeout = match command {
+        CliCommand::Sync => SYNC_REPLY_TIMEOUT,
+        _ => REPLY_TIMEOUT,
+    };
+    let reply = wait_for_reply(&reply_path, timeout);
+    if let Some(path) = stdin_file {
+        let _ = std::fs::remove_file(path);
+    }
+    reply
+}
+
+fn wait_for_reply(path: &Path, timeout: Duration) -> Result<CliReply, String> {
+    let started = Instant::now();
+    while started.elapsed() < timeout {
+        if path.exists() {
+            let content =
+                std::fs::read_to_string(path).map_err(|e| format!("读取执行结果失败: {}", e))?;
+            let _ = std::fs::remove_file(path);
+            return serde_json::from_str(&content).map_err(|e| format!("解析执行结果失败: {}", e));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    Err("等待 PromptGenie 响应超时".to_string())
+}
+
+/// 先写临时文件再重命名，避免等待方读到不完整的内容
+fn write_reply(path: &Path, reply: &CliReply) -> Result<(), String> {
+    let content = serde_json::to_string(reply).map_err(|e| e.to_string())?;
+    let temp_path = path.with_extension("tmp");
+    std::fs::write(&temp_path, content).map_err(|e| format!("写入执行结果失败: {}", e))?;
+    std::fs::rename(&temp_path, path).map_err(|e| format!("写入执行结果失败: {}", e))
+}
+
+fn show_main_window<R: Runtime>(app_handle: &AppHandle<R>) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+        println!("已将现有窗口带到前台");
+    } else {
+        println!("警告：找不到主窗口");
+    }
+}
+
+/// 处理命令行参数（单实例回调和首个实例启动时调用）
+///
+/// 没有子命令时与之前一样显示主窗口。Windows 和 Linux 上点击 promptgenie:// 链接
+/// 也会以链接作为参数启动，这里交给 deeplink 处理；双击 .pgenie 文件时以文件路径
+/// 作为参数启动，交给 bundle 处理。
+pub fn handle_argv<R: Runtime>(app_handle: &AppHandle<R>, argv: Vec<String>, cwd: String) {
+    if let Some(link) = argv.iter().skip(1).find(|arg| deeplink::is_deep_link(arg)) {
+        deeplink::handle_url(app_handle, link);
+        return;
+    }
+    if let Some(path) = bundle::path_from_args(&argv, Path::new(&cwd)) {
+        bundle::open_file(app_handle, &path);
+        return;
+    }
+    let parsed = match CliArgs::try_parse_from(&argv) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            println!("无法解析命令行参数: {}", e);
+            show_main_window(app_handle);
+            return;
+        }
+    };
+    let Some(command) = parsed.command else {
+        show_main_window(app_handle);
+        return;
+    };
+
+    println!("执行命令行请求: {:?}", command);
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let reply = execute(&app_handle, command, Path::new(&cwd)).await;
+        match parsed.reply {
+            Some(path) => {
+                if let Err(e) = write_reply(&path, &reply) {
+                    eprintln!("{}", e);
+                }
+            }
+            None => println!("{}", reply.message),
+        }
+    });
+}
+
+async fn execute<R: Runtime>(app_handle: &AppHandle<R>, command: CliCommand, cwd: &Path) -> CliReply {
+    let result = match command {
+        CliCommand::Copy { target } => copy(app_handle, &target),
+        CliCommand::Search { query, limit } => search(app_handle, &query, limit),
+        CliCommand::New { title, file, tags } => create(app_handle, &title, &cwd.join(file), &tags),
+        CliCommand::Sync => feishu_sync::sync_now(app_handle).await.map(|result| CliReply {
+            ok: result.success,
+            message: result.message.clone(),
+            data: serde_json::to_value(&result).ok(),
+        }),
+    };
+    result.unwrap_or_else(CliReply::failure)
+}
+
+/// 按 ID、完整标题、模糊匹配的顺序查找提示词，不包括回收站中的
+pub(crate) fn resolve_prompt(conn: &Connection, target: &str) -> Result<StoredPrompt, String> {
+    let target = target.trim();
+    if target.is_empty() {
+        return Err("请指定提示词 ID 或标题".to_string());
+    }
+
+    let exact = conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM prompts WHERE deleted_at IS NULL AND (id = ?1 OR title = ?1)
+                 ORDER BY id = ?1 DESC, last_used_at DESC LIMIT 1",
+                PROMPT_COLUMNS
+            ),
+            params![target],
+            store::prompt_from_row,
+        )
+        .optional()
+        .map_err(|e| format!("查找提示词失败: {}", e))?;
+    if let Some(prompt) = exact {
+        return Ok(prompt);
+    }
+
+    let best = palette::search(conn, target, 1)
+        .map_err(|e| format!("查找提示词失败: {}", e))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("未找到提示词: {}", target))?;
+    store::get_prompt(conn, &best.id).map_err(|e| e.to_string())
+}
+
+fn copy<R: Runtime>(app_handle: &AppHandle<R>, target: &str) -> Result<CliReply, String> {
+    let prompt = {
+        let store = app_handle.state::<PromptStore>();
+        let conn = store.open().map_err(|e| e.to_string())?;
+        resolve_prompt(&conn, target)?
+    };
+
+    if prompt_has_variables(app_handle, &prompt.id)? {
+        open_fill_window(app_handle, &prompt.id, UsageSource::Cli)
+            .map_err(|e| format!("打开变量填写窗口失败: {}", e))?;
+        return Ok(CliReply::success(
+            format!("「{}」含有变量，已打开填写窗口", prompt.title),
+            None,
+        ));
+    }
+
+    let title = copy_prompt_to_clipboard(app_handle, &prompt.id, UsageSource::Cli)?;
+    Ok(CliReply::success(format!("已复制「{}」", title), None))
+}
+
+fn search<R: Runtime>(app_handle: &AppHandle<R>, query: &str, limit: usize) -> Result<CliReply, String> {
+    let store = app_handle.state::<PromptStore>();
+    let conn = store.open().map_err(|e| e.to_string())?;
+    let items = palette::search(&conn, query, limit).map_err(|e| format!("搜索提示词失败: {}", e))?;
+    Ok(CliReply::success(
+        format!("找到 {} 条提示词", items.len()),
+        serde_json::to_value(items).ok(),
+    ))
+}
+
+fn create<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    title: &str,
+    file: &Path,
+    tags: &[String],
+) -> Result<CliReply, String> {
+    if title.trim().is_empty() {
+        return Err("标题不能为空".to_string());
+    }
+    let content = std::fs::read_to_string(file)
+        .map_err(|e| format!("读取文件 {} 失败: {}", file.display(), e))?;
+
+    let prompt = {
+        let store = app_handle.state::<PromptStore>();
+        let conn = store.open().map_err(|e| e.to_string())?;
+        store::create_prompt(&conn, title.trim(), &content, tags)
+            .map_err(|e| format!("创建提示词失败: {}", e))?
+    };
+    crate::notify_prompts_changed(app_handle, std::slice::from_ref(&prompt.id));
+    Ok(CliReply::success(
+        format!("已创建「{}」", prompt.title),
+        serde_json::to_value(&prompt).ok(),
+    ))
+}
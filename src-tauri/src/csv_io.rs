@@ -0,0 +1,589 @@
+use encoding_rs::GBK;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Runtime, State};
+use tauri_plugin_dialog::DialogExt;
+use thiserror::Error;
+
+use crate::backup::{self, BackupReason};
+use crate::library::{self, DraftPrompt, ImportReport, ImportStrategy, LibraryError};
+use crate::store::PromptStore;
+
+/// 预览时返回的行数
+const PREVIEW_ROWS: usize = 20;
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 导出时的列，与导入时自动识别的列名一致
+const EXPORT_HEADERS: [&str; 7] = [
+    "id",
+    "title",
+    "content",
+    "tags",
+    "favorite",
+    "created_at",
+    "updated_at",
+];
+
+/// 电子表格会把以这些字符开头的单元格当作公式执行
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+#[derive(Debug, Error)]
+pub enum CsvError {
+    #[error("文件读写失败: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("CSV 格式错误: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("文件不是有效的 UTF-8 编码，请尝试选择 GBK")]
+    InvalidUtf8,
+    #[error("CSV 文件中没有列")]
+    NoHeaders,
+    #[error("CSV 文件中没有列「{0}」")]
+    MissingColumn(String),
+    #[error("请选择提示词内容所在的列")]
+    NoContentColumn,
+    #[error("{0}")]
+    Library(#[from] LibraryError),
+}
+
+/// 文件编码，Auto 时按 BOM 和内容判断
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CsvEncoding {
+    #[default]
+    Auto,
+    #[serde(rename = "utf-8")]
+    Utf8,
+    Gbk,
+}
+
+/// 列映射：各字段对应的列名，未设置的字段不导入
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvMapping {
+    pub id: Option<String>,
+    /// 没有标题列时从内容第一行截取
+    pub title: Option<String>,
+    pub content: String,
+    pub tags: Option<String>,
+    /// 标签列中多个标签的分隔符
+    pub tag_delimiter: String,
+    pub favorite: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+/// 导入前的预览
+#[derive(Debug, Clone, Serialize)]
+pub struct CsvPreview {
+    pub path: String,
+    /// 实际使用的编码
+    pub encoding: CsvEncoding,
+    pub headers: Vec<String>,
+    pub mapping: CsvMapping,
+    /// 可以导入的行数
+    pub total: usize,
+    /// 内容为空而跳过的行数
+    pub skipped: usize,
+    pub prompts: Vec<DraftPrompt>,
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CsvExportSummary {
+    pub path: String,
+    pub prompts: usize,
+    /// 有字符无法用 GBK 表示（被替换为 &#...; 形式）
+    pub unmappable: bool,
+}
+
+/// 按编码解码文件内容，返回文本和实际使用的编码
+pub fn decode(bytes: &[u8], encoding: CsvEncoding) -> Result<(String, CsvEncoding), CsvError> {
+    let utf8 = |bytes: &[u8]| {
+        let bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+        std::str::from_utf8(bytes)
+            .map(str::to_string)
+            .map_err(|_| CsvError::InvalidUtf8)
+    };
+    match encoding {
+        CsvEncoding::Utf8 => Ok((utf8(bytes)?, CsvEncoding::Utf8)),
+        CsvEncoding::Gbk => Ok((GBK.decode(bytes).0.into_owned(), CsvEncoding::Gbk)),
+        // 带 BOM 或能按 UTF-8 解码的当作 UTF-8，否则多半是中文 Windows 下 Excel 保存的 GBK
+        CsvEncoding::Auto => match utf8(bytes) {
+            Ok(text) => Ok((text, CsvEncoding::Utf8)),
+            Err(_) => Ok((GBK.decode(bytes).0.into_owned(), CsvEncoding::Gbk)),
+        },
+    }
+}
+
+/// 按第一行判断分隔符：逗号、分号（欧洲地区的 Excel）或制表符
+fn detect_delimiter(text: &str) -> u8 {
+    let first_line = text.lines().next().unwrap_or_default();
+    // 数量相同时优先逗号（max_by_key 取最后一个最大值）
+    [b'\t', b';', b',']
+        .into_iter()
+        .max_by_key(|delimiter| first_line.bytes().filter(|b| b == delimiter).count())
+        .filter(|delimiter| first_line.as_bytes().contains(delimiter))
+        .unwrap_or(b',')
+}
+
+/// 解析表头和所有行，支持引号中的换行
+fn read_table(text: &str) -> Result<(Vec<String>, Vec<csv::StringRecord>), CsvError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(detect_delimiter(text))
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()?
+        .iter()
+        .map(|header| header.trim().to_string())
+        .collect();
+    if headers.iter().all(|header| header.is_empty()) {
+        return Err(CsvError::NoHeaders);
+    }
+    let records = reader.records().collect::<Result<Vec<_>, _>>()?;
+    Ok((headers, records))
+}
+
+/// 按列名猜测映射，兼容 awesome-chatgpt-prompts 的 act,prompt 和本应用导出的列
+pub fn suggest_mapping(headers: &[String]) -> CsvMapping {
+    let find = |names: &[&str]| {
+        headers
+            .iter()
+            .find(|header| names.iter().any(|name| header.eq_ignore_ascii_case(name)))
+            .cloned()
+    };
+    let title = find(&["title", "act", "name", "标题", "名称"]);
+    let content = find(&["content", "prompt", "text", "内容", "提示词"])
+        .or_else(|| {
+            headers
+                .iter()
+                .rfind(|header| Some(*header) != title.as_ref())
+                .cloned()
+        })
+        .unwrap_or_default();
+    CsvMapping {
+        id: find(&["id"]),
+        title,
+        content,
+        tags: find(&["tags", "tag", "category", "标签", "分类"]),
+        tag_delimiter: ",".to_string(),
+        favorite: find(&["favorite", "is_favorite", "收藏"]),
+        created_at: find(&["created_at", "创建时间"]),
+        updated_at: find(&["updated_at", "更新时间"]),
+    }
+}
+
+/// 导出时在可能被当作公式的单元格前加单引号，Excel 等会把它当作文本标记
+fn escape_formula(value: &str) -> Cow<'_, str> {
+    if value.starts_with(FORMULA_PREFIXES) {
+        Cow::Owned(format!("'{}", value))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// 去掉导出时加上的单引号，使导出再导入的内容保持不变
+fn unescape_formula(value: &str) -> &str {
+    match value.strip_prefix('\'') {
+        Some(rest) if rest.starts_with(FORMULA_PREFIXES) => rest,
+        _ => value,
+    }
+}
+
+fn parse_bool(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "1" | "true" | "yes" | "y" | "是" | "★"
+    )
+}
+
+/// 按映射把每行转换为提示词，内容为空的行跳过，返回提示词和跳过的行数
+pub fn to_drafts(
+    headers: &[String],
+    records: &[csv::StringRecord],
+    mapping: &CsvMapping,
+) -> Result<(Vec<DraftPrompt>, usize), CsvError> {
+    let columns: HashMap<&str, usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(index, header)| (header.as_str(), index))
+        .collect();
+    let column = |name: &Option<String>| -> Result<Option<usize>, CsvError> {
+        match name.as_deref().filter(|name| !name.is_empty()) {
+            Some(name) => columns
+                .get(name)
+                .copied()
+                .map(Some)
+                .ok_or_else(|| CsvError::MissingColumn(name.to_string())),
+            None => Ok(None),
+        }
+    };
+    let content_column =
+        column(&Some(mapping.content.clone()))?.ok_or(CsvError::NoContentColumn)?;
+    let id_column = column(&mapping.id)?;
+    let title_column = column(&mapping.title)?;
+    let tags_column = column(&mapping.tags)?;
+    let favorite_column = column(&mapping.favorite)?;
+    let created_column = column(&mapping.created_at)?;
+    let updated_column = column(&mapping.updated_at)?;
+    let delimiter = if mapping.tag_delimiter.is_empty() {
+        ","
+    } else {
+        mapping.tag_delimiter.as_str()
+    };
+
+    let mut drafts = Vec::with_capacity(records.len());
+    let mut skipped = 0;
+    for record in records {
+        let cell = |index: Option<usize>| {
+            index
+                .and_then(|index| record.get(index))
+                .map(str::trim)
+                .map(unescape_formula)
+                .filter(|value| !value.is_empty())
+        };
+        // 内容只去掉首尾空行，保留缩进
+        let content = unescape_formula(record.get(content_column).unwrap_or_default());
+        if content.trim().is_empty() {
+            skipped += 1;
+            continue;
+        }
+        let content = content.replace("\r\n", "\n").trim_matches('\n').to_string();
+        let title = cell(title_column)
+            .map(str::to_string)
+            .unwrap_or_else(|| library::title_from_content(&content));
+        let tags = cell(tags_column)
+            .map(|tags| {
+                // 默认的逗号分隔同时接受中文逗号
+                let tags = if delimiter == "," {
+                    tags.replace('，', ",")
+                } else {
+                    tags.to_string()
+                };
+                tags.split(delimiter)
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        drafts.push(DraftPrompt {
+            id: cell(id_column).map(str::to_string),
+            title,
+            content,
+            tags,
+            is_favorite: cell(favorite_column).is_some_and(parse_bool),
+            created_at: cell(created_column).map(str::to_string),
+            updated_at: cell(updated_column).map(str::to_string),
+        });
+    }
+    Ok((drafts, skipped))
+}
+
+/// 读取并预览 CSV 文件，未指定映射时按列名猜测
+pub fn preview_file(
+    path: &Path,
+    encoding: CsvEncoding,
+    mapping: Option<CsvMapping>,
+) -> Result<CsvPreview, CsvError> {
+    let (text, encoding) = decode(&std::fs::read(path)?, encoding)?;
+    let (headers, records) = read_table(&text)?;
+    let mapping = mapping.unwrap_or_else(|| suggest_mapping(&headers));
+    let (mut prompts, skipped) = to_drafts(&headers, &records, &mapping)?;
+    let total = prompts.len();
+    prompts.truncate(PREVIEW_ROWS);
+    Ok(CsvPreview {
+        path: path.to_string_lossy().into_owned(),
+        encoding,
+        headers,
+        mapping,
+        total,
+        skipped,
+        prompts,
+    })
+}
+
+/// 按映射导入 CSV 文件，ID 冲突时的处理与 JSON 导入相同
+pub fn import_file(
+    conn: &Connection,
+    path: &Path,
+    encoding: CsvEncoding,
+    mapping: &CsvMapping,
+    strategy: ImportStrategy,
+) -> Result<ImportReport, CsvError> {
+    let (text, _) = decode(&std::fs::read(path)?, encoding)?;
+    let (headers, records) = read_table(&text)?;
+    let (drafts, _) = to_drafts(&headers, &records, mapping)?;
+    Ok(library::import_document(
+        conn,
+        &library::document_from_drafts(&drafts),
+        strategy,
+    )?)
+}
+
+/// 生成 CSV 文本，标签以逗号分隔，可能被当作公式的单元格加单引号
+pub fn export_text(conn: &Connection) -> Result<(String, usize), CsvError> {
+    let document = library::export_document(conn, false)?;
+    let tag_names: HashMap<&str, &str> = document
+        .tags
+        .iter()
+        .map(|tag| (tag.id.as_str(), tag.name.as_str()))
+        .collect();
+    let mut prompt_tags: HashMap<&str, Vec<&str>> = HashMap::new();
+    for link in &document.prompt_tags {
+        if let Some(name) = tag_names.get(link.tag_id.as_str()) {
+            prompt_tags
+                .entry(link.prompt_id.as_str())
+                .or_default()
+                .push(name);
+        }
+    }
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(EXPORT_HEADERS)?;
+    for prompt in &document.prompts {
+        let mut tags = prompt_tags.remove(prompt.id.as_str()).unwrap_or_default();
+        tags.sort();
+        let tags = tags.join(",");
+        let record = [
+            escape_formula(&prompt.id),
+            escape_formula(&prompt.title),
+            escape_formula(&prompt.content),
+            escape_formula(&tags),
+            Cow::Borrowed(if prompt.is_favorite { "true" } else { "false" }),
+            Cow::Borrowed(prompt.created_at.as_str()),
+            Cow::Borrowed(prompt.updated_at.as_str()),
+        ];
+        writer.write_record(record.iter().map(|cell| cell.as_bytes()))?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| CsvError::Io(e.into_error()))?;
+    // 写入的都是 &str，结果一定是有效的 UTF-8
+    let text = String::from_utf8(bytes).unwrap_or_default();
+    Ok((text, document.prompts.len()))
+}
+
+/// 导出为 CSV 文件；UTF-8 时可以加 BOM，便于 Excel 识别编码
+pub fn export_file(
+    conn: &Connection,
+    path: &Path,
+    encoding: CsvEncoding,
+    bom: bool,
+) -> Result<CsvExportSummary, CsvError> {
+    let (text, prompts) = export_text(conn)?;
+    let (bytes, unmappable) = match encoding {
+        CsvEncoding::Gbk => {
+            let (bytes, _, unmappable) = GBK.encode(&text);
+            (bytes.into_owned(), unmappable)
+        }
+        CsvEncoding::Auto | CsvEncoding::Utf8 => {
+            let mut bytes = Vec::with_capacity(text.len() + UTF8_BOM.len());
+            if bom {
+                bytes.extend_from_slice(UTF8_BOM);
+            }
+            bytes.extend_from_slice(text.as_bytes());
+            (bytes, false)
+        }
+    };
+    std::fs::write(path, bytes)?;
+    Ok(CsvExportSummary {
+        path: path.to_string_lossy().into_owned(),
+        prompts,
+        unmappable,
+    })
+}
+
+/// 导出文件的默认名称，例如 promptgenie-20250101.csv
+fn default_file_name() -> String {
+    format!("promptgenie-{}.csv", chrono::Local::now().format("%Y%m%d"))
+}
+
+/// 导出为 CSV，未指定路径时弹出保存对话框，取消时返回 None
+#[tauri::command]
+pub async fn export_csv<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+    path: Option<String>,
+    encoding: CsvEncoding,
+    bom: bool,
+) -> Result<Option<CsvExportSummary>, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => match app_handle
+            .dialog()
+            .file()
+            .add_filter("CSV", &["csv"])
+            .set_file_name(default_file_name())
+            .blocking_save_file()
+            .and_then(|path| path.into_path().ok())
+        {
+            Some(path) => path,
+            None => return Ok(None),
+        },
+    };
+
+    let conn = store.open().map_err(|e| e.to_string())?;
+    let summary =
+        export_file(&conn, &path, encoding, bom).map_err(|e| format!("导出失败: {}", e))?;
+    println!("已导出 {} 条提示词到 {}", summary.prompts, summary.path);
+    Ok(Some(summary))
+}
+
+/// 预览 CSV 文件，未指定路径时弹出打开对话框，取消时返回 None
+#[tauri::command]
+pub async fn preview_csv<R: Runtime>(
+    app_handle: AppHandle<R>,
+    path: Option<String>,
+    encoding: CsvEncoding,
+    mapping: Option<CsvMapping>,
+) -> Result<Option<CsvPreview>, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => match app_handle
+            .dialog()
+            .file()
+            .add_filter("CSV", &["csv", "tsv", "txt"])
+            .blocking_pick_file()
+            .and_then(|path| path.into_path().ok())
+        {
+            Some(path) => path,
+            None => return Ok(None),
+        },
+    };
+    preview_file(&path, encoding, mapping)
+        .map(Some)
+        .map_err(|e| format!("读取 CSV 失败: {}", e))
+}
+
+/// 按预览确认的映射导入 CSV 文件
+#[tauri::command]
+pub async fn import_csv<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+    path: String,
+    encoding: CsvEncoding,
+    mapping: CsvMapping,
+    strategy: ImportStrategy,
+) -> Result<ImportReport, String> {
+    backup::before_change(&store, BackupReason::Import)?;
+    let report = {
+        let conn = store.open().map_err(|e| e.to_string())?;
+        import_file(&conn, Path::new(&path), encoding, &mapping, strategy)
+            .map_err(|e| format!("导入失败: {}", e))?
+    };
+    println!(
+        "CSV 导入完成: 新建 {}，更新 {}，另存 {}，跳过 {}",
+        report.created, report.updated, report.duplicated, report.skipped
+    );
+    crate::notify_prompts_changed(&app_handle, &report.changed_ids());
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::open_in_memory;
+
+    fn drafts(text: &str) -> Vec<DraftPrompt> {
+        let (headers, records) = read_table(text).unwrap();
+        to_drafts(&headers, &records, &suggest_mapping(&headers))
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn maps_awesome_chatgpt_prompts_headers() {
+        let text =
+            "\"act\",\"prompt\"\n\"Linux Terminal\",\"I want you to act as a linux terminal.\"\n";
+        let (headers, _) = read_table(text).unwrap();
+        let mapping = suggest_mapping(&headers);
+        assert_eq!(mapping.title.as_deref(), Some("act"));
+        assert_eq!(mapping.content, "prompt");
+        assert_eq!(mapping.tags, None);
+
+        let drafts = drafts(text);
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts[0].title, "Linux Terminal");
+        assert_eq!(drafts[0].content, "I want you to act as a linux terminal.");
+    }
+
+    #[test]
+    fn decode_strips_utf8_bom() {
+        let mut bytes = UTF8_BOM.to_vec();
+        bytes.extend_from_slice("title,content\n标题,内容\n".as_bytes());
+        let (text, encoding) = decode(&bytes, CsvEncoding::Auto).unwrap();
+        assert_eq!(encoding, CsvEncoding::Utf8);
+        assert!(text.starts_with("title,"));
+        let (headers, _) = read_table(&text).unwrap();
+        assert_eq!(headers[0], "title");
+    }
+
+    #[test]
+    fn decode_falls_back_to_gbk() {
+        let (bytes, _, _) = GBK.encode("标题,内容\n周报,总结本周工作\n");
+        let (text, encoding) = decode(&bytes, CsvEncoding::Auto).unwrap();
+        assert_eq!(encoding, CsvEncoding::Gbk);
+        let drafts = drafts(&text);
+        assert_eq!(drafts[0].title, "周报");
+        assert_eq!(drafts[0].content, "总结本周工作");
+
+        assert!(matches!(
+            decode(&bytes, CsvEncoding::Utf8),
+            Err(CsvError::InvalidUtf8)
+        ));
+    }
+
+    #[test]
+    fn keeps_newlines_in_quoted_cells() {
+        let text =
+            "title,content,tags\r\n代码审查,\"第一行\r\n  第二行\r\n\",\"a，b\"\r\n空行,,\r\n";
+        let (headers, records) = read_table(text).unwrap();
+        let (drafts, skipped) = to_drafts(&headers, &records, &suggest_mapping(&headers)).unwrap();
+        assert_eq!(skipped, 1);
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts[0].content, "第一行\n  第二行");
+        assert_eq!(drafts[0].tags, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn export_round_trip_keeps_dates_and_escapes_formulas() {
+        let conn = open_in_memory();
+        let draft = DraftPrompt {
+            id: Some("a".to_string()),
+            title: "=HYPERLINK(\"http://example.com\")".to_string(),
+            content: "- 第一条\n- 第二条".to_string(),
+            tags: vec!["@team".to_string()],
+            is_favorite: true,
+            created_at: Some("2024-01-02T03:04:05.000Z".to_string()),
+            updated_at: Some("2024-06-01T00:00:00.000Z".to_string()),
+        };
+        library::import_document(
+            &conn,
+            &library::document_from_drafts(&[draft.clone()]),
+            ImportStrategy::Skip,
+        )
+        .unwrap();
+
+        let (text, count) = export_text(&conn).unwrap();
+        assert_eq!(count, 1);
+        assert!(text.contains("\"'=HYPERLINK("));
+        assert!(text.contains("'- 第一条"));
+        assert!(text.contains("'@team"));
+
+        let (headers, _) = read_table(&text).unwrap();
+        assert_eq!(
+            suggest_mapping(&headers).created_at.as_deref(),
+            Some("created_at")
+        );
+        let imported = drafts(&text);
+        assert_eq!(imported[0].title, draft.title);
+        assert_eq!(imported[0].content, draft.content);
+        assert_eq!(imported[0].tags, draft.tags);
+        assert!(imported[0].is_favorite);
+        assert_eq!(imported[0].created_at, draft.created_at);
+        assert_eq!(imported[0].updated_at, draft.updated_at);
+    }
+}
@@ -0,0 +1,510 @@
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::backup::{self, BackupReason};
+use crate::library::{self, DraftPrompt, ImportReport, ImportStrategy};
+use crate::store::{self, PromptStore};
+use crate::template::{self, TemplateVariable};
+use crate::usage::UsageSource;
+use crate::{copy_prompt_to_clipboard, open_fill_window, prompt_has_variables, tray};
+
+/// 与 tauri.conf.json 中 plugins.deep-link 注册的协议一致
+pub const SCHEME: &str = "promptgenie";
+
+/// 通过链接导入时允许下载的最大字节数
+const MAX_IMPORT_BYTES: usize = 1024 * 1024;
+const IMPORT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 解析后的链接
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DeepLink {
+    /// promptgenie://prompt/<id>
+    Open(String),
+    /// promptgenie://copy/<id>
+    Copy(String),
+    /// promptgenie://import?url=...
+    ImportUrl(String),
+    /// promptgenie://import?data=<base64>
+    ImportData(String),
+}
+
+/// 分享的提示词，与 create_share_link 生成的内容一致
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SharedPrompt {
+    title: String,
+    content: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// 导入内容可以是单条，也可以是列表
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SharedPayload {
+    One(SharedPrompt),
+    Many(Vec<SharedPrompt>),
+}
+
+/// 等待前端加载完成后打开的提示词（应用由链接启动时前端还没有监听事件）
+#[derive(Default)]
+pub struct PendingPrompt(Mutex<Option<String>>);
+
+/// 通过链接收到、等待用户在前端确认的导入内容
+#[derive(Default)]
+pub struct PendingImport(Mutex<Option<LinkImport>>);
+
+#[derive(Debug, Clone)]
+struct LinkImport {
+    source: String,
+    prompts: Vec<SharedPrompt>,
+}
+
+/// 导入前的预览
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkImportPreview {
+    /// 下载地址，内容直接编码在链接中时为 None
+    pub source: Option<String>,
+    pub prompts: Vec<LinkImportPrompt>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkImportPrompt {
+    pub title: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub variables: Vec<TemplateVariable>,
+}
+
+fn parse(link: &str) -> Result<DeepLink, String> {
+    let url = Url::parse(link).map_err(|e| format!("无效的链接 {}: {}", link, e))?;
+    if url.scheme() != SCHEME {
+        return Err(format!("不支持的链接协议: {}", url.scheme()));
+    }
+    let action = url.host_str().unwrap_or_default();
+    let id = url.path().trim_matches('/');
+    let query = |key: &str| {
+        url.query_pairs()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.into_owned())
+    };
+
+    match action {
+        "prompt" | "copy" if id.is_empty() => Err(format!("链接缺少提示词 ID: {}", link)),
+        "prompt" => Ok(DeepLink::Open(id.to_string())),
+        "copy" => Ok(DeepLink::Copy(id.to_string())),
+        "import" => {
+            if let Some(data) = query("data") {
+                Ok(DeepLink::ImportData(data))
+            } else if let Some(source) = query("url") {
+                Ok(DeepLink::ImportUrl(source))
+            } else {
+                Err("导入链接需要 url 或 data 参数".to_string())
+            }
+        }
+        _ => Err(format!("不支持的链接: {}", link)),
+    }
+}
+
+/// 是否为本应用的链接
+pub fn is_deep_link(arg: &str) -> bool {
+    arg.starts_with(&format!("{}://", SCHEME))
+}
+
+/// 注册深度链接回调（macOS 通过系统事件传入，Windows 和 Linux 由单实例插件转发命令行参数）
+pub fn register<R: Runtime>(app_handle: &AppHandle<R>) {
+    // 开发模式下没有安装包，需要在运行时注册协议
+    #[cfg(all(debug_assertions, any(windows, target_os = "linux")))]
+    if let Err(e) = app_handle.deep_link().register_all() {
+        eprintln!("注册链接协议失败: {}", e);
+    }
+
+    let handle = app_handle.clone();
+    app_handle.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            handle_url(&handle, url.as_str());
+        }
+    });
+}
+
+/// 处理一个 promptgenie:// 链接
+pub fn handle_url<R: Runtime>(app_handle: &AppHandle<R>, link: &str) {
+    println!("处理链接: {}", link);
+    let link = match parse(link) {
+        Ok(link) => link,
+        Err(e) => {
+            eprintln!("{}", e);
+            tray::show_notification(app_handle, &e);
+            return;
+        }
+    };
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = match link {
+            DeepLink::Open(id) => open_prompt(&app_handle, &id),
+            DeepLink::Copy(id) => copy_prompt(&app_handle, &id),
+            DeepLink::ImportUrl(source) => match download(&source).await {
+                Ok(content) => request_import(&app_handle, source, &content),
+                Err(e) => Err(e),
+            },
+            DeepLink::ImportData(data) => decode_data(&data)
+                .and_then(|content| request_import(&app_handle, String::new(), &content)),
+        };
+        if let Err(e) = result {
+            eprintln!("{}", e);
+            tray::show_notification(&app_handle, &e);
+        }
+    });
+}
+
+/// 确认提示词存在且不在回收站中
+fn active_prompt<R: Runtime>(app_handle: &AppHandle<R>, id: &str) -> Result<store::StoredPrompt, String> {
+    let store = app_handle.state::<PromptStore>();
+    let conn = store.open().map_err(|e| e.to_string())?;
+    match store::get_prompt(&conn, id) {
+        Ok(prompt) if prompt.deleted_at.is_none() => Ok(prompt),
+        _ => Err(format!("本机没有这条提示词: {}", id)),
+    }
+}
+
+/// 显示主窗口并在编辑器中打开提示词
+fn open_prompt<R: Runtime>(app_handle: &AppHandle<R>, id: &str) -> Result<(), String> {
+    active_prompt(app_handle, id)?;
+    let window = app_handle
+        .get_webview_window("main")
+        .ok_or_else(|| "找不到主窗口".to_string())?;
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+
+    // 前端收到事件后通过 take_pending_prompt 取出，还没加载完成时则在加载后取出
+    *app_handle.state::<PendingPrompt>().0.lock().unwrap() = Some(id.to_string());
+    window
+        .emit("open-prompt", id)
+        .map_err(|e| format!("打开提示词失败: {}", e))
+}
+
+fn copy_prompt<R: Runtime>(app_handle: &AppHandle<R>, id: &str) -> Result<(), String> {
+    let prompt = active_prompt(app_handle, id)?;
+    if prompt_has_variables(app_handle, id)? {
+        return open_fill_window(app_handle, id, UsageSource::Link)
+            .map_err(|e| format!("打开变量填写窗口失败: {}", e));
+    }
+    copy_prompt_to_clipboard(app_handle, id, UsageSource::Link)?;
+    tray::show_notification(app_handle, &format!("已复制「{}」", prompt.title));
+    Ok(())
+}
+
+fn decode_data(data: &str) -> Result<String, String> {
+    let data = data.trim();
+    let bytes = URL_SAFE_NO_PAD
+        .decode(data.trim_end_matches('='))
+        .or_else(|_| STANDARD.decode(data))
+        .map_err(|e| format!("链接中的数据无法解码: {}", e))?;
+    String::from_utf8(bytes).map_err(|e| format!("链接中的数据不是有效的文本: {}", e))
+}
+
+/// 是否为公网地址：链接可以由任意网页触发，不能借此访问本机或局域网中的服务
+fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => is_public_ipv6(ip),
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_documentation()
+        // 0.0.0.0/8 本网络
+        || a == 0
+        // 100.64.0.0/10 运营商级 NAT
+        || (a == 100 && (64..128).contains(&b))
+        // 192.0.0.0/24 协议保留
+        || (a == 192 && b == 0 && c == 0)
+        // 198.18.0.0/15 基准测试
+        || (a == 198 && (b & 0xfe) == 18)
+        // 224.0.0.0/4 组播，240.0.0.0/4 保留（含广播地址）
+        || a >= 224)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    // 最后 32 位中内嵌的 IPv4 地址
+    let embedded = |high: u16, low: u16| Ipv4Addr::from(((high as u32) << 16) | low as u32);
+    match segments {
+        // ::/96：未指定地址、::1 以及已废弃的 IPv4 兼容地址
+        [0, 0, 0, 0, 0, 0, _, _] => false,
+        // ::ffff:0:0/96 IPv4 映射地址
+        [0, 0, 0, 0, 0, 0xffff, high, low] => is_public_ipv4(embedded(high, low)),
+        // 64:ff9b::/96 NAT64
+        [0x64, 0xff9b, 0, 0, 0, 0, high, low] => is_public_ipv4(embedded(high, low)),
+        // 64:ff9b:1::/48 本地 NAT64
+        [0x64, 0xff9b, 1, ..] => false,
+        // 2002::/16 6to4，第 16 到 48 位是 IPv4 地址
+        [0x2002, high, low, ..] => is_public_ipv4(embedded(high, low)),
+        // 2001::/32 Teredo，2001:db8::/32 文档
+        [0x2001, 0, ..] | [0x2001, 0xdb8, ..] => false,
+        // ff00::/8 组播，fc00::/7 唯一本地，fe80::/10 链路本地，fec0::/10 站点本地
+        [first, ..] => {
+            !(first >= 0xff00
+                || (first & 0xfe00) == 0xfc00
+                || matches!(first & 0xffc0, 0xfe80 | 0xfec0))
+        }
+    }
+}
+
+/// 解析导入地址的主机，拒绝本机和局域网地址
+///
+/// 返回域名和解析出的地址，下载时固定使用这个地址，避免再次解析时指向内网。
+async fn resolve_public_host(url: &Url) -> Result<Option<(String, SocketAddr)>, String> {
+    let port = url.port_or_known_default().unwrap_or(443);
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("导入地址缺少主机名: {}", url))?;
+    // IPv6 地址在链接中带有方括号
+    let literal = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>();
+    let (domain, addrs): (Option<String>, Vec<SocketAddr>) = match literal {
+        Ok(ip) => (None, vec![SocketAddr::new(ip, port)]),
+        Err(_) => {
+            let addrs = tokio::net::lookup_host((host, port))
+                .await
+                .map_err(|e| format!("解析导入地址失败: {}", e))?
+                .collect();
+            (Some(host.to_string()), addrs)
+        }
+    };
+    if addrs.is_empty() {
+        return Err(format!("解析导入地址失败: {}", url));
+    }
+    if addrs.iter().any(|addr| !is_public_address(addr.ip())) {
+        return Err(format!("不能从本机或局域网地址导入: {}", url));
+    }
+    Ok(domain.map(|domain| (domain, addrs[0])))
+}
+
+async fn download(source: &str) -> Result<String, String> {
+    let url = Url::parse(source).map_err(|e| format!("无效的导入地址 {}: {}", source, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("只支持从 http(s) 地址导入: {}", source));
+    }
+    let mut client = reqwest::Client::builder()
+        .timeout(IMPORT_TIMEOUT)
+        // 重定向可能指向内网地址，不跟随
+        .redirect(reqwest::redirect::Policy::none())
+        // 经过代理时由代理解析域名，下面固定的地址不会生效
+        .no_proxy();
+    if let Some((domain, addr)) = resolve_public_host(&url).await? {
+        client = client.resolve(&domain, addr);
+    }
+    let response = client
+        .build()
+        .map_err(|e| e.to_string())?
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("下载导入内容失败: {}", e))?;
+    if response.content_length().unwrap_or(0) > MAX_IMPORT_BYTES as u64 {
+        return Err("导入内容过大".to_string());
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("下载导入内容失败: {}", e))?;
+    if bytes.len() > MAX_IMPORT_BYTES {
+        return Err("导入内容过大".to_string());
+    }
+    String::from_utf8(bytes.to_vec()).map_err(|e| format!("导入内容不是有效的文本: {}", e))
+}
+
+fn parse_shared(content: &str) -> Result<Vec<SharedPrompt>, String> {
+    let prompts = match serde_json::from_str(content).map_err(|e| format!("解析导入内容失败: {}", e))? {
+        SharedPayload::One(prompt) => vec![prompt],
+        SharedPayload::Many(prompts) => prompts,
+    };
+    let prompts: Vec<SharedPrompt> = prompts
+        .into_iter()
+        .filter(|prompt| !prompt.title.trim().is_empty())
+        .collect();
+    if prompts.is_empty() {
+        return Err("导入内容中没有提示词".to_string());
+    }
+    Ok(prompts)
+}
+
+/// 解析链接中的提示词，显示主窗口由用户预览后确认导入
+///
+/// 链接可以由任意网页触发，不能在用户确认前写入数据库。
+fn request_import<R: Runtime>(app_handle: &AppHandle<R>, source: String, content: &str) -> Result<(), String> {
+    let prompts = parse_shared(content)?;
+    let window = app_handle
+        .get_webview_window("main")
+        .ok_or_else(|| "找不到主窗口".to_string())?;
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+
+    // 前端收到事件后通过 preview_link_import 读取，还没加载完成时则在加载后读取
+    *app_handle.state::<PendingImport>().0.lock().unwrap() = Some(LinkImport { source, prompts });
+    window
+        .emit("import-link", ())
+        .map_err(|e| format!("打开导入预览失败: {}", e))
+}
+
+/// 在一个事务中导入分享的提示词，总是新建，不会覆盖本地已有的内容
+fn import_prompts(conn: &rusqlite::Connection, prompts: &[SharedPrompt]) -> Result<ImportReport, String> {
+    let drafts: Vec<DraftPrompt> = prompts
+        .iter()
+        .map(|prompt| DraftPrompt {
+            id: None,
+            title: prompt.title.trim().to_string(),
+            content: prompt.content.clone(),
+            tags: prompt.tags.clone(),
+            is_favorite: false,
+            created_at: None,
+            updated_at: None,
+        })
+        .collect();
+    let document = library::document_from_drafts(&drafts);
+    library::import_document(conn, &document, ImportStrategy::Duplicate)
+        .map_err(|e| format!("导入提示词失败: {}", e))
+}
+
+/// 前端加载完成后取出待打开的提示词
+#[tauri::command]
+pub fn take_pending_prompt(pending: State<'_, PendingPrompt>) -> Option<String> {
+    pending.0.lock().unwrap().take()
+}
+
+/// 读取等待确认的链接导入内容，没有时返回 None
+#[tauri::command]
+pub fn preview_link_import(pending: State<'_, PendingImport>) -> Option<LinkImportPreview> {
+    let pending = pending.0.lock().unwrap();
+    let import = pending.as_ref()?;
+    Some(LinkImportPreview {
+        source: Some(import.source.clone()).filter(|source| !source.is_empty()),
+        prompts: import
+            .prompts
+            .iter()
+            .map(|prompt| LinkImportPrompt {
+                title: prompt.title.trim().to_string(),
+                content: prompt.content.clone(),
+                tags: prompt.tags.clone(),
+                variables: template::variables(&prompt.content),
+            })
+            .collect(),
+    })
+}
+
+/// 用户确认后导入链接中的提示词
+#[tauri::command]
+pub async fn confirm_link_import<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+    pending: State<'_, PendingImport>,
+) -> Result<ImportReport, String> {
+    let import = pending
+        .0
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| "没有等待导入的内容".to_string())?;
+    backup::before_change(&store, BackupReason::Import)?;
+    let report = {
+        let conn = store.open().map_err(|e| e.to_string())?;
+        import_prompts(&conn, &import.prompts)?
+    };
+    println!("已通过链接导入 {} 条提示词", report.created);
+    crate::notify_prompts_changed(&app_handle, &report.changed_ids());
+    Ok(report)
+}
+
+/// 放弃等待确认的链接导入
+#[tauri::command]
+pub fn cancel_link_import(pending: State<'_, PendingImport>) {
+    pending.0.lock().unwrap().take();
+}
+
+/// 生成提示词链接：embed 为 false 时只包含 ID（需要对方已同步），
+/// 为 true 时把标题、内容和标签编码在链接中，可以直接导入
+#[tauri::command]
+pub fn create_share_link(
+    store: State<'_, PromptStore>,
+    prompt_id: String,
+    embed: bool,
+) -> Result<String, String> {
+    if !embed {
+        return Ok(format!("{}://prompt/{}", SCHEME, prompt_id));
+    }
+    let conn = store.open().map_err(|e| e.to_string())?;
+    let prompt = store::get_prompt(&conn, &prompt_id).map_err(|e| format!("获取提示词失败: {}", e))?;
+    let shared = SharedPrompt {
+        tags: store::prompt_tag_names(&conn, &prompt_id).map_err(|e| format!("获取标签失败: {}", e))?,
+        title: prompt.title,
+        content: prompt.content,
+    };
+    let json = serde_json::to_string(&shared).map_err(|e| e.to_string())?;
+    Ok(format!("{}://import?data={}", SCHEME, URL_SAFE_NO_PAD.encode(json)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_addresses() {
+        let cases = [
+            ("8.8.8.8", true),
+            ("1.1.1.1", true),
+            ("0.0.0.0", false),
+            ("0.1.2.3", false),
+            ("10.0.0.1", false),
+            ("100.64.0.1", false),
+            ("100.128.0.1", true),
+            ("127.0.0.1", false),
+            ("169.254.169.254", false),
+            ("172.16.0.1", false),
+            ("192.0.0.8", false),
+            ("192.0.2.1", false),
+            ("192.168.1.1", false),
+            ("198.18.0.1", false),
+            ("198.19.255.255", false),
+            ("198.20.0.1", true),
+            ("224.0.0.1", false),
+            ("239.255.255.250", false),
+            ("240.0.0.1", false),
+            ("255.255.255.255", false),
+            ("2606:4700:4700::1111", true),
+            ("::", false),
+            ("::1", false),
+            ("::127.0.0.1", false),
+            ("::ffff:127.0.0.1", false),
+            ("::ffff:8.8.8.8", true),
+            ("64:ff9b::10.0.0.1", false),
+            ("64:ff9b::8.8.8.8", true),
+            ("64:ff9b:1::1", false),
+            ("2002:c0a8:0101::1", false),
+            ("2002:0808:0808::1", true),
+            ("2001::1", false),
+            ("2001:db8::1", false),
+            ("fc00::1", false),
+            ("fd12:3456::1", false),
+            ("fe80::1", false),
+            ("fec0::1", false),
+            ("ff02::1", false),
+        ];
+        for (address, public) in cases {
+            let ip: IpAddr = address.parse().unwrap();
+            assert_eq!(is_public_address(ip), public, "{}", address);
+        }
+    }
+}
@@ -1,10 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Emitter, Manager, Runtime};
 use thiserror::Error;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, Utc};
 use reqwest::Client;
+use rusqlite::{params, Connection};
+
+use crate::backup::{self, BackupReason};
+use crate::store::{self, PromptStore, StoreError, PROMPT_COLUMNS};
 
 #[derive(Debug, Error)]
 pub enum FeishuSyncError {
@@ -18,6 +22,8 @@ pub enum FeishuSyncError {
     FeishuApiError { code: i32, msg: String },
     #[error("URL解析失败: {0}")]
     UrlParseError(String),
+    #[error("本地数据读写失败: {0}")]
+    StoreError(#[from] StoreError),
 }
 
 /// 飞书配置结构
@@ -44,6 +50,8 @@ pub struct PromptRecord {
     pub last_used: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub record_id: Option<String>, // 用于临时存储飞书的记录ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>, // 本地已移入回收站的时间，不同步到云端
 }
 
 /// 同步结果统计
@@ -135,7 +143,7 @@ pub async fn save_feishu_config<R: Runtime>(
     std::fs::write(config_file, config_json)
         .map_err(|e| format!("写入配置文件失败: {}", e))?;
 
-    println!("飞书配置已保存");
+    eprintln!("飞书配置已保存");
     Ok(())
 }
 
@@ -200,7 +208,7 @@ pub async fn get_feishu_table_fields<R: Runtime>(
         config.app_token, config.table_id
     );
     
-    println!("获取字段信息URL: {}", url);
+    eprintln!("获取字段信息URL: {}", url);
     
     let response = client
         .get(&url)
@@ -212,7 +220,7 @@ pub async fn get_feishu_table_fields<R: Runtime>(
     let response_text = response.text().await
         .map_err(|e| format!("读取响应失败: {}", e))?;
     
-    println!("字段信息响应: {}", response_text);
+    eprintln!("字段信息响应: {}", response_text);
     Ok(response_text)
 }
 
@@ -221,19 +229,19 @@ pub async fn get_feishu_table_fields<R: Runtime>(
 pub async fn test_feishu_connection<R: Runtime>(
     app_handle: AppHandle<R>,
 ) -> Result<String, String> {
-    println!("开始测试飞书连接...");
+    eprintln!("开始测试飞书连接...");
     
     let config = load_feishu_config(&app_handle).await
         .map_err(|e| {
-            println!("加载配置失败: {}", e);
+            eprintln!("加载配置失败: {}", e);
             format!("加载配置失败: {}", e)
         })?
         .ok_or_else(|| {
-            println!("配置未设置");
+            eprintln!("配置未设置");
             "配置未设置".to_string()
         })?;
 
-    println!("配置加载成功，App ID: {}, Base URL: {}", config.app_id, config.base_url);
+    eprintln!("配置加载成功，App ID: {}, Base URL: {}", config.app_id, config.base_url);
 
     let client = Client::new();
     let access_token = get_tenant_access_token(&client, &config.app_id, &config.app_secret)
@@ -242,11 +250,11 @@ pub async fn test_feishu_connection<R: Runtime>(
 
     match list_all_records(&client, &access_token, &config.app_token, &config.table_id).await {
         Ok(records) => {
-            println!("连接测试完全成功，获取到 {} 条记录", records.len());
+            eprintln!("连接测试完全成功，获取到 {} 条记录", records.len());
             Ok(format!("连接测试成功！找到 {} 条记录，飞书云同步可以正常使用", records.len()))
         },
         Err(e) => {
-            println!("获取记录失败: {}", e);
+            eprintln!("获取记录失败: {}", e);
             Err(format!("连接测试失败: {}", e))
         }
     }
@@ -257,60 +265,87 @@ pub async fn test_feishu_connection<R: Runtime>(
 pub async fn trigger_sync<R: Runtime>(
     app_handle: AppHandle<R>,
 ) -> Result<SyncResult, String> {
+    sync_now(&app_handle).await
+}
+
+/// 完全在 Rust 端执行一次同步，不需要前端提供本地数据
+pub(crate) async fn sync_now<R: Runtime>(app_handle: &AppHandle<R>) -> Result<SyncResult, String> {
+    let config_dir = get_config_dir(app_handle).map_err(|e| format!("加载配置失败: {}", e))?;
+    let store = app_handle.state::<PromptStore>();
+    let (result, changed_ids) = sync_store(&store, &config_dir).await?;
+    if !changed_ids.is_empty() {
+        crate::notify_prompts_changed(app_handle, &changed_ids);
+    }
+    Ok(result)
+}
+
+/// 使用指定的数据库和配置目录执行同步，返回结果和本地发生变化的提示词 ID
+///
+/// 不依赖 Tauri，命令行工具也通过这里同步。
+pub(crate) async fn sync_store(
+    store: &PromptStore,
+    config_dir: &Path,
+) -> Result<(SyncResult, Vec<String>), String> {
     // 加载配置
-    let config = load_feishu_config(&app_handle).await
+    let config = read_feishu_config(config_dir)
         .map_err(|e| format!("加载配置失败: {}", e))?
         .ok_or_else(|| "配置未设置".to_string())?;
 
     if !config.enabled {
         return Err("同步功能已禁用".to_string());
     }
+    backup::before_change(store, BackupReason::Sync)?;
 
-    println!("开始同步操作...");
+    eprintln!("开始同步操作...");
     
-    match perform_sync(&app_handle, &config).await {
-        Ok(result) => {
-            println!("同步完成: {:?}", result);
-            Ok(result)
+    match perform_sync(store, &config).await {
+        Ok((result, changed_ids)) => {
+            eprintln!("同步完成: {:?}", result);
+            Ok((result, changed_ids))
         }
         Err(e) => {
             let error_msg = format!("同步失败: {}", e);
-            println!("{}", error_msg);
-            Ok(SyncResult {
-                success: false,
-                message: error_msg,
-                local_created: 0,
-                local_updated: 0,
-                remote_created: 0,
-                remote_updated: 0,
-                total_processed: 0,
-            })
+            eprintln!("{}", error_msg);
+            Ok((
+                SyncResult {
+                    success: false,
+                    message: error_msg,
+                    local_created: 0,
+                    local_updated: 0,
+                    remote_created: 0,
+                    remote_updated: 0,
+                    total_processed: 0,
+                },
+                Vec::new(),
+            ))
         }
     }
 }
 
 /// 执行核心同步逻辑
-async fn perform_sync<R: Runtime>(
-    app_handle: &AppHandle<R>,
+///
+/// 本地数据直接读写数据库，返回结果和本地发生变化的提示词 ID。
+async fn perform_sync(
+    store: &PromptStore,
     config: &FeishuConfig,
-) -> Result<SyncResult, FeishuSyncError> {
+) -> Result<(SyncResult, Vec<String>), FeishuSyncError> {
     // 1. 获取访问令牌
     let client = Client::new();
     let access_token = get_tenant_access_token(&client, &config.app_id, &config.app_secret).await?;
     
     // 2. 获取云端数据
-    println!("正在获取云端数据...");
+    eprintln!("正在获取云端数据...");
     let remote_records = list_all_records(&client, &access_token, &config.app_token, &config.table_id).await?;
-    println!("获取到 {} 条云端记录", remote_records.len());
+    eprintln!("获取到 {} 条云端记录", remote_records.len());
     
     // 3. 获取本地数据
-    println!("正在获取本地数据...");
-    let local_records = get_local_prompts(app_handle).await?;
-    println!("获取到 {} 条本地记录", local_records.len());
+    eprintln!("正在获取本地数据...");
+    let local_records = get_local_prompts(&store.open()?)?;
+    eprintln!("获取到 {} 条本地记录", local_records.len());
     
     // 4. 执行同步算法
     let sync_plan = calculate_sync_plan(&local_records, &remote_records);
-    println!("同步计划: 本地创建{}条, 本地更新{}条, 云端创建{}条, 云端更新{}条", 
+    eprintln!("同步计划: 本地创建{}条, 本地更新{}条, 云端创建{}条, 云端更新{}条", 
              sync_plan.to_create_local.len(),
              sync_plan.to_update_local.len(),
              sync_plan.to_create_remote.len(),
@@ -341,35 +376,40 @@ async fn perform_sync<R: Runtime>(
         result.remote_updated = count as u32;
     }
 
+    let changed_ids: Vec<String> = sync_plan
+        .to_create_local
+        .iter()
+        .chain(sync_plan.to_update_local.iter())
+        .map(|record| record.id.clone())
+        .collect();
+
     // 创建到本地
     if !sync_plan.to_create_local.is_empty() {
-        let count = sync_plan.to_create_local.len();
-        create_local_prompts(app_handle, sync_plan.to_create_local).await?;
-        result.local_created = count as u32;
+        create_local_prompts(&store.open()?, &sync_plan.to_create_local)?;
+        result.local_created = sync_plan.to_create_local.len() as u32;
     }
 
     // 更新到本地
     if !sync_plan.to_update_local.is_empty() {
-        let count = sync_plan.to_update_local.len();
-        update_local_prompts(app_handle, sync_plan.to_update_local).await?;
-        result.local_updated = count as u32;
+        update_local_prompts(&store.open()?, &sync_plan.to_update_local)?;
+        result.local_updated = sync_plan.to_update_local.len() as u32;
     }
 
     result.total_processed = result.local_created + result.local_updated + result.remote_created + result.remote_updated;
     
-    Ok(result)
+    Ok((result, changed_ids))
 }
 
 /// 同步计划
-struct SyncPlan {
-    to_create_local: Vec<PromptRecord>,
-    to_update_local: Vec<PromptRecord>,
-    to_create_remote: Vec<PromptRecord>,
-    to_update_remote: Vec<(String, PromptRecord)>, // (record_id, prompt_record)
+pub(crate) struct SyncPlan {
+    pub to_create_local: Vec<PromptRecord>,
+    pub to_update_local: Vec<PromptRecord>,
+    pub to_create_remote: Vec<PromptRecord>,
+    pub to_update_remote: Vec<(String, PromptRecord)>, // (record_id, prompt_record)
 }
 
-/// 计算同步计划
-fn calculate_sync_plan(local_records: &[PromptRecord], remote_records: &[PromptRecord]) -> SyncPlan {
+/// 计算同步计划（Markdown 目录同步也使用，record_id 为文件路径）
+pub(crate) fn calculate_sync_plan(local_records: &[PromptRecord], remote_records: &[PromptRecord]) -> SyncPlan {
     let local_map: HashMap<String, &PromptRecord> = local_records.iter()
         .map(|r| (r.id.clone(), r))
         .collect();
@@ -390,6 +430,10 @@ fn calculate_sync_plan(local_records: &[PromptRecord], remote_records: &[PromptR
 
     // 遍历本地记录，决定是否需要创建或更新到云端
     for local_record in local_records {
+        // 已移入回收站的提示词不推送到云端
+        if local_record.deleted_at.is_some() {
+            continue;
+        }
         match remote_map_by_custom_id.get(&local_record.id) {
             None => {
                 // 本地有，云端没有 -> 创建到云端
@@ -405,7 +449,7 @@ fn calculate_sync_plan(local_records: &[PromptRecord], remote_records: &[PromptR
                     } else {
                         // 这是一个异常情况：在云端找到了匹配的记录，但它没有 record_id
                         // 这可能意味着解析出了问题，或者是一个没有被正确创建的记录
-                        println!("警告: 云端记录 {} (自定义ID: {}) 缺少 feishu_record_id，无法更新。", remote_record.title, remote_record.id);
+                        eprintln!("警告: 云端记录 {} (自定义ID: {}) 缺少 feishu_record_id，无法更新。", remote_record.title, remote_record.id);
                     }
                 }
             }
@@ -419,6 +463,9 @@ fn calculate_sync_plan(local_records: &[PromptRecord], remote_records: &[PromptR
                 // 云端有，本地没有 -> 创建到本地
                 plan.to_create_local.push(remote_record.clone());
             }
+            Some(local_record) if local_record.deleted_at.is_some() => {
+                // 本地已删除（在回收站中），不从云端恢复
+            }
             Some(local_record) => {
                 // 本地和云端都存在，比较更新时间
                 if remote_record.updated_at > local_record.updated_at {
@@ -478,7 +525,7 @@ fn parse_feishu_base_url(url: &str) -> Result<(String, String), FeishuSyncError>
         FeishuSyncError::UrlParseError("无法从URL中提取table_id，请确保URL包含?table=参数".to_string())
     )?;
 
-    println!("成功解析URL - app_token: {}, table_id: {}", app_token, table_id);
+    eprintln!("成功解析URL - app_token: {}, table_id: {}", app_token, table_id);
     Ok((app_token, table_id))
 }
 
@@ -487,6 +534,11 @@ async fn load_feishu_config<R: Runtime>(
     app_handle: &AppHandle<R>,
 ) -> Result<Option<FeishuConfig>, FeishuSyncError> {
     let config_dir = get_config_dir(app_handle)?;
+    read_feishu_config(&config_dir)
+}
+
+/// 从指定的配置目录读取飞书配置
+pub(crate) fn read_feishu_config(config_dir: &Path) -> Result<Option<FeishuConfig>, FeishuSyncError> {
     let config_file = config_dir.join("feishu_config.json");
     
     if !config_file.exists() {
@@ -512,7 +564,7 @@ async fn get_tenant_access_token(
         "app_secret": app_secret
     });
 
-    println!("正在获取访问令牌，App ID: {}", app_id);
+    eprintln!("正在获取访问令牌，App ID: {}", app_id);
 
     let response = client
         .post(url)
@@ -521,17 +573,17 @@ async fn get_tenant_access_token(
         .send()
         .await?;
 
-    println!("收到HTTP响应，状态码: {}", response.status());
+    eprintln!("收到HTTP响应，状态码: {}", response.status());
 
     // 先获取原始响应文本，便于调试
     let response_text = response.text().await?;
-    println!("原始API响应: {}", response_text);
+    eprintln!("原始API响应: {}", response_text);
 
     // 首先尝试解析为通用的飞书API响应来检查是否有错误
     let api_response: serde_json::Value = serde_json::from_str(&response_text)
         .map_err(|e| {
-            println!("JSON解析失败: {}", e);
-            println!("尝试解析的文本: {}", response_text);
+            eprintln!("JSON解析失败: {}", e);
+            eprintln!("尝试解析的文本: {}", response_text);
             FeishuSyncError::JsonError(e)
         })?;
 
@@ -550,7 +602,7 @@ async fn get_tenant_access_token(
                 _ => &format!("飞书API错误 (代码: {}): {}", code, msg)
             };
             
-                         println!("飞书API返回错误: {} - {}", code, msg);
+                         eprintln!("飞书API返回错误: {} - {}", code, msg);
              return Err(FeishuSyncError::FeishuApiError {
                  code: code as i32,
                  msg: error_msg.to_string(),
@@ -561,74 +613,128 @@ async fn get_tenant_access_token(
     // 如果没有错误，尝试解析访问令牌
     let token_response: TenantTokenApiResponse = serde_json::from_str(&response_text)
         .map_err(|e| {
-            println!("解析访问令牌响应失败: {}", e);
+            eprintln!("解析访问令牌响应失败: {}", e);
             FeishuSyncError::JsonError(e)
         })?;
 
-    println!("访问令牌获取成功，过期时间: {} 秒", token_response.expire);
+    eprintln!("访问令牌获取成功，过期时间: {} 秒", token_response.expire);
     Ok(token_response.tenant_access_token)
 }
 
-/// 获取本地提示词数据
-async fn get_local_prompts<R: Runtime>(_app_handle: &AppHandle<R>) -> Result<Vec<PromptRecord>, FeishuSyncError> {
-    println!("开始获取本地提示词数据...");
-    
-    // 在 Tauri v2 中，建议通过 JavaScript API 调用数据库
-    // 这里我们返回一个简化的实现，实际的数据库操作应该在前端完成
-    // 然后通过 IPC 传递给 Rust 端进行同步操作
-    
-    // 暂时返回空的 Vec，等待前端提供数据
-    println!("获取本地提示词数据 - 当前实现需要前端配合");
-    Ok(Vec::new())
+/// 解析本地数据库中的时间，兼容 SQLite CURRENT_TIMESTAMP 的默认格式
+pub(crate) fn parse_local_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+                .map(|time| time.and_utc())
+                .ok()
+        })
 }
 
-/// 创建本地提示词
-async fn create_local_prompts<R: Runtime>(
-    _app_handle: &AppHandle<R>,
-    records: Vec<PromptRecord>,
-) -> Result<(), FeishuSyncError> {
-    if records.is_empty() {
-        return Ok(());
+/// 本地数据库使用的时间格式
+fn format_local_time(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// 解析记录中的标签，兼容逗号分隔的旧格式
+fn parse_record_tags(tags: &str) -> Vec<String> {
+    serde_json::from_str::<Vec<String>>(tags).unwrap_or_else(|_| {
+        tags.split(',')
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect()
+    })
+}
+
+/// 从数据库读取本地提示词（包括回收站中的，用于避免从云端恢复）
+pub(crate) fn get_local_prompts(conn: &Connection) -> Result<Vec<PromptRecord>, StoreError> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM prompts", PROMPT_COLUMNS))?;
+    let prompts = stmt
+        .query_map([], store::prompt_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut records = Vec::with_capacity(prompts.len());
+    for prompt in prompts {
+        let tags = store::prompt_tag_names(conn, &prompt.id)?;
+        let created_at = parse_local_time(&prompt.created_at).unwrap_or_default();
+        records.push(PromptRecord {
+            tags: serde_json::to_string(&tags).unwrap_or_else(|_| "[]".to_string()),
+            is_favorite: prompt.is_favorite,
+            updated_at: parse_local_time(&prompt.updated_at).unwrap_or(created_at),
+            last_used: prompt
+                .last_used_at
+                .as_deref()
+                .and_then(parse_local_time)
+                .or(Some(created_at)),
+            deleted_at: prompt.deleted_at.as_deref().and_then(parse_local_time),
+            record_id: None,
+            created_at,
+            id: prompt.id,
+            title: prompt.title,
+            content: prompt.content,
+        });
     }
+    Ok(records)
+}
 
-    println!("创建本地提示词，记录数: {}", records.len());
-    
-    // 在 Tauri v2 中，数据库操作应该通过前端 JavaScript API 完成
-    // 这里我们可以发送事件给前端，让前端处理数据库操作
-    
+/// 在本地创建云端新增的提示词
+pub(crate) fn create_local_prompts(conn: &Connection, records: &[PromptRecord]) -> Result<(), StoreError> {
+    eprintln!("创建本地提示词，记录数: {}", records.len());
+
+    let tx = conn.unchecked_transaction()?;
     for record in records {
-        println!("需要创建提示词: {} - {}", record.id, record.title);
-        
-        // 这里可以通过事件发送给前端处理
-        // app_handle.emit_all("create_prompt", &record).ok();
+        let created_at = format_local_time(&record.created_at);
+        let last_used_at = record
+            .last_used
+            .as_ref()
+            .map(format_local_time)
+            .unwrap_or_else(|| created_at.clone());
+        tx.execute(
+            "INSERT INTO prompts (id, title, content, is_favorite, created_at, updated_at, last_used_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                record.id,
+                record.title,
+                record.content,
+                record.is_favorite as i64,
+                created_at,
+                format_local_time(&record.updated_at),
+                last_used_at
+            ],
+        )?;
+        store::set_prompt_tags(&tx, &record.id, &parse_record_tags(&record.tags))?;
+        eprintln!("成功创建本地记录: {}", record.title);
     }
-    
-    println!("提示词创建完成（需要前端配合）");
+    tx.commit()?;
     Ok(())
 }
 
-/// 更新本地提示词
-async fn update_local_prompts<R: Runtime>(
-    _app_handle: &AppHandle<R>,
-    records: Vec<PromptRecord>,
-) -> Result<(), FeishuSyncError> {
-    if records.is_empty() {
-        return Ok(());
-    }
+/// 用云端较新的内容更新本地提示词
+pub(crate) fn update_local_prompts(conn: &Connection, records: &[PromptRecord]) -> Result<(), StoreError> {
+    eprintln!("更新本地提示词，记录数: {}", records.len());
 
-    println!("更新本地提示词，记录数: {}", records.len());
-    
-    // 在 Tauri v2 中，数据库操作应该通过前端 JavaScript API 完成
-    // 这里我们可以发送事件给前端，让前端处理数据库操作
-    
+    let tx = conn.unchecked_transaction()?;
     for record in records {
-        println!("需要更新提示词: {} - {}", record.id, record.title);
-        
-        // 这里可以通过事件发送给前端处理
-        // app_handle.emit_all("update_prompt", &record).ok();
+        // 云端没有使用时间时保留本地的值
+        tx.execute(
+            "UPDATE prompts SET title = ?1, content = ?2, is_favorite = ?3, updated_at = ?4,
+                    last_used_at = COALESCE(?5, last_used_at)
+             WHERE id = ?6",
+            params![
+                record.title,
+                record.content,
+                record.is_favorite as i64,
+                format_local_time(&record.updated_at),
+                record.last_used.as_ref().map(format_local_time),
+                record.id
+            ],
+        )?;
+        store::set_prompt_tags(&tx, &record.id, &parse_record_tags(&record.tags))?;
+        eprintln!("成功更新本地记录: {}", record.title);
     }
-    
-    println!("提示词更新完成（需要前端配合）");
+    tx.commit()?;
     Ok(())
 }
 
@@ -638,12 +744,12 @@ pub async fn sync_with_local_data<R: Runtime>(
     app_handle: AppHandle<R>,
     local_prompts: Vec<PromptRecord>, // 从前端传递的本地数据
 ) -> Result<SyncResult, String> {
-    println!("开始同步操作（带本地数据）...");
-    println!("收到本地数据: {} 条", local_prompts.len());
+    eprintln!("开始同步操作（带本地数据）...");
+    eprintln!("收到本地数据: {} 条", local_prompts.len());
     
     // 打印本地数据详情
     for (i, prompt) in local_prompts.iter().enumerate() {
-        println!("本地数据 {}: {} - {}", i + 1, prompt.id, prompt.title);
+        eprintln!("本地数据 {}: {} - {}", i + 1, prompt.id, prompt.title);
     }
     
     // 加载配置
@@ -654,6 +760,7 @@ pub async fn sync_with_local_data<R: Runtime>(
     if !config.enabled {
         return Err("同步功能已禁用".to_string());
     }
+    backup::before_change(&app_handle.state::<PromptStore>(), BackupReason::Sync)?;
     
     let client = Client::new();
     
@@ -663,22 +770,22 @@ pub async fn sync_with_local_data<R: Runtime>(
         .map_err(|e| format!("获取访问令牌失败: {}", e))?;
     
     // 获取云端数据
-    println!("正在获取云端数据...");
+    eprintln!("正在获取云端数据...");
     let remote_records = list_all_records(&client, &access_token, &config.app_token, &config.table_id)
         .await
         .map_err(|e| format!("获取云端数据失败: {}", e))?;
     
-    println!("获取到 {} 条云端记录", remote_records.len());
+    eprintln!("获取到 {} 条云端记录", remote_records.len());
     
     // 打印云端数据详情
     for (i, record) in remote_records.iter().enumerate() {
-        println!("云端数据 {}: {} - {}", i + 1, record.id, record.title);
+        eprintln!("云端数据 {}: {} - {}", i + 1, record.id, record.title);
     }
     
     // 比较并计算同步操作
     let sync_plan = calculate_sync_plan(&local_prompts, &remote_records);
     
-    println!("同步计划: 本地创建{}条, 本地更新{}条, 云端创建{}条, 云端更新{}条", 
+    eprintln!("同步计划: 本地创建{}条, 本地更新{}条, 云端创建{}条, 云端更新{}条", 
              sync_plan.to_create_local.len(), 
              sync_plan.to_update_local.len(),
              sync_plan.to_create_remote.len(), 
@@ -696,15 +803,15 @@ pub async fn sync_with_local_data<R: Runtime>(
     
     // 执行云端创建操作
     if !sync_plan.to_create_remote.is_empty() {
-        println!("开始向云端创建 {} 条记录", sync_plan.to_create_remote.len());
+        eprintln!("开始向云端创建 {} 条记录", sync_plan.to_create_remote.len());
         match create_remote_records(&client, &access_token, &config.app_token, &config.table_id, sync_plan.to_create_remote.clone()).await {
             Ok(count) => {
                 sync_result.remote_created = count as u32;
-                println!("成功向云端创建 {} 条记录", count);
+                eprintln!("成功向云端创建 {} 条记录", count);
             }
             Err(e) => {
                 let error_msg = format!("向云端创建记录失败: {}", e);
-                println!("{}", error_msg);
+                eprintln!("{}", error_msg);
                 sync_result.success = false;
                 sync_result.message = error_msg;
             }
@@ -713,15 +820,15 @@ pub async fn sync_with_local_data<R: Runtime>(
     
     // 执行云端更新操作  
     if !sync_plan.to_update_remote.is_empty() {
-        println!("开始向云端更新 {} 条记录", sync_plan.to_update_remote.len());
+        eprintln!("开始向云端更新 {} 条记录", sync_plan.to_update_remote.len());
         match update_remote_records(&client, &access_token, &config.app_token, &config.table_id, sync_plan.to_update_remote.clone()).await {
             Ok(count) => {
                 sync_result.remote_updated = count as u32;
-                println!("成功向云端更新 {} 条记录", count);
+                eprintln!("成功向云端更新 {} 条记录", count);
             }
             Err(e) => {
                 let error_msg = format!("向云端更新记录失败: {}", e);
-                println!("{}", error_msg);
+                eprintln!("{}", error_msg);
                 sync_result.success = false;
                 sync_result.message = error_msg;
             }
@@ -730,28 +837,28 @@ pub async fn sync_with_local_data<R: Runtime>(
     
     // 计算需要在本地创建/更新的记录（通过事件通知前端）
     if !sync_plan.to_create_local.is_empty() {
-        println!("需要在本地创建 {} 条记录", sync_plan.to_create_local.len());
+        eprintln!("需要在本地创建 {} 条记录", sync_plan.to_create_local.len());
         sync_result.local_created = sync_plan.to_create_local.len() as u32;
         
         // 发送事件给前端，让前端处理本地数据库操作
         if let Err(e) = app_handle.emit("sync-create-local", &sync_plan.to_create_local) {
-            println!("发送本地创建事件失败: {}", e);
+            eprintln!("发送本地创建事件失败: {}", e);
         }
     }
     
     if !sync_plan.to_update_local.is_empty() {
-        println!("需要在本地更新 {} 条记录", sync_plan.to_update_local.len());
+        eprintln!("需要在本地更新 {} 条记录", sync_plan.to_update_local.len());
         sync_result.local_updated = sync_plan.to_update_local.len() as u32;
         
         // 发送事件给前端，让前端处理本地数据库操作
         if let Err(e) = app_handle.emit("sync-update-local", &sync_plan.to_update_local) {
-            println!("发送本地更新事件失败: {}", e);
+            eprintln!("发送本地更新事件失败: {}", e);
         }
     }
     
     sync_result.total_processed = sync_result.local_created + sync_result.local_updated + sync_result.remote_created + sync_result.remote_updated;
     
-    println!("同步完成: {:?}", sync_result);
+    eprintln!("同步完成: {:?}", sync_result);
     Ok(sync_result)
 }
 
@@ -767,7 +874,7 @@ async fn create_remote_records(
         return Ok(0);
     }
 
-    println!("开始向云端创建 {} 条记录", records.len());
+    eprintln!("开始向云端创建 {} 条记录", records.len());
 
     let url = format!(
         "https://open.feishu.cn/open-apis/bitable/v1/apps/{}/tables/{}/records/batch_create",
@@ -808,7 +915,7 @@ async fn create_remote_records(
         "records": feishu_records
     });
 
-    println!("创建记录请求payload: {}", serde_json::to_string_pretty(&payload).unwrap_or_default());
+    eprintln!("创建记录请求payload: {}", serde_json::to_string_pretty(&payload).unwrap_or_default());
 
     let response = client
         .post(&url)
@@ -818,14 +925,14 @@ async fn create_remote_records(
         .send()
         .await?;
 
-    println!("创建记录响应状态码: {}", response.status());
+    eprintln!("创建记录响应状态码: {}", response.status());
 
     let response_text = response.text().await?;
-    println!("创建记录响应: {}", response_text);
+    eprintln!("创建记录响应: {}", response_text);
 
     let api_response: serde_json::Value = serde_json::from_str(&response_text)
         .map_err(|e| {
-            println!("创建记录响应JSON解析失败: {}", e);
+            eprintln!("创建记录响应JSON解析失败: {}", e);
             FeishuSyncError::JsonError(e)
         })?;
 
@@ -841,7 +948,7 @@ async fn create_remote_records(
 
     // 返回实际创建的记录数
     let created_count = records.len() as i32;
-    println!("成功创建 {} 条记录", created_count);
+    eprintln!("成功创建 {} 条记录", created_count);
     Ok(created_count)
 }
 
@@ -857,7 +964,7 @@ async fn update_remote_records(
         return Ok(0);
     }
 
-    println!("开始向云端批量更新 {} 条记录", records.len());
+    eprintln!("开始向云端批量更新 {} 条记录", records.len());
 
     let url = format!(
         "https://open.feishu.cn/open-apis/bitable/v1/apps/{}/tables/{}/records/batch_update",
@@ -901,7 +1008,7 @@ async fn update_remote_records(
         "records": feishu_records
     });
 
-    println!("更新记录请求payload: {}", serde_json::to_string_pretty(&payload).unwrap_or_default());
+    eprintln!("更新记录请求payload: {}", serde_json::to_string_pretty(&payload).unwrap_or_default());
 
     let response = client
         .post(&url)
@@ -911,14 +1018,14 @@ async fn update_remote_records(
         .send()
         .await?;
 
-    println!("更新记录响应状态码: {}", response.status());
+    eprintln!("更新记录响应状态码: {}", response.status());
 
     let response_text = response.text().await?;
-    println!("更新记录响应: {}", response_text);
+    eprintln!("更新记录响应: {}", response_text);
 
     let api_response: FeishuApiResponse<UpdateRecordsResponse> = serde_json::from_str(&response_text)
         .map_err(|e| {
-            println!("更新记录响应JSON解析失败: {}", e);
+            eprintln!("更新记录响应JSON解析失败: {}", e);
             FeishuSyncError::JsonError(e)
         })?;
     
@@ -930,7 +1037,7 @@ async fn update_remote_records(
     }
 
     let updated_count = api_response.data.map_or(0, |d| d.records.len());
-    println!("总共成功更新 {} 条记录", updated_count);
+    eprintln!("总共成功更新 {} 条记录", updated_count);
     Ok(updated_count as i32)
 }
 
@@ -960,7 +1067,7 @@ async fn list_all_records(
             url.push_str(&query_params.join("&"));
         }
 
-        println!("正在请求表格记录，URL: {}", url);
+        eprintln!("正在请求表格记录，URL: {}", url);
 
         let response = client
             .get(&url)
@@ -969,21 +1076,21 @@ async fn list_all_records(
             .send()
             .await?;
 
-        println!("收到表格记录响应，状态码: {}", response.status());
+        eprintln!("收到表格记录响应，状态码: {}", response.status());
 
         // 先获取原始响应文本，便于调试
         let response_text = response.text().await?;
-        println!("原始表格记录API响应: {}", response_text);
+        eprintln!("原始表格记录API响应: {}", response_text);
 
         // 尝试解析JSON
         let api_response: FeishuApiResponse<RecordsResponse> = serde_json::from_str(&response_text)
             .map_err(|e| {
-                println!("表格记录JSON解析失败: {}", e);
-                println!("尝试解析的文本: {}", response_text);
+                eprintln!("表格记录JSON解析失败: {}", e);
+                eprintln!("尝试解析的文本: {}", response_text);
                 FeishuSyncError::JsonError(e)
             })?;
 
-        println!("表格记录API响应解析成功，code: {}, msg: {}", api_response.code, api_response.msg);
+        eprintln!("表格记录API响应解析成功，code: {}, msg: {}", api_response.code, api_response.msg);
 
         if api_response.code != 0 {
             let error_msg = match api_response.code {
@@ -1015,7 +1122,7 @@ async fn list_all_records(
             match parse_record_from_feishu(item) {
                 Ok(record) => all_records.push(record),
                 Err(e) => {
-                    println!("跳过无法解析的记录: {}", e);
+                    eprintln!("跳过无法解析的记录: {}", e);
                     // 可以选择在这里记录更详细的错误日志
                 }
             }
@@ -1094,5 +1201,6 @@ fn parse_record_from_feishu(item: serde_json::Value) -> Result<PromptRecord, Str
         updated_at,
         last_used,
         record_id: Some(record_id), // 存储飞书的 record_id
+        deleted_at: None,
     })
 } 
\ No newline at end of file
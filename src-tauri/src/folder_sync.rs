@@ -0,0 +1,690 @@
+use chrono::{DateTime, SubsecRound, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_dialog::DialogExt;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::backup::{self, BackupReason};
+use crate::feishu_sync::{self, PromptRecord};
+use crate::markdown::{self, MarkdownError, MarkdownPrompt};
+use crate::settings::load_app_settings;
+use crate::store::{now_timestamp, PromptStore, StoreError};
+use crate::trash;
+
+/// 上次同步后目录中各文件的内容摘要，保存在配置目录下
+const STATE_FILE: &str = "folder_sync_state.json";
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Error)]
+pub enum FolderSyncError {
+    #[error("文件读写失败: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Markdown(#[from] MarkdownError),
+    #[error("{0}")]
+    Store(#[from] StoreError),
+    #[error("同步状态格式错误: {0}")]
+    State(#[from] serde_json::Error),
+    #[error("同步目录不存在: {}", .0.display())]
+    MissingFolder(PathBuf),
+    #[error("同步目录中没有提示词文件，为避免清空本地提示词已跳过: {}", .0.display())]
+    EmptyFolder(PathBuf),
+    #[error("上次同步过的文件无法读取或解析，修正后才会继续同步: {}", .0.display())]
+    BrokenFile(PathBuf),
+}
+
+/// Markdown 目录同步设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FolderSyncSettings {
+    /// 开启后监听目录和数据库的变化并自动同步
+    pub enabled: bool,
+    /// 同步目录，例如 git 仓库或 Obsidian 库中的子目录
+    pub path: String,
+}
+
+/// 上次同步的状态，用于区分“对方新增”和“本方删除”
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncState {
+    pub path: String,
+    /// 提示词 ID -> 上次同步后的文件
+    pub files: BTreeMap<String, SyncedFile>,
+}
+
+/// 上次同步后的文件路径和内容摘要
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncedFile {
+    pub path: PathBuf,
+    pub hash: u64,
+}
+
+/// 同步结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FolderSyncResult {
+    pub local_created: u32,
+    pub local_updated: u32,
+    /// 文件被删除后移入回收站的提示词
+    pub local_deleted: u32,
+    pub files_written: u32,
+    pub files_removed: u32,
+    #[serde(skip)]
+    pub changed_ids: Vec<String>,
+}
+
+/// 管理目录同步：当前设置和同步锁
+#[derive(Default)]
+pub struct FolderSync {
+    settings: Mutex<FolderSyncSettings>,
+    /// 监听线程和手动同步不能同时进行
+    running: Mutex<()>,
+}
+
+/// 目录中的一个提示词文件
+struct FolderFile {
+    prompt: MarkdownPrompt,
+    record: PromptRecord,
+    /// 文件中没有 ID（或与其他文件重复），同步时需要写回新分配的 ID
+    needs_id: bool,
+}
+
+/// 文件内容摘要（FNV-1a），只用于判断文件在两次同步之间是否被修改
+fn content_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn modified_time(path: &Path) -> Result<DateTime<Utc>, std::io::Error> {
+    let modified = std::fs::metadata(path)?.modified()?;
+    Ok(DateTime::<Utc>::from(modified).trunc_subsecs(3))
+}
+
+/// 目录中的文件：可以解析的文件及其原始内容，以及读取或解析失败的文件
+struct ParsedFiles {
+    files: Vec<(MarkdownPrompt, String)>,
+    broken: Vec<PathBuf>,
+}
+
+fn parse_files(dir: &Path) -> Result<ParsedFiles, FolderSyncError> {
+    let mut parsed = ParsedFiles {
+        files: Vec::new(),
+        broken: Vec::new(),
+    };
+    for path in markdown::markdown_files(dir)? {
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("跳过 {}: {}", path.display(), e);
+                parsed.broken.push(path);
+                continue;
+            }
+        };
+        match markdown::parse_file(&path, &text) {
+            Ok(prompt) => parsed.files.push((prompt, text)),
+            Err(e) => {
+                eprintln!("跳过 {}", e);
+                parsed.broken.push(path);
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+fn file_id(prompt: &MarkdownPrompt) -> Option<&str> {
+    prompt
+        .frontmatter
+        .id
+        .as_deref()
+        .filter(|id| !id.trim().is_empty())
+}
+
+/// 读取目录中的文件并转换为同步记录
+///
+/// 上次同步后被外部修改的文件，更新时间取 frontmatter 和文件修改时间中较新的一个，
+/// 这样在编辑器中修改正文（不改 updated_at）也能同步回本地。
+/// 复制文件会产生重复的 ID，只有上次同步时的那个文件沿用原 ID，其余作为新提示词。
+///
+/// 上次同步过的文件无法读取或解析时（例如正在编辑器中修改 frontmatter）中止同步，
+/// 否则它会被当作已删除，对应的提示词移入回收站，修正后的文件也会在导出时被清理。
+fn read_files(dir: &Path, state: &SyncState) -> Result<Vec<FolderFile>, FolderSyncError> {
+    let ParsedFiles { files: parsed, broken } = parse_files(dir)?;
+    if let Some(path) = broken
+        .into_iter()
+        .find(|path| state.files.values().any(|synced| &synced.path == path))
+    {
+        return Err(FolderSyncError::BrokenFile(path));
+    }
+    let owners: HashSet<&Path> = parsed
+        .iter()
+        .filter_map(|(prompt, _)| {
+            let synced = state.files.get(file_id(prompt)?)?;
+            (synced.path == prompt.path).then_some(synced.path.as_path())
+        })
+        .collect();
+    let owned_ids: HashSet<&str> = parsed
+        .iter()
+        .filter(|(prompt, _)| owners.contains(prompt.path.as_path()))
+        .filter_map(|(prompt, _)| file_id(prompt))
+        .collect();
+
+    let mut files = Vec::new();
+    let mut seen_ids = HashSet::new();
+    for (prompt, text) in &parsed {
+        let existing_id = file_id(prompt).filter(|id| {
+            !seen_ids.contains(*id)
+                && (owners.contains(prompt.path.as_path()) || !owned_ids.contains(id))
+        });
+        let needs_id = existing_id.is_none();
+        let id = existing_id
+            .map(str::to_string)
+            .unwrap_or_else(|| Uuid::now_v7().to_string());
+        seen_ids.insert(id.clone());
+
+        let path = &prompt.path;
+        let frontmatter_time = prompt
+            .frontmatter
+            .updated_at
+            .as_deref()
+            .and_then(feishu_sync::parse_local_time);
+        let modified = state
+            .files
+            .get(&id)
+            .is_some_and(|synced| synced.hash != content_hash(text));
+        let updated_at = match frontmatter_time {
+            Some(time) if !modified => time,
+            Some(time) => time.max(modified_time(path)?),
+            None => modified_time(path)?,
+        };
+        let created_at = prompt
+            .frontmatter
+            .created_at
+            .as_deref()
+            .and_then(feishu_sync::parse_local_time)
+            .unwrap_or(updated_at);
+        let tags: Vec<&str> = prompt
+            .frontmatter
+            .tags
+            .iter()
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .collect();
+
+        let record = PromptRecord {
+            id,
+            title: markdown::file_title(prompt),
+            content: prompt.content.clone(),
+            tags: serde_json::to_string(&tags)?,
+            is_favorite: prompt.frontmatter.favorite,
+            created_at,
+            updated_at,
+            last_used: None,
+            record_id: Some(path.to_string_lossy().into_owned()),
+            deleted_at: None,
+        };
+        files.push(FolderFile {
+            prompt: prompt.clone(),
+            record,
+            needs_id,
+        });
+    }
+    Ok(files)
+}
+
+/// 同步完成后目录中的文件，ID 重复时以导出时沿用的第一个文件为准
+fn synced_files(dir: &Path) -> Result<BTreeMap<String, SyncedFile>, FolderSyncError> {
+    let mut synced = BTreeMap::new();
+    for (prompt, text) in parse_files(dir)?.files {
+        if let Some(id) = file_id(&prompt) {
+            synced.entry(id.to_string()).or_insert_with(|| SyncedFile {
+                hash: content_hash(&text),
+                path: prompt.path.clone(),
+            });
+        }
+    }
+    Ok(synced)
+}
+
+/// 双向同步本地提示词和 Markdown 目录
+///
+/// 新增和修改按 `calculate_sync_plan` 的规则处理，较新的一方覆盖另一方；
+/// 删除依据上次同步的状态判断：上次同步过的文件被删除时，本地提示词移入回收站；
+/// 本地移入回收站或彻底删除的提示词，对应的文件也会删除。
+pub fn sync_folder(
+    conn: &Connection,
+    dir: &Path,
+    state: &mut SyncState,
+) -> Result<FolderSyncResult, FolderSyncError> {
+    let dir_key = dir.to_string_lossy().into_owned();
+    if state.path != dir_key {
+        // 换了目录，按首次同步处理
+        *state = SyncState {
+            path: dir_key,
+            ..Default::default()
+        };
+    }
+    if !dir.exists() {
+        if !state.files.is_empty() {
+            return Err(FolderSyncError::MissingFolder(dir.to_path_buf()));
+        }
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let local_records = feishu_sync::get_local_prompts(conn)?;
+    if local_records.is_empty() {
+        // 本地数据库是新的，不能把目录中的文件当作已删除
+        state.files.clear();
+    }
+    let mut files = read_files(dir, state)?;
+    if files.is_empty() && !state.files.is_empty() {
+        return Err(FolderSyncError::EmptyFolder(dir.to_path_buf()));
+    }
+
+    let folder_records: Vec<PromptRecord> = files.iter().map(|file| file.record.clone()).collect();
+    let plan = feishu_sync::calculate_sync_plan(&local_records, &folder_records);
+
+    // 上次同步过、本地却已彻底删除的提示词不再创建，文件在导出时清理
+    let to_create: Vec<PromptRecord> = plan
+        .to_create_local
+        .into_iter()
+        .filter(|record| !state.files.contains_key(&record.id))
+        .collect();
+    // 上次同步过、文件却已不存在的提示词移入回收站
+    let to_trash: Vec<&PromptRecord> = plan
+        .to_create_remote
+        .iter()
+        .filter(|record| state.files.contains_key(&record.id))
+        .collect();
+
+    let mut result = FolderSyncResult::default();
+    if !to_create.is_empty() {
+        feishu_sync::create_local_prompts(conn, &to_create)?;
+        result.local_created = to_create.len() as u32;
+    }
+    if !plan.to_update_local.is_empty() {
+        feishu_sync::update_local_prompts(conn, &plan.to_update_local)?;
+        result.local_updated = plan.to_update_local.len() as u32;
+    }
+    let deleted_at = now_timestamp();
+    for record in &to_trash {
+        if trash::move_to_trash(conn, &record.id, &deleted_at)? {
+            result.local_deleted += 1;
+            result.changed_ids.push(record.id.clone());
+        }
+    }
+    result.changed_ids.extend(
+        to_create
+            .iter()
+            .chain(&plan.to_update_local)
+            .map(|record| record.id.clone()),
+    );
+
+    // 先把新分配的 ID 写回原文件，导出时才能沿用这些文件而不是另建一份
+    let created_ids: HashSet<&str> = to_create.iter().map(|record| record.id.as_str()).collect();
+    for file in files.iter_mut().filter(|file| file.needs_id) {
+        if !created_ids.contains(file.record.id.as_str()) {
+            continue;
+        }
+        file.prompt.frontmatter.id = Some(file.record.id.clone());
+        let text = markdown::render_file(&file.prompt.frontmatter, &file.prompt.content)
+            .map_err(|e| MarkdownError::Frontmatter(file.prompt.path.clone(), e))?;
+        std::fs::write(&file.prompt.path, text)?;
+    }
+
+    let summary = markdown::export_folder(conn, dir, true)?;
+    result.files_written = summary.written;
+    result.files_removed = summary.removed;
+    state.files = synced_files(dir)?;
+    Ok(result)
+}
+
+fn state_path<R: Runtime>(app_handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    let config_dir = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("获取配置目录失败: {}", e))?;
+    Ok(config_dir.join(STATE_FILE))
+}
+
+fn load_state(path: &Path) -> Result<SyncState, FolderSyncError> {
+    if !path.exists() {
+        return Ok(SyncState::default());
+    }
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+fn save_state(path: &Path, state: &SyncState) -> Result<(), FolderSyncError> {
+    std::fs::write(path, serde_json::to_string_pretty(state)?)?;
+    Ok(())
+}
+
+/// 读取设置中的同步目录（启动时和保存设置后调用）
+pub fn apply_settings<R: Runtime>(app_handle: &AppHandle<R>) {
+    let settings = load_app_settings(app_handle).folder_sync;
+    *app_handle.state::<FolderSync>().settings.lock().unwrap() = settings;
+}
+
+/// 按当前设置执行一次同步
+pub fn run_sync<R: Runtime>(app_handle: &AppHandle<R>) -> Result<FolderSyncResult, String> {
+    let folder_sync = app_handle.state::<FolderSync>();
+    let settings = folder_sync.settings.lock().unwrap().clone();
+    if settings.path.trim().is_empty() {
+        return Err("未设置同步目录".to_string());
+    }
+    let _running = folder_sync.running.lock().unwrap();
+
+    let state_path = state_path(app_handle)?;
+    let mut state = load_state(&state_path).map_err(|e| format!("读取同步状态失败: {}", e))?;
+    let result = {
+        let store = app_handle.state::<PromptStore>();
+        backup::before_change(&store, BackupReason::Sync)?;
+        let conn = store.open().map_err(|e| e.to_string())?;
+        sync_folder(&conn, Path::new(settings.path.trim()), &mut state)
+            .map_err(|e| format!("目录同步失败: {}", e))?
+    };
+    save_state(&state_path, &state).map_err(|e| format!("保存同步状态失败: {}", e))?;
+
+    if !result.changed_ids.is_empty() {
+        crate::notify_prompts_changed(app_handle, &result.changed_ids);
+    }
+    Ok(result)
+}
+
+/// 目录和数据库的当前状态，有变化时触发同步
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Snapshot {
+    path: String,
+    files: Vec<(PathBuf, Option<SystemTime>, u64)>,
+    prompts: Option<PromptsSignature>,
+}
+
+/// 会影响导出文件的数据摘要
+///
+/// 不使用 `PRAGMA data_version`：每次复制都会写入 prompt_usage 并更新 last_used_at，
+/// 这些不会出现在文件中，不应触发同步。
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PromptsSignature {
+    count: i64,
+    active: i64,
+    favorites: i64,
+    max_updated_at: Option<String>,
+    max_deleted_at: Option<String>,
+    /// 提示词与标签名称的对应关系的摘要
+    tags: u64,
+}
+
+fn prompts_signature(conn: &Connection) -> rusqlite::Result<PromptsSignature> {
+    let mut signature = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(deleted_at IS NULL), 0), COALESCE(SUM(is_favorite = 1), 0),
+                MAX(updated_at), MAX(deleted_at)
+         FROM prompts",
+        [],
+        |row| {
+            Ok(PromptsSignature {
+                count: row.get(0)?,
+                active: row.get(1)?,
+                favorites: row.get(2)?,
+                max_updated_at: row.get(3)?,
+                max_deleted_at: row.get(4)?,
+                tags: 0,
+            })
+        },
+    )?;
+    let mut stmt = conn.prepare(
+        "SELECT pt.prompt_id, t.name FROM prompt_tags pt JOIN tags t ON t.id = pt.tag_id
+         ORDER BY pt.prompt_id, t.name",
+    )?;
+    let mut links = String::new();
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        links.push_str(&row.get::<_, String>(0)?);
+        links.push('\t');
+        links.push_str(&row.get::<_, String>(1)?);
+        links.push('\n');
+    }
+    signature.tags = content_hash(&links);
+    Ok(signature)
+}
+
+fn take_snapshot(path: &str, conn: &Option<Connection>) -> Snapshot {
+    let files = markdown::markdown_files(Path::new(path))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|file| {
+            let metadata = std::fs::metadata(&file).ok();
+            let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+            let len = metadata.map(|m| m.len()).unwrap_or(0);
+            (file, modified, len)
+        })
+        .collect();
+    let prompts = conn
+        .as_ref()
+        .and_then(|conn| prompts_signature(conn).ok());
+    Snapshot {
+        path: path.to_string(),
+        files,
+        prompts,
+    }
+}
+
+/// 在后台线程中监听同步目录和数据库，任一方有变化时自动同步
+///
+/// 与托盘的 watch_database 一样采用轮询；检测到变化后等一个周期确认没有继续写入再同步，
+/// 避免读到编辑器写了一半的文件。
+pub fn watch<R: Runtime>(app_handle: AppHandle<R>) {
+    std::thread::spawn(move || {
+        let mut conn: Option<Connection> = None;
+        let mut synced: Option<Snapshot> = None;
+        let mut last_seen: Option<Snapshot> = None;
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+
+            let settings = app_handle
+                .state::<FolderSync>()
+                .settings
+                .lock()
+                .unwrap()
+                .clone();
+            if !settings.enabled || settings.path.trim().is_empty() {
+                synced = None;
+                last_seen = None;
+                continue;
+            }
+            if conn.is_none() {
+                conn = app_handle.state::<PromptStore>().open().ok();
+            }
+
+            let snapshot = take_snapshot(settings.path.trim(), &conn);
+            if synced.as_ref() == Some(&snapshot) {
+                continue;
+            }
+            if last_seen.as_ref() != Some(&snapshot) {
+                last_seen = Some(snapshot);
+                continue;
+            }
+
+            match run_sync(&app_handle) {
+                Ok(result) => {
+                    if !result.changed_ids.is_empty()
+                        || result.files_written > 0
+                        || result.files_removed > 0
+                    {
+                        println!(
+                            "目录同步完成: 本地新增 {}，更新 {}，删除 {}；写入文件 {}，删除文件 {}",
+                            result.local_created,
+                            result.local_updated,
+                            result.local_deleted,
+                            result.files_written,
+                            result.files_removed
+                        );
+                    }
+                }
+                // 出错后等下一次变化再重试，不在每个周期重复报错
+                Err(e) => eprintln!("{}", e),
+            }
+            let snapshot = take_snapshot(settings.path.trim(), &conn);
+            synced = Some(snapshot.clone());
+            last_seen = Some(snapshot);
+        }
+    });
+}
+
+/// 立即同步 Markdown 目录
+#[tauri::command]
+pub async fn sync_markdown_folder<R: Runtime>(
+    app_handle: AppHandle<R>,
+) -> Result<FolderSyncResult, String> {
+    run_sync(&app_handle)
+}
+
+/// 选择同步目录，取消时返回 None
+#[tauri::command]
+pub async fn pick_sync_folder<R: Runtime>(app_handle: AppHandle<R>) -> Option<String> {
+    app_handle
+        .dialog()
+        .file()
+        .blocking_pick_folder()
+        .and_then(|path| path.into_path().ok())
+        .map(|path| path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::open_in_memory;
+    use rusqlite::params;
+
+    /// 第一次同步时本地数据的修改时间
+    const SYNCED_AT: &str = "2024-01-01T00:00:00.000Z";
+
+    /// 两条提示词，已同步到一个新的临时目录
+    fn synced_folder() -> (Connection, PathBuf, SyncState) {
+        let conn = open_in_memory();
+        for (id, title) in [("a", "Alpha"), ("b", "Beta")] {
+            conn.execute(
+                "INSERT INTO prompts (id, title, content, is_favorite, created_at, updated_at, last_used_at)
+                 VALUES (?1, ?2, ?3, 0, ?4, ?4, ?4)",
+                params![id, title, format!("{} 内容", title), SYNCED_AT],
+            )
+            .unwrap();
+        }
+        let dir = std::env::temp_dir().join(format!("promptgenie-test-{}", Uuid::now_v7()));
+        let mut state = SyncState::default();
+        sync_folder(&conn, &dir, &mut state).unwrap();
+        assert_eq!(state.files.len(), 2);
+        (conn, dir, state)
+    }
+
+    fn local_content(conn: &Connection, id: &str) -> String {
+        conn.query_row("SELECT content FROM prompts WHERE id = ?1", [id], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    fn edit_local(conn: &Connection, id: &str, content: &str, updated_at: &str) {
+        conn.execute(
+            "UPDATE prompts SET content = ?1, updated_at = ?2 WHERE id = ?3",
+            params![content, updated_at, id],
+        )
+        .unwrap();
+    }
+
+    /// 在编辑器中修改正文，不改 frontmatter 中的 updated_at
+    fn edit_file(state: &SyncState, id: &str, from: &str, to: &str) {
+        let path = &state.files[id].path;
+        let text = std::fs::read_to_string(path).unwrap();
+        assert!(text.contains(from));
+        std::fs::write(path, text.replace(from, to)).unwrap();
+    }
+
+    fn file_text(state: &SyncState, id: &str) -> String {
+        std::fs::read_to_string(&state.files[id].path).unwrap()
+    }
+
+    #[test]
+    fn external_edit_updates_local_prompt() {
+        let (conn, dir, mut state) = synced_folder();
+        edit_file(&state, "a", "Alpha 内容", "外部修改");
+
+        let result = sync_folder(&conn, &dir, &mut state).unwrap();
+        assert_eq!(result.local_updated, 1);
+        assert_eq!(result.changed_ids, vec!["a".to_string()]);
+        assert_eq!(local_content(&conn, "a"), "外部修改");
+        assert_eq!(local_content(&conn, "b"), "Beta 内容");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn local_edit_rewrites_file() {
+        let (conn, dir, mut state) = synced_folder();
+        edit_local(&conn, "a", "本地修改", "2024-06-01T00:00:00.000Z");
+
+        let result = sync_folder(&conn, &dir, &mut state).unwrap();
+        assert_eq!(result.local_updated, 0);
+        assert_eq!(result.files_written, 1);
+        assert!(file_text(&state, "a").contains("本地修改"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn deleted_file_moves_prompt_to_trash() {
+        let (conn, dir, mut state) = synced_folder();
+        std::fs::remove_file(&state.files["b"].path).unwrap();
+
+        let result = sync_folder(&conn, &dir, &mut state).unwrap();
+        assert_eq!(result.local_deleted, 1);
+        let deleted_at: Option<String> = conn
+            .query_row("SELECT deleted_at FROM prompts WHERE id = 'b'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert!(deleted_at.is_some());
+        assert!(!state.files.contains_key("b"));
+        assert!(state.files.contains_key("a"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn conflicting_edits_keep_newer_side() {
+        let (conn, dir, mut state) = synced_folder();
+        // a: 本地在同步后修改，但早于文件的修改时间，文件获胜
+        edit_local(&conn, "a", "本地修改 a", "2024-06-01T00:00:00.000Z");
+        edit_file(&state, "a", "Alpha 内容", "外部修改 a");
+        // b: 本地的修改时间晚于文件，本地获胜
+        edit_local(&conn, "b", "本地修改 b", "2999-01-01T00:00:00.000Z");
+        edit_file(&state, "b", "Beta 内容", "外部修改 b");
+
+        let result = sync_folder(&conn, &dir, &mut state).unwrap();
+        assert_eq!(result.local_updated, 1);
+        assert_eq!(local_content(&conn, "a"), "外部修改 a");
+        assert_eq!(local_content(&conn, "b"), "本地修改 b");
+        assert!(file_text(&state, "a").contains("外部修改 a"));
+        assert!(file_text(&state, "b").contains("本地修改 b"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn usage_does_not_change_prompts_signature() {
+        let (conn, dir, _) = synced_folder();
+        let before = prompts_signature(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO prompt_usage (id, prompt_id, action, source, used_at)
+             VALUES ('u1', 'a', 'copy', 'tray', '2024-02-01T00:00:00.000Z')",
+            [],
+        )
+        .unwrap();
+        assert_eq!(prompts_signature(&conn).unwrap(), before);
+
+        conn.execute("UPDATE prompts SET is_favorite = 1 WHERE id = 'a'", [])
+            .unwrap();
+        assert_ne!(prompts_signature(&conn).unwrap(), before);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
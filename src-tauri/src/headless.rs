@@ -0,0 +1,361 @@
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::backup::{self, BackupReason};
+use crate::cli::resolve_prompt;
+use crate::feishu_sync;
+use crate::integrity;
+use crate::library::{self, ImportStrategy};
+use crate::migrations;
+use crate::store::{self, PromptFilter, PromptStore};
+use crate::usage::{self, UsageAction, UsageSource};
+use crate::{palette, template};
+
+/// 与 tauri.conf.json 中的 identifier 一致，用于定位应用配置目录
+const APP_IDENTIFIER: &str = "com.chriszou.promptgenie";
+
+/// 不启动应用、直接读写数据库的命令行工具（promptgenie-cli）
+///
+/// 与运行中的应用共用同一个数据库，依靠 WAL 模式和忙等待避免冲突。
+#[derive(Debug, Parser)]
+#[command(
+    name = "promptgenie-cli",
+    version,
+    about = "PromptGenie 命令行工具，无需启动应用"
+)]
+struct HeadlessArgs {
+    /// 数据库文件，默认使用配置目录下的 promptgenie.db
+    #[arg(long, global = true)]
+    db: Option<PathBuf>,
+
+    /// 配置目录（读取飞书配置），默认与应用一致
+    #[arg(long, global = true)]
+    config_dir: Option<PathBuf>,
+
+    /// 以 JSON 格式输出
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: HeadlessCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum HeadlessCommand {
+    /// 列出提示词，最近修改的在前
+    List {
+        /// 只列出带有该标签的提示词
+        #[arg(long)]
+        tag: Option<String>,
+        /// 只列出收藏的提示词
+        #[arg(long)]
+        favorites: bool,
+        /// 列出回收站中的提示词
+        #[arg(long)]
+        trash: bool,
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// 模糊搜索提示词
+    Search {
+        query: String,
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// 渲染提示词，输出最终文本
+    Render {
+        /// 提示词 ID 或标题
+        target: String,
+        /// 变量值，格式为 name=value，可重复指定
+        #[arg(long = "var", value_parser = parse_var)]
+        vars: Vec<(String, String)>,
+    },
+    /// 导出提示词库（与应用中「导出」相同的文件格式）
+    Export {
+        /// 输出文件，默认输出到标准输出
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// 同时导出回收站中的提示词
+        #[arg(long)]
+        include_trash: bool,
+    },
+    /// 导入 export 或应用导出的文件
+    Import {
+        /// 导入文件，`-` 表示从标准输入读取
+        file: PathBuf,
+        /// ID 相同时的处理方式：skip、overwrite、duplicate、newer_wins
+        #[arg(long, value_parser = parse_strategy, default_value = "newer_wins")]
+        strategy: ImportStrategy,
+    },
+    /// 与飞书多维表格同步
+    Sync,
+    /// 查看数据库版本，或升级、回退到指定版本（会先备份数据库）
+    Migrate {
+        /// 目标版本，默认升级到最新版本
+        #[arg(long)]
+        to: Option<i64>,
+    },
+    /// 检查数据库完整性和数据问题
+    Check {
+        /// 修复能安全修复的问题（会先备份数据库）
+        #[arg(long)]
+        repair: bool,
+    },
+}
+
+fn parse_var(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(name, value)| (name.trim().to_string(), value.to_string()))
+        .ok_or_else(|| format!("变量格式应为 name=value: {}", value))
+}
+
+fn parse_strategy(value: &str) -> Result<ImportStrategy, String> {
+    match value.replace('-', "_").as_str() {
+        "skip" => Ok(ImportStrategy::Skip),
+        "overwrite" => Ok(ImportStrategy::Overwrite),
+        "duplicate" => Ok(ImportStrategy::Duplicate),
+        "newer_wins" => Ok(ImportStrategy::NewerWins),
+        _ => Err(format!(
+            "未知的导入方式: {}（可选 skip、overwrite、duplicate、newer_wins）",
+            value
+        )),
+    }
+}
+
+/// 渲染结果
+#[derive(Debug, Serialize)]
+struct RenderedPrompt {
+    id: String,
+    title: String,
+    text: String,
+}
+
+/// 命令行工具入口，返回进程退出码
+pub fn run() -> i32 {
+    let args = HeadlessArgs::parse();
+    match execute(args) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+fn execute(args: HeadlessArgs) -> Result<i32, String> {
+    let config_dir = match args.config_dir {
+        Some(dir) => dir,
+        None => default_config_dir()?,
+    };
+    let db_path = args.db.unwrap_or_else(|| config_dir.join("promptgenie.db"));
+    // migrate 用于升级或回退数据库，不要求版本与应用一致
+    let store = match args.command {
+        HeadlessCommand::Migrate { .. } => open_initialized(db_path)?,
+        _ => open_store(db_path)?,
+    };
+    let json = args.json;
+
+    match args.command {
+        HeadlessCommand::List {
+            tag,
+            favorites,
+            trash,
+            limit,
+        } => {
+            let conn = store.open().map_err(|e| e.to_string())?;
+            let filter = PromptFilter {
+                tag,
+                favorites,
+                trash,
+                limit,
+            };
+            let prompts =
+                store::list_prompts(&conn, &filter).map_err(|e| format!("获取提示词失败: {}", e))?;
+            print_output(json, &prompts, |prompts| {
+                prompts
+                    .iter()
+                    .map(|item| format_line(&item.prompt.id, &item.prompt.title, &item.tags))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })?;
+        }
+        HeadlessCommand::Search { query, limit } => {
+            let conn = store.open().map_err(|e| e.to_string())?;
+            let items = palette::search(&conn, &query, limit)
+                .map_err(|e| format!("搜索提示词失败: {}", e))?;
+            print_output(json, &items, |items| {
+                items
+                    .iter()
+                    .map(|item| format_line(&item.id, &item.title, &item.tags))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })?;
+        }
+        HeadlessCommand::Render { target, vars } => {
+            let conn = store.open().map_err(|e| e.to_string())?;
+            let prompt = resolve_prompt(&conn, &target)?;
+            let values: HashMap<String, String> = vars.into_iter().collect();
+            // 没有运行中的应用时无法读取剪贴板，使用模板中的默认值
+            let text = template::render_stored_prompt_with(&conn, &prompt.id, &values, || None)
+                .map_err(|e| format!("渲染提示词失败: {}", e))?;
+            usage::record_usage(&conn, &prompt.id, UsageAction::Render, UsageSource::Cli)
+                .map_err(|e| format!("记录使用失败: {}", e))?;
+            let rendered = RenderedPrompt {
+                id: prompt.id,
+                title: prompt.title,
+                text,
+            };
+            print_output(json, &rendered, |rendered| rendered.text.clone())?;
+        }
+        HeadlessCommand::Export {
+            output,
+            include_trash,
+        } => {
+            let conn = store.open().map_err(|e| e.to_string())?;
+            let document = library::export_document(&conn, include_trash)
+                .map_err(|e| format!("导出失败: {}", e))?;
+            let content = serde_json::to_string_pretty(&document).map_err(|e| e.to_string())?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, content)
+                        .map_err(|e| format!("写入文件 {} 失败: {}", path.display(), e))?;
+                    eprintln!(
+                        "已导出 {} 条提示词到 {}",
+                        document.prompts.len(),
+                        path.display()
+                    );
+                }
+                None => println!("{}", content),
+            }
+        }
+        HeadlessCommand::Import { file, strategy } => {
+            let content = read_input(&file)?;
+            let document = library::parse_document(&content)
+                .map_err(|e| format!("解析导入文件失败: {}", e))?;
+            backup::before_change(&store, BackupReason::Import)?;
+            let conn = store.open().map_err(|e| e.to_string())?;
+            let report = library::import_document(&conn, &document, strategy)
+                .map_err(|e| format!("导入失败: {}", e))?;
+            print_output(json, &report, |report| {
+                format!(
+                    "新建 {} 条，更新 {} 条，另存 {} 条，跳过 {} 条",
+                    report.created, report.updated, report.duplicated, report.skipped
+                )
+            })?;
+        }
+        HeadlessCommand::Sync => {
+            let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+            let (result, _) = runtime.block_on(feishu_sync::sync_store(&store, &config_dir))?;
+            print_output(json, &result, |result| result.message.clone())?;
+            if !result.success {
+                return Ok(1);
+            }
+        }
+        HeadlessCommand::Migrate { to } => {
+            let target = to.unwrap_or_else(migrations::latest_version);
+            let report = migrations::migrate_store(&store, target).map_err(|e| e.to_string())?;
+            print_output(json, &report, |report| {
+                if report.applied.is_empty() {
+                    return format!("数据库已是版本 {}", report.to);
+                }
+                let mut message = format!("数据库已从版本 {} 迁移到 {}", report.from, report.to);
+                if let Some(backup) = &report.backup {
+                    message.push_str(&format!("，迁移前的备份: {}", backup));
+                }
+                message
+            })?;
+        }
+        HeadlessCommand::Check { repair: false } => {
+            let conn = store.open().map_err(|e| e.to_string())?;
+            let result = integrity::check(&conn).map_err(|e| format!("检查数据库失败: {}", e))?;
+            print_output(json, &result, |result| {
+                if result.ok {
+                    return "数据库没有发现问题".to_string();
+                }
+                format_issues(&result.issues)
+            })?;
+            if !result.ok {
+                return Ok(1);
+            }
+        }
+        HeadlessCommand::Check { repair: true } => {
+            let report =
+                integrity::repair_store(&store).map_err(|e| format!("修复数据库失败: {}", e))?;
+            print_output(json, &report, |report| {
+                let mut lines = report.repaired.clone();
+                if let Some(backup) = &report.backup {
+                    lines.push(format!("修复前的备份: {}", backup));
+                }
+                if report.remaining.is_empty() {
+                    lines.push("数据库没有发现问题".to_string());
+                } else {
+                    lines.push(format!("仍有 {} 个问题需要手动处理:", report.remaining.len()));
+                    lines.push(format_issues(&report.remaining));
+                }
+                lines.join("\n")
+            })?;
+            if !report.remaining.is_empty() {
+                return Ok(1);
+            }
+        }
+    }
+    Ok(0)
+}
+
+/// 应用的配置目录，与 Tauri 的 app_config_dir 一致
+pub(crate) fn default_config_dir() -> Result<PathBuf, String> {
+    dirs::config_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER))
+        .ok_or_else(|| "无法获取配置目录，请使用 --config-dir 指定".to_string())
+}
+
+/// 打开已由应用初始化过的数据库，并确认数据库版本与本程序一致
+pub(crate) fn open_store(db_path: PathBuf) -> Result<PromptStore, String> {
+    let store = open_initialized(db_path)?;
+    let current = store
+        .open()
+        .and_then(|conn| migrations::stored_version(&conn).map_err(Into::into))
+        .map_err(|e| e.to_string())?;
+    let latest = migrations::latest_version();
+    if current > latest {
+        return Err(format!(
+            "数据库版本 {} 高于本程序支持的版本 {}，请升级 PromptGenie",
+            current, latest
+        ));
+    }
+    if current < latest {
+        return Err(format!(
+            "数据库版本 {} 低于本程序需要的版本 {}，请先启动一次 PromptGenie 或运行 promptgenie-cli migrate 升级",
+            current, latest
+        ));
+    }
+    Ok(store)
+}
+
+/// 打开已由应用初始化过的数据库，不会创建新文件
+fn open_initialized(db_path: PathBuf) -> Result<PromptStore, String> {
+    if !db_path.exists() {
+        return Err(format!("数据库文件不存在: {}", db_path.display()));
+    }
+    let store = PromptStore::new(db_path);
+    let initialized: bool = store
+        .open()
+        .and_then(|conn| {
+            conn.query_row(
+                "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'prompts')",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(Into::into)
+        })
+        .map_err(|e| e.to_string())?;
+    if !initialized {
+        return Err("数据库尚未初始化，请先启动一次 PromptGenie".to_string());
+    }
+    Ok(store)
+}
+
@@ -0,0 +1,644 @@
+use chrono::{DateTime, SecondsFormat};
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Runtime, State};
+use tauri_plugin_dialog::DialogExt;
+use thiserror::Error;
+
+use crate::backup::{self, BackupReason};
+use crate::library::{self, DraftPrompt, ImportReport, ImportStrategy, LibraryError};
+use crate::store::PromptStore;
+use crate::template;
+
+/// 预览时返回的提示词数量
+const PREVIEW_PROMPTS: usize = 20;
+
+#[derive(Debug, Error)]
+pub enum ImporterError {
+    #[error("文件读写失败: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{}: JSON 格式错误: {}", .0.display(), .1)]
+    Json(PathBuf, serde_json::Error),
+    #[error("{}: 文件不是有效的 UTF-8 文本", .0.display())]
+    InvalidText(PathBuf),
+    #[error("未知的导入格式: {0}")]
+    UnknownFormat(String),
+    #[error("{}: 无法识别文件格式", .0.display())]
+    Unrecognized(PathBuf),
+    #[error("{}: 不是 {} 导出的文件", .0.display(), .1)]
+    WrongFormat(PathBuf, &'static str),
+    #[error("{0}")]
+    Library(#[from] LibraryError),
+}
+
+/// 其他提示词工具的导出格式
+///
+/// 每种格式实现一个 Importer，把文件转换为 DraftPrompt，
+/// 之后统一经过预览、确认，再按 JSON 导入的规则合并到本地。
+pub trait Importer: Sync {
+    /// 格式标识，前端选择格式时使用
+    fn id(&self) -> &'static str;
+    /// 显示名称，也作为导入提示词的标签
+    fn name(&self) -> &'static str;
+    /// 选择文件对话框中的扩展名
+    fn extensions(&self) -> &'static [&'static str];
+    /// 自动识别格式时判断文件是否属于这种格式
+    fn detect(&self, path: &Path, text: &str) -> bool;
+    fn parse(&self, path: &Path, text: &str) -> Result<Vec<DraftPrompt>, ImporterError>;
+}
+
+/// 自动识别时按顺序尝试，纯文本放在最后兜底
+static IMPORTERS: &[&dyn Importer] = &[&OpenWebUi, &ChatGptInstructions, &Raycast, &PlainText];
+
+/// 格式信息，供前端列出可选的格式
+#[derive(Debug, Clone, Serialize)]
+pub struct ImporterInfo {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub extensions: &'static [&'static str],
+}
+
+/// 一个文件的识别结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSource {
+    pub path: String,
+    pub format: &'static str,
+    pub format_name: &'static str,
+    pub count: usize,
+}
+
+/// 导入前的预览
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportPreview {
+    pub sources: Vec<ImportSource>,
+    pub total: usize,
+    pub prompts: Vec<DraftPrompt>,
+}
+
+fn parse_json(path: &Path, text: &str) -> Result<Value, ImporterError> {
+    serde_json::from_str(text).map_err(|e| ImporterError::Json(path.to_path_buf(), e))
+}
+
+/// 数组中的每个对象，单个对象视为只有一项的数组
+fn json_items(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(_) => vec![value],
+        _ => Vec::new(),
+    }
+}
+
+fn is_json(text: &str) -> bool {
+    text.trim_start().starts_with(['[', '{'])
+}
+
+/// 第一个对象是否包含所有字段
+fn first_item_has(text: &str, keys: &[&str]) -> bool {
+    let Ok(value) = serde_json::from_str::<Value>(text) else {
+        return false;
+    };
+    json_items(&value)
+        .first()
+        .is_some_and(|item| keys.iter().all(|key| item.get(key).is_some()))
+}
+
+fn string_field<'a>(item: &'a Value, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .filter_map(|key| item.get(key).and_then(Value::as_str))
+        .map(str::trim)
+        .find(|value| !value.is_empty())
+}
+
+/// Unix 时间戳（秒）转换为本地数据库使用的时间格式
+fn timestamp_to_time(seconds: i64) -> Option<String> {
+    DateTime::from_timestamp(seconds, 0)
+        .map(|time| time.to_rfc3339_opts(SecondsFormat::Millis, true))
+}
+
+/// Open WebUI「工作空间 → 提示词」导出的 JSON
+///
+/// 每项包含 command（如 `/summarize`）、title 和 content，content 中的
+/// `{{CLIPBOARD}}` 等内置变量转换为本应用的写法。
+pub struct OpenWebUi;
+
+impl OpenWebUi {
+    /// Open WebUI 的内置变量与本应用内置变量的对应关系
+    const VARIABLES: [(&'static str, &'static str); 3] = [
+        ("{{CLIPBOARD}}", "{{clipboard}}"),
+        ("{{CURRENT_DATE}}", "{{date}}"),
+        ("{{CURRENT_TIME}}", "{{time}}"),
+    ];
+}
+
+impl Importer for OpenWebUi {
+    fn id(&self) -> &'static str {
+        "open_webui"
+    }
+
+    fn name(&self) -> &'static str {
+        "Open WebUI"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+
+    fn detect(&self, _path: &Path, text: &str) -> bool {
+        first_item_has(text, &["command", "content"])
+    }
+
+    fn parse(&self, path: &Path, text: &str) -> Result<Vec<DraftPrompt>, ImporterError> {
+        let value = parse_json(path, text)?;
+        let mut prompts = Vec::new();
+        for item in json_items(&value) {
+            let Some(content) = string_field(item, &["content"]) else {
+                continue;
+            };
+            let title = string_field(item, &["title"])
+                .or_else(|| string_field(item, &["command"]).map(|c| c.trim_start_matches('/')))
+                .unwrap_or_default();
+            let content = Self::VARIABLES
+                .iter()
+                .fold(content.to_string(), |content, (from, to)| {
+                    content.replace(from, to)
+                });
+            let updated_at = item
+                .get("timestamp")
+                .and_then(Value::as_i64)
+                .and_then(timestamp_to_time);
+            prompts.push(DraftPrompt {
+                title: title.to_string(),
+                content,
+                tags: vec![self.name().to_string()],
+                created_at: updated_at.clone(),
+                updated_at,
+                ..Default::default()
+            });
+        }
+        Ok(prompts)
+    }
+}
+
+/// ChatGPT 自定义指令（个性化设置）的 JSON，例如数据导出中的 model_comparisons
+/// 或浏览器插件保存的内容，每个非空字段导入为一条提示词
+pub struct ChatGptInstructions;
+
+impl ChatGptInstructions {
+    /// 字段和导入后的标题，新旧两版设置的字段名不同
+    const FIELDS: [(&'static str, &'static str); 5] = [
+        ("about_user_message", "关于我"),
+        ("about_model_message", "回复方式"),
+        ("name_user_message", "称呼"),
+        ("role_user_message", "职业"),
+        ("traits_model_message", "回复风格"),
+    ];
+
+    /// 自定义指令可能直接在顶层，也可能在 custom_instructions 字段中
+    fn instructions(value: &Value) -> Vec<&Value> {
+        json_items(value)
+            .into_iter()
+            .map(|item| item.get("custom_instructions").unwrap_or(item))
+            .filter(|item| Self::FIELDS.iter().any(|(key, _)| item.get(key).is_some()))
+            .collect()
+    }
+}
+
+impl Importer for ChatGptInstructions {
+    fn id(&self) -> &'static str {
+        "chatgpt_instructions"
+    }
+
+    fn name(&self) -> &'static str {
+        "ChatGPT"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+
+    fn detect(&self, _path: &Path, text: &str) -> bool {
+        serde_json::from_str::<Value>(text)
+            .is_ok_and(|value| !Self::instructions(&value).is_empty())
+    }
+
+    fn parse(&self, path: &Path, text: &str) -> Result<Vec<DraftPrompt>, ImporterError> {
+        let value = parse_json(path, text)?;
+        let instructions = Self::instructions(&value);
+        if instructions.is_empty() {
+            return Err(ImporterError::WrongFormat(path.to_path_buf(), self.name()));
+        }
+        let mut prompts = Vec::new();
+        for item in instructions {
+            for (key, label) in Self::FIELDS {
+                if let Some(content) = string_field(item, &[key]) {
+                    prompts.push(DraftPrompt {
+                        title: format!("ChatGPT 自定义指令：{}", label),
+                        content: content.to_string(),
+                        tags: vec![self.name().to_string()],
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+        Ok(prompts)
+    }
+}
+
+/// Raycast AI Commands 和 Presets 导出的 JSON
+///
+/// prompt 中的 `{selection}`、`{clipboard}`、`{argument name="..." default="..."}`
+/// 等占位符转换为本应用的变量。
+pub struct Raycast;
+
+impl Raycast {
+    /// 把单花括号占位符转换为双花括号变量，无法识别的原样保留（例如 JSON 示例）
+    fn convert_placeholders(prompt: &str) -> String {
+        let mut result = String::with_capacity(prompt.len());
+        let mut rest = prompt;
+        while let Some(start) = rest.find('{') {
+            result.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let converted = after
+                .find('}')
+                .and_then(|end| Some((Self::convert_placeholder(&after[..end])?, end)));
+            match converted {
+                Some((variable, end)) => {
+                    result.push_str(&variable);
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    result.push('{');
+                    rest = after;
+                }
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+
+    fn convert_placeholder(inner: &str) -> Option<String> {
+        let (name, attributes) = inner.split_once(' ').unwrap_or((inner, ""));
+        match name {
+            "selection" | "clipboard" | "date" | "time" => Some(format!("{{{{{}}}}}", name)),
+            "argument" => {
+                let attribute = |key: &str| {
+                    let start = attributes.find(&format!("{}=\"", key))? + key.len() + 2;
+                    let end = attributes[start..].find('"')?;
+                    Some(&attributes[start..start + end])
+                };
+                let name = Self::variable_name(attribute("name").unwrap_or_default());
+                Some(match attribute("default") {
+                    Some(default) => {
+                        format!("{{{{{}|{}}}}}", name, template::escape_default(default))
+                    }
+                    None => format!("{{{{{}}}}}", name),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Raycast 的参数名可以包含空格等字符，替换为下划线使其成为有效的变量名
+    fn variable_name(name: &str) -> String {
+        let name: String = name
+            .trim()
+            .chars()
+            .map(|c| {
+                if template::is_valid_name(c.encode_utf8(&mut [0; 4])) {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        if name.is_empty() {
+            "argument".to_string()
+        } else {
+            name
+        }
+    }
+}
+
+impl Importer for Raycast {
+    fn id(&self) -> &'static str {
+        "raycast"
+    }
+
+    fn name(&self) -> &'static str {
+        "Raycast"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+
+    fn detect(&self, _path: &Path, text: &str) -> bool {
+        // Open WebUI 的提示词也有 title，带 command 的交给它处理
+        (first_item_has(text, &["title", "prompt"])
+            || first_item_has(text, &["name", "instructions"]))
+            && !first_item_has(text, &["command"])
+    }
+
+    fn parse(&self, path: &Path, text: &str) -> Result<Vec<DraftPrompt>, ImporterError> {
+        let value = parse_json(path, text)?;
+        let mut prompts = Vec::new();
+        for item in json_items(&value) {
+            let Some(prompt) = string_field(item, &["prompt", "instructions"]) else {
+                continue;
+            };
+            prompts.push(DraftPrompt {
+                title: string_field(item, &["title", "name"])
+                    .unwrap_or_default()
+                    .to_string(),
+                content: Self::convert_placeholders(prompt),
+                tags: vec![self.name().to_string()],
+                ..Default::default()
+            });
+        }
+        Ok(prompts)
+    }
+}
+
+/// 纯文本文件，每个文件一条提示词，文件名作为标题
+pub struct PlainText;
+
+impl Importer for PlainText {
+    fn id(&self) -> &'static str {
+        "text"
+    }
+
+    fn name(&self) -> &'static str {
+        "纯文本"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["txt", "md", "prompt"]
+    }
+
+    fn detect(&self, path: &Path, text: &str) -> bool {
+        let has_extension = path.extension().is_some_and(|ext| {
+            self.extensions()
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        });
+        has_extension || !is_json(text)
+    }
+
+    fn parse(&self, path: &Path, text: &str) -> Result<Vec<DraftPrompt>, ImporterError> {
+        let content = text.trim_end_matches(['\r', '\n']);
+        if content.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let title = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(vec![DraftPrompt {
+            title,
+            content: content.to_string(),
+            ..Default::default()
+        }])
+    }
+}
+
+fn find_importer(id: &str) -> Result<&'static dyn Importer, ImporterError> {
+    IMPORTERS
+        .iter()
+        .copied()
+        .find(|importer| importer.id() == id)
+        .ok_or_else(|| ImporterError::UnknownFormat(id.to_string()))
+}
+
+/// 读取文件并按格式转换，未指定格式时逐个文件自动识别
+pub fn read_files(
+    paths: &[PathBuf],
+    format: Option<&str>,
+) -> Result<(Vec<ImportSource>, Vec<DraftPrompt>), ImporterError> {
+    let chosen = format.map(find_importer).transpose()?;
+    let mut sources = Vec::with_capacity(paths.len());
+    let mut prompts = Vec::new();
+    for path in paths {
+        let bytes = std::fs::read(path)?;
+        let text =
+            String::from_utf8(bytes).map_err(|_| ImporterError::InvalidText(path.clone()))?;
+        let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+        let importer = match chosen {
+            Some(importer) => importer,
+            None => IMPORTERS
+                .iter()
+                .copied()
+                .find(|importer| importer.detect(path, text))
+                .ok_or_else(|| ImporterError::Unrecognized(path.clone()))?,
+        };
+
+        let mut parsed = importer.parse(path, text)?;
+        // 没有标题的用内容开头代替
+        for prompt in &mut parsed {
+            if prompt.title.trim().is_empty() {
+                prompt.title = library::title_from_content(&prompt.content);
+            }
+        }
+        sources.push(ImportSource {
+            path: path.to_string_lossy().into_owned(),
+            format: importer.id(),
+            format_name: importer.name(),
+            count: parsed.len(),
+        });
+        prompts.extend(parsed);
+    }
+    Ok((sources, prompts))
+}
+
+/// 预览导入的内容
+pub fn preview_files(
+    paths: &[PathBuf],
+    format: Option<&str>,
+) -> Result<ImportPreview, ImporterError> {
+    let (sources, mut prompts) = read_files(paths, format)?;
+    let total = prompts.len();
+    prompts.truncate(PREVIEW_PROMPTS);
+    Ok(ImportPreview {
+        sources,
+        total,
+        prompts,
+    })
+}
+
+/// 导入文件，ID 冲突时的处理与 JSON 导入相同（其他工具导出的提示词没有 ID，总是新建）
+pub fn import_files(
+    conn: &Connection,
+    paths: &[PathBuf],
+    format: Option<&str>,
+    strategy: ImportStrategy,
+) -> Result<ImportReport, ImporterError> {
+    let (_, prompts) = read_files(paths, format)?;
+    Ok(library::import_document(
+        conn,
+        &library::document_from_drafts(&prompts),
+        strategy,
+    )?)
+}
+
+/// 可选的导入格式
+#[tauri::command]
+pub fn list_importers() -> Vec<ImporterInfo> {
+    IMPORTERS
+        .iter()
+        .map(|importer| ImporterInfo {
+            id: importer.id(),
+            name: importer.name(),
+            extensions: importer.extensions(),
+        })
+        .collect()
+}
+
+/// 预览其他工具导出的文件，未指定路径时弹出选择文件对话框（可多选），取消时返回 None
+#[tauri::command]
+pub async fn preview_import<R: Runtime>(
+    app_handle: AppHandle<R>,
+    paths: Option<Vec<String>>,
+    format: Option<String>,
+) -> Result<Option<ImportPreview>, String> {
+    let paths: Vec<PathBuf> = match paths {
+        Some(paths) => paths.into_iter().map(PathBuf::from).collect(),
+        None => {
+            let mut dialog = app_handle.dialog().file();
+            if let Some(importer) = format.as_deref().and_then(|id| find_importer(id).ok()) {
+                dialog = dialog.add_filter(importer.name(), importer.extensions());
+            }
+            match dialog.blocking_pick_files() {
+                Some(paths) => paths
+                    .into_iter()
+                    .filter_map(|path| path.into_path().ok())
+                    .collect(),
+                None => return Ok(None),
+            }
+        }
+    };
+    preview_files(&paths, format.as_deref())
+        .map(Some)
+        .map_err(|e| format!("读取导入文件失败: {}", e))
+}
+
+/// 确认预览后导入
+#[tauri::command]
+pub async fn confirm_import<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+    paths: Vec<String>,
+    format: Option<String>,
+    strategy: ImportStrategy,
+) -> Result<ImportReport, String> {
+    let paths: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+    backup::before_change(&store, BackupReason::Import)?;
+    let report = {
+        let conn = store.open().map_err(|e| e.to_string())?;
+        import_files(&conn, &paths, format.as_deref(), strategy)
+            .map_err(|e| format!("导入失败: {}", e))?
+    };
+    println!(
+        "导入完成: 新建 {}，更新 {}，另存 {}，跳过 {}",
+        report.created, report.updated, report.duplicated, report.skipped
+    );
+    crate::notify_prompts_changed(&app_handle, &report.changed_ids());
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/importers")
+            .join(name)
+    }
+
+    fn detected(name: &str) -> &'static str {
+        let (sources, _) = read_files(&[fixture(name)], None).unwrap();
+        sources[0].format
+    }
+
+    #[test]
+    fn detects_each_fixture_in_order() {
+        assert_eq!(detected("open_webui.json"), "open_webui");
+        assert_eq!(
+            detected("chatgpt_instructions.json"),
+            "chatgpt_instructions"
+        );
+        assert_eq!(detected("raycast_commands.json"), "raycast");
+        assert_eq!(detected("raycast_presets.json"), "raycast");
+        assert_eq!(detected("weekly_report.md"), "text");
+    }
+
+    #[test]
+    fn raycast_does_not_claim_open_webui_files() {
+        let path = fixture("open_webui.json");
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(!Raycast.detect(&path, &text));
+        assert!(!ChatGptInstructions.detect(&path, &text));
+        // 即使 Open WebUI 导出的项带有 prompt 字段也不会被 Raycast 识别
+        let text = r#"[{"command": "/a", "title": "A", "prompt": "p", "content": "c"}]"#;
+        assert!(!Raycast.detect(&path, text));
+        assert!(OpenWebUi.detect(&path, text));
+    }
+
+    #[test]
+    fn rejects_unknown_json() {
+        let dir = std::env::temp_dir().join(format!("promptgenie-test-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("unknown.json");
+        std::fs::write(&path, r#"[{"foo": 1}]"#).unwrap();
+        assert!(matches!(
+            read_files(&[path], None),
+            Err(ImporterError::Unrecognized(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parses_fixtures() {
+        let (_, prompts) = read_files(&[fixture("open_webui.json")], None).unwrap();
+        assert_eq!(prompts[0].title, "Summarize");
+        assert!(prompts[0].content.contains("{{clipboard}}"));
+        assert!(prompts[0].content.contains("Date: {{date}}"));
+        assert_eq!(
+            prompts[0].updated_at.as_deref(),
+            Some("2024-06-01T00:00:00.000Z")
+        );
+
+        let (_, prompts) = read_files(&[fixture("raycast_commands.json")], None).unwrap();
+        assert_eq!(
+            prompts[0].content,
+            "Translate {{selection}} into {{target_language|English}}."
+        );
+
+        let (_, prompts) = read_files(&[fixture("weekly_report.md")], None).unwrap();
+        assert_eq!(prompts[0].title, "weekly_report");
+    }
+
+    #[test]
+    fn converts_raycast_placeholders() {
+        assert_eq!(
+            Raycast::convert_placeholders(r#"{argument name="a b"} and {argument}"#),
+            "{{a_b}} and {{argument}}"
+        );
+        assert_eq!(
+            Raycast::convert_placeholders(r#"{argument name="语言" default="C# 或 Rust"}"#),
+            "{{语言|C\\# 或 Rust}}"
+        );
+        // 无法识别的花括号原样保留
+        assert_eq!(
+            Raycast::convert_placeholders(r#"返回 {"ok": true}"#),
+            r#"返回 {"ok": true}"#
+        );
+
+        let variables = template::variables(&Raycast::convert_placeholders(
+            r#"{argument name=" a b:c "}"#,
+        ));
+        assert_eq!(variables.len(), 1);
+        assert_eq!(variables[0].name, "a_b_c");
+    }
+}
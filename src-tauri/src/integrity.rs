@@ -0,0 +1,629 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, TimeZone, Utc};
+use rusqlite::types::Value;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::backup::{self, BackupReason};
+use crate::library::title_from_content;
+use crate::store::{now_timestamp, PromptStore};
+
+/// 外键检查中可以直接删除孤立行的表，这些表只记录与提示词、标签的关联
+const ORPHAN_TABLES: [&str; 3] = ["prompt_tags", "prompt_versions", "prompt_usage"];
+/// 需要是 RFC3339 格式的时间列
+const TIMESTAMP_COLUMNS: [&str; 4] = ["created_at", "updated_at", "last_used_at", "deleted_at"];
+/// 无法修复的问题逐条列出，超过这个数量时只报告总数
+const MAX_LISTED_ISSUES: usize = 20;
+
+/// 问题的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// PRAGMA integrity_check 报告的结构损坏
+    Integrity,
+    /// 引用了不存在的提示词或标签
+    ForeignKey,
+    /// 多个标签同名
+    DuplicateTag,
+    /// 时间不是 RFC3339 格式
+    InvalidTimestamp,
+    /// 标题为空
+    EmptyTitle,
+}
+
+/// 检查发现的一个问题
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseIssue {
+    pub kind: IssueKind,
+    pub message: String,
+    /// repair_database 能否自动修复
+    pub repairable: bool,
+}
+
+/// 数据库检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseCheck {
+    pub ok: bool,
+    pub issues: Vec<DatabaseIssue>,
+}
+
+/// 修复结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RepairReport {
+    /// 已修复的内容
+    pub repaired: Vec<String>,
+    /// 修复后仍然存在、需要手动处理的问题
+    pub remaining: Vec<DatabaseIssue>,
+    /// 修复前生成的备份文件名
+    pub backup: Option<String>,
+}
+
+fn issue(kind: IssueKind, message: String, repairable: bool) -> DatabaseIssue {
+    DatabaseIssue {
+        kind,
+        message,
+        repairable,
+    }
+}
+
+/// 运行全部检查
+///
+/// 结构已损坏时只报告 integrity_check 的结果，其余检查的结论不可信。
+pub fn check(conn: &Connection) -> rusqlite::Result<DatabaseCheck> {
+    let mut issues: Vec<DatabaseIssue> = backup::integrity_problems(conn)?
+        .into_iter()
+        .map(|problem| issue(IssueKind::Integrity, problem, false))
+        .collect();
+    if issues.is_empty() {
+        issues.extend(foreign_key_issues(conn)?);
+        issues.extend(duplicate_tag_issues(conn)?);
+        issues.extend(timestamp_issues(conn)?);
+        issues.extend(empty_title_issues(conn)?);
+    }
+    Ok(DatabaseCheck {
+        ok: issues.is_empty(),
+        issues,
+    })
+}
+
+/// PRAGMA foreign_key_check 的结果，按 (表, 引用的表) 汇总行号
+fn orphan_rows(conn: &Connection) -> rusqlite::Result<BTreeMap<(String, String), Vec<i64>>> {
+    let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, Option<i64>>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    let mut orphans: BTreeMap<(String, String), Vec<i64>> = BTreeMap::new();
+    for row in rows {
+        let (table, rowid, parent) = row?;
+        orphans.entry((table, parent)).or_default().extend(rowid);
+    }
+    Ok(orphans)
+}
+
+fn foreign_key_issues(conn: &Connection) -> rusqlite::Result<Vec<DatabaseIssue>> {
+    Ok(orphan_rows(conn)?
+        .into_iter()
+        .map(|((table, parent), rowids)| {
+            issue(
+                IssueKind::ForeignKey,
+                format!(
+                    "{} 中有 {} 行引用了 {} 中不存在的记录",
+                    table,
+                    rowids.len(),
+                    parent
+                ),
+                ORPHAN_TABLES.contains(&table.as_str()),
+            )
+        })
+        .collect())
+}
+
+/// 同名标签，每组按关联的提示词数量从多到少排列
+fn duplicate_tags(conn: &Connection) -> rusqlite::Result<Vec<(String, Vec<String>)>> {
+    let mut stmt = conn.prepare(
+        "SELECT name, id FROM tags
+         WHERE name IN (SELECT name FROM tags GROUP BY name HAVING COUNT(*) > 1)
+         ORDER BY name,
+                  (SELECT COUNT(*) FROM prompt_tags WHERE prompt_tags.tag_id = tags.id) DESC,
+                  rowid",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    for row in rows {
+        let (name, id) = row?;
+        match groups.last_mut() {
+            Some((last, ids)) if *last == name => ids.push(id),
+            _ => groups.push((name, vec![id])),
+        }
+    }
+    Ok(groups)
+}
+
+fn duplicate_tag_issues(conn: &Connection) -> rusqlite::Result<Vec<DatabaseIssue>> {
+    Ok(duplicate_tags(conn)?
+        .into_iter()
+        .map(|(name, ids)| {
+            issue(
+                IssueKind::DuplicateTag,
+                format!("标签「{}」有 {} 个同名记录", name, ids.len()),
+                true,
+            )
+        })
+        .collect())
+}
+
+/// 合并同名标签：保留关联提示词最多的一个，其余的关联转移过去后删除
+///
+/// 返回删除的标签数量。
+pub fn merge_duplicate_tags(conn: &Connection) -> rusqlite::Result<usize> {
+    let mut removed = 0;
+    for (_, ids) in duplicate_tags(conn)? {
+        let Some((keep, duplicates)) = ids.split_first() else {
+            continue;
+        };
+        for duplicate in duplicates {
+            conn.execute(
+                "INSERT OR IGNORE INTO prompt_tags (prompt_id, tag_id)
+                 SELECT prompt_id, ?1 FROM prompt_tags WHERE tag_id = ?2",
+                params![keep, duplicate],
+            )?;
+            conn.execute(
+                "DELETE FROM prompt_tags WHERE tag_id = ?1",
+                params![duplicate],
+            )?;
+            conn.execute("DELETE FROM tags WHERE id = ?1", params![duplicate])?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// 把常见的时间写法统一为数据库使用的 RFC3339 格式，无法识别时返回 None
+///
+/// SQLite 的 CURRENT_TIMESTAMP 和不带时区的时间按 UTC 处理，只有日期时取当天零点，
+/// 纯数字按 Unix 时间戳处理。导入表格和 Markdown 时也用它整理时间。
+pub(crate) fn normalize_timestamp(value: &str) -> Option<String> {
+    let value = value.trim();
+    let utc = if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        time.with_timezone(&Utc)
+    } else if let Some(time) = [
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y/%m/%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y/%m/%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    {
+        Utc.from_utc_datetime(&time)
+    } else if let Some(date) = ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+    {
+        Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN))
+    } else if !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()) {
+        let number: i64 = value.parse().ok()?;
+        // 13 位是 Date.now() 的毫秒数，10 位是秒数
+        match value.len() {
+            13 => Utc.timestamp_millis_opt(number).single()?,
+            10 => Utc.timestamp_opt(number, 0).single()?,
+            _ => return None,
+        }
+    } else {
+        return None;
+    };
+    Some(utc.to_rfc3339_opts(SecondsFormat::Millis, true))
+}
+
+/// 时间列不是 RFC3339 的提示词：(列, 提示词 ID, 标题, 原值)
+fn invalid_timestamps(
+    conn: &Connection,
+) -> rusqlite::Result<Vec<(&'static str, String, String, String)>> {
+    let mut invalid = Vec::new();
+    for column in TIMESTAMP_COLUMNS {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, title, {column} FROM prompts WHERE {column} IS NOT NULL"
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Value>(2)?,
+            ))
+        })?;
+        for row in rows {
+            let (id, title, value) = row?;
+            // 数字形式的时间戳按文本处理，统一由 normalize_timestamp 识别
+            let value = match value {
+                Value::Text(text) => text,
+                Value::Integer(number) => number.to_string(),
+                Value::Real(number) => number.to_string(),
+                Value::Blob(_) | Value::Null => String::new(),
+            };
+            if DateTime::parse_from_rfc3339(&value).is_err() {
+                invalid.push((column, id, title, value));
+            }
+        }
+    }
+    Ok(invalid)
+}
+
+fn timestamp_issues(conn: &Connection) -> rusqlite::Result<Vec<DatabaseIssue>> {
+    let mut convertible: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut unknown = Vec::new();
+    for (column, id, title, value) in invalid_timestamps(conn)? {
+        if normalize_timestamp(&value).is_some() {
+            *convertible.entry(column).or_default() += 1;
+        } else {
+            // 标题也可能为空，这时用 ID 指明是哪条提示词
+            let name = if title.trim().is_empty() { id } else { title };
+            unknown.push(format!(
+                "提示词「{}」的 {} 无法识别: {}",
+                name, column, value
+            ));
+        }
+    }
+
+    let mut issues: Vec<DatabaseIssue> = convertible
+        .into_iter()
+        .map(|(column, count)| {
+            issue(
+                IssueKind::InvalidTimestamp,
+                format!("{} 条提示词的 {} 不是 RFC3339 格式", count, column),
+                true,
+            )
+        })
+        .collect();
+    issues.extend(limit_listed(
+        IssueKind::InvalidTimestamp,
+        unknown,
+        "条无法识别的时间",
+    ));
+    Ok(issues)
+}
+
+/// 把时间转换为 RFC3339，返回转换的数量
+fn normalize_timestamps(conn: &Connection) -> rusqlite::Result<usize> {
+    let mut fixed = 0;
+    for (column, id, _, value) in invalid_timestamps(conn)? {
+        if let Some(normalized) = normalize_timestamp(&value) {
+            conn.execute(
+                &format!("UPDATE prompts SET {column} = ?1 WHERE id = ?2"),
+                params![normalized, id],
+            )?;
+            fixed += 1;
+        }
+    }
+    Ok(fixed)
+}
+
+/// 标题为空的提示词：(ID, 内容)
+fn empty_titles(conn: &Connection) -> rusqlite::Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare("SELECT id, content FROM prompts WHERE TRIM(title) = ''")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+fn empty_title_issues(conn: &Connection) -> rusqlite::Result<Vec<DatabaseIssue>> {
+    let (derivable, empty): (Vec<_>, Vec<_>) = empty_titles(conn)?
+        .into_iter()
+        .partition(|(_, content)| !title_from_content(content).is_empty());
+
+    let mut issues = Vec::new();
+    if !derivable.is_empty() {
+        issues.push(issue(
+            IssueKind::EmptyTitle,
+            format!(
+                "{} 条提示词的标题为空，可以用内容的第一行作为标题",
+                derivable.len()
+            ),
+            true,
+        ));
+    }
+    issues.extend(limit_listed(
+        IssueKind::EmptyTitle,
+        empty
+            .into_iter()
+            .map(|(id, _)| format!("提示词 {} 的标题和内容都为空", id))
+            .collect(),
+        "条标题和内容都为空的提示词",
+    ));
+    Ok(issues)
+}
+
+/// 用内容的第一行补全空标题，同时更新 updated_at 让同步带上新标题
+fn fill_empty_titles(conn: &Connection) -> rusqlite::Result<usize> {
+    let now = now_timestamp();
+    let mut fixed = 0;
+    for (id, content) in empty_titles(conn)? {
+        let title = title_from_content(&content);
+        if title.is_empty() {
+            continue;
+        }
+        conn.execute(
+            "UPDATE prompts SET title = ?1, updated_at = ?2 WHERE id = ?3",
+            params![title, now, id],
+        )?;
+        fixed += 1;
+    }
+    Ok(fixed)
+}
+
+/// 无法修复的问题逐条列出，过多时其余的合并成一条
+fn limit_listed(kind: IssueKind, messages: Vec<String>, rest: &str) -> Vec<DatabaseIssue> {
+    let total = messages.len();
+    let mut issues: Vec<DatabaseIssue> = messages
+        .into_iter()
+        .take(MAX_LISTED_ISSUES)
+        .map(|message| issue(kind, message, false))
+        .collect();
+    if total > MAX_LISTED_ISSUES {
+        issues.push(issue(
+            kind,
+            format!("另有 {} {}", total - MAX_LISTED_ISSUES, rest),
+            false,
+        ));
+    }
+    issues
+}
+
+/// 在一个事务中修复能安全修复的问题，返回修复内容的说明
+///
+/// 结构已损坏时不做任何修改，应从备份恢复。
+fn repair(conn: &mut Connection) -> rusqlite::Result<Vec<String>> {
+    if !backup::integrity_problems(conn)?.is_empty() {
+        return Ok(Vec::new());
+    }
+    let tx = conn.transaction()?;
+    let mut repaired = Vec::new();
+
+    for ((table, parent), rowids) in orphan_rows(&tx)? {
+        if !ORPHAN_TABLES.contains(&table.as_str()) {
+            continue;
+        }
+        for rowid in &rowids {
+            tx.execute(
+                &format!("DELETE FROM {table} WHERE rowid = ?1"),
+                params![rowid],
+            )?;
+        }
+        repaired.push(format!(
+            "删除了 {} 中 {} 行引用 {} 中不存在记录的数据",
+            table,
+            rowids.len(),
+            parent
+        ));
+    }
+
+    let merged = merge_duplicate_tags(&tx)?;
+    if merged > 0 {
+        repaired.push(format!("合并了 {} 个同名标签", merged));
+    }
+    let normalized = normalize_timestamps(&tx)?;
+    if normalized > 0 {
+        repaired.push(format!("把 {} 个时间转换为 RFC3339 格式", normalized));
+    }
+    let titled = fill_empty_titles(&tx)?;
+    if titled > 0 {
+        repaired.push(format!("为 {} 条提示词补全了标题", titled));
+    }
+
+    tx.commit()?;
+    Ok(repaired)
+}
+
+/// 修复前先备份，修复后重新检查，返回仍需手动处理的问题
+pub fn repair_store(store: &PromptStore) -> Result<RepairReport, String> {
+    let mut conn = store.open().map_err(|e| e.to_string())?;
+    let before = check(&conn).map_err(|e| e.to_string())?;
+    if before.ok {
+        return Ok(RepairReport {
+            repaired: Vec::new(),
+            remaining: Vec::new(),
+            backup: None,
+        });
+    }
+
+    let backup = if before.issues.iter().any(|issue| issue.repairable) {
+        let dir = backup::backup_dir(store.db_path());
+        backup::snapshot(store.db_path(), &dir, BackupReason::PreRepair)
+            .map_err(|e| format!("修复前备份数据库失败，已取消修复: {}", e))?
+            .map(|info| info.file_name)
+    } else {
+        None
+    };
+    let repaired = repair(&mut conn).map_err(|e| e.to_string())?;
+    let remaining = check(&conn).map_err(|e| e.to_string())?.issues;
+    Ok(RepairReport {
+        repaired,
+        remaining,
+        backup,
+    })
+}
+
+/// 检查数据库的完整性和数据问题
+#[tauri::command]
+pub async fn check_database<R: Runtime>(app_handle: AppHandle<R>) -> Result<DatabaseCheck, String> {
+    let store = app_handle.state::<PromptStore>();
+    store
+        .open()
+        .map_err(|e| e.to_string())
+        .and_then(|conn| check(&conn).map_err(|e| e.to_string()))
+        .map_err(|e| format!("检查数据库失败: {}", e))
+}
+
+/// 修复能安全修复的问题，修复前会先备份数据库
+#[tauri::command]
+pub async fn repair_database<R: Runtime>(app_handle: AppHandle<R>) -> Result<RepairReport, String> {
+    let store = app_handle.state::<PromptStore>();
+    let report = repair_store(&store).map_err(|e| format!("修复数据库失败: {}", e))?;
+    if !report.repaired.is_empty() {
+        println!("已修复数据库: {}", report.repaired.join("；"));
+        crate::notify_prompts_changed(&app_handle, &[]);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::open_in_memory;
+
+    const TIME: &str = "2024-06-01T00:00:00.000Z";
+
+    #[test]
+    fn normalizes_common_timestamp_formats() {
+        for (value, expected) in [
+            ("2024-06-01T00:00:00Z", TIME),
+            ("2024-06-01T08:00:00+08:00", TIME),
+            // SQLite CURRENT_TIMESTAMP
+            ("2024-06-01 00:00:00", TIME),
+            ("2024-06-01 00:00:00.123", "2024-06-01T00:00:00.123Z"),
+            ("2024-06-01T00:00:00", TIME),
+            ("2024/06/01 08:30", "2024-06-01T08:30:00.000Z"),
+            ("2024-06-01", TIME),
+            ("2024.06.01", TIME),
+            ("1717200000", TIME),
+            (" 1717200000123 ", "2024-06-01T00:00:00.123Z"),
+        ] {
+            assert_eq!(
+                normalize_timestamp(value).as_deref(),
+                Some(expected),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_timestamps() {
+        for value in [
+            "",
+            "昨天",
+            "not a date",
+            "2024-13-01",
+            "2024-06-01 25:00",
+            "12345",
+            "17172000001",
+            "99999999999999999999",
+        ] {
+            assert_eq!(normalize_timestamp(value), None, "{}", value);
+        }
+    }
+
+    fn insert_prompt(conn: &Connection, id: &str, title: &str, content: &str, last_used_at: &str) {
+        conn.execute(
+            "INSERT INTO prompts (id, title, content, created_at, updated_at, last_used_at)
+             VALUES (?1, ?2, ?3, ?4, ?4, ?5)",
+            params![id, title, content, TIME, last_used_at],
+        )
+        .unwrap();
+    }
+
+    fn tag_links(conn: &Connection) -> Vec<(String, String)> {
+        let mut stmt = conn
+            .prepare("SELECT prompt_id, tag_id FROM prompt_tags ORDER BY prompt_id, tag_id")
+            .unwrap();
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    /// 有孤立关联、同名标签、非 RFC3339 时间和空标题的数据库
+    fn damaged_library() -> Connection {
+        let conn = open_in_memory();
+        // 模拟加上唯一索引之前的数据库
+        conn.execute_batch("DROP INDEX idx_tags_name").unwrap();
+        insert_prompt(&conn, "p1", "周报", "总结本周工作", "2024-06-01 00:00:00");
+        insert_prompt(&conn, "p2", " ", "\n第一行\n第二行", TIME);
+        conn.execute_batch(
+            "INSERT INTO tags (id, name, color) VALUES ('t1', '写作', '#000'), ('t2', '写作', '#000');
+             INSERT INTO prompt_tags (prompt_id, tag_id) VALUES ('p1', 't1'), ('p1', 't2'), ('p2', 't2');",
+        )
+        .unwrap();
+        conn.pragma_update(None, "foreign_keys", false).unwrap();
+        conn.execute(
+            "INSERT INTO prompt_tags (prompt_id, tag_id) VALUES ('missing', 't1')",
+            [],
+        )
+        .unwrap();
+        conn.pragma_update(None, "foreign_keys", true).unwrap();
+        conn
+    }
+
+    #[test]
+    fn check_reports_repairable_issues() {
+        let conn = damaged_library();
+        let check = check(&conn).unwrap();
+        assert!(!check.ok);
+        let kinds: Vec<IssueKind> = check.issues.iter().map(|issue| issue.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                IssueKind::ForeignKey,
+                IssueKind::DuplicateTag,
+                IssueKind::InvalidTimestamp,
+                IssueKind::EmptyTitle,
+            ]
+        );
+        assert!(check.issues.iter().all(|issue| issue.repairable));
+    }
+
+    #[test]
+    fn repair_removes_orphans_and_merges_duplicate_tags() {
+        let mut conn = damaged_library();
+        let repaired = repair(&mut conn).unwrap();
+        assert_eq!(repaired.len(), 4, "{:?}", repaired);
+        assert!(check(&conn).unwrap().ok);
+
+        // 孤立关联先被删除，t2 关联的提示词更多，保留 t2
+        assert_eq!(
+            tag_links(&conn),
+            vec![
+                ("p1".to_string(), "t2".to_string()),
+                ("p2".to_string(), "t2".to_string()),
+            ]
+        );
+        let tags: i64 = conn
+            .query_row("SELECT COUNT(*) FROM tags", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tags, 1);
+
+        let last_used_at: String = conn
+            .query_row(
+                "SELECT last_used_at FROM prompts WHERE id = 'p1'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(last_used_at, TIME);
+        let title: String = conn
+            .query_row("SELECT title FROM prompts WHERE id = 'p2'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(title, "第一行");
+    }
+
+    #[test]
+    fn unknown_timestamps_remain_after_repair() {
+        let mut conn = open_in_memory();
+        insert_prompt(&conn, "p1", "周报", "总结本周工作", "上周");
+        let repaired = repair(&mut conn).unwrap();
+        assert!(repaired.is_empty());
+        let issues = check(&conn).unwrap().issues;
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, IssueKind::InvalidTimestamp);
+        assert!(!issues[0].repairable);
+        assert!(issues[0].message.contains("上周"));
+    }
+}
@@ -2,32 +2,91 @@
 use tauri::{
     menu::{MenuBuilder, MenuItem},
     tray::{TrayIcon, TrayIconBuilder, TrayIconEvent},
-    AppHandle, Emitter, Manager, Runtime,
+    AppHandle, Emitter, Manager, Runtime, WebviewUrl, WebviewWindowBuilder,
 };
 use tauri_plugin_clipboard_manager;
-use tauri_plugin_sql::{Migration, MigrationKind};
+use tauri_plugin_clipboard_manager::ClipboardExt;
 use tauri_plugin_single_instance;
 
 // 飞书同步模块
 mod feishu_sync;
 use feishu_sync::{save_feishu_config, get_feishu_config, check_feishu_config_exists, get_feishu_table_fields, test_feishu_connection, trigger_sync, sync_with_local_data};
 
-// 新增：用于从前端接收菜单项数据的结构体
-#[derive(serde::Deserialize)]
-struct PromptMenuItem {
-    id: String,
-    title: String,
-}
+// Rust 端数据访问与应用设置
+mod api;
+mod backup;
+mod builtins;
+mod bundle;
+mod csv_io;
+pub mod cli;
+mod deeplink;
+mod folder_sync;
+pub mod headless;
+mod importers;
+mod integrity;
+mod library;
+mod markdown;
+mod migrations;
+pub mod mcp;
+mod palette;
+mod settings;
+mod snippets;
+mod store;
+mod template;
+mod trash;
+mod tray;
+mod usage;
+mod versions;
+use api::{regenerate_api_token, ApiServer};
+use backup::{create_backup, list_backups, restore_backup, verify_backup};
+use bundle::{
+    export_bundle, get_bundle_fingerprint, import_bundle, preview_bundle, take_pending_bundle,
+    PendingBundle,
+};
+use csv_io::{export_csv, import_csv, preview_csv};
+use deeplink::{
+    cancel_link_import, confirm_link_import, create_share_link, preview_link_import,
+    take_pending_prompt, PendingImport, PendingPrompt,
+};
+use folder_sync::{pick_sync_folder, sync_markdown_folder, FolderSync};
+use importers::{confirm_import, list_importers, preview_import};
+use integrity::{check_database, repair_database};
+use library::{export_library, import_library};
+use markdown::{export_markdown, import_markdown};
+use migrations::get_migration_error;
+use palette::{palette_copy_prompt, search_prompts, PaletteShortcut};
+use settings::{get_app_settings, save_app_settings};
+use snippets::{delete_snippet, get_snippet_backlinks, list_snippets, save_snippet};
+use store::PromptStore;
+use template::{get_prompt_variables, render_prompt, DroppedFile};
+use trash::{empty_trash, list_trash, purge_from_trash, restore_from_trash};
+use tray::update_tray_menu;
+use usage::{record_prompt_usage, usage_stats, UsageAction, UsageSource};
+use versions::{diff_prompt_versions, list_prompt_versions, restore_prompt_version};
 
 // 初始化数据库
 fn init_db<R: Runtime>(app: &AppHandle<R>) {
     // 确保数据目录存在
-    let app_dir = app.path().app_data_dir().expect("无法获取app数据目录");
+    // tauri-plugin-sql 会把 sqlite:promptgenie.db 解析到配置目录下，这里保持一致
+    let app_dir = app.path().app_config_dir().expect("无法获取app配置目录");
     std::fs::create_dir_all(&app_dir).expect("无法创建数据目录");
 
     // 打印数据库路径
     let db_path = app_dir.join("promptgenie.db");
     println!("数据库路径: {}", db_path.to_string_lossy());
+
+    // 注册 Rust 端的数据访问
+    app.manage(PromptStore::new(db_path));
+}
+
+// 通知前端提示词发生变化（Rust 端修改数据库后调用）
+pub(crate) fn notify_prompts_changed<R: Runtime>(app_handle: &AppHandle<R>, ids: &[String]) {
+    if let Err(e) = app_handle.emit("prompts-changed", ids) {
+        println!("发送提示词变更事件失败: {}", e);
+    }
+    if let Err(e) = tray::refresh_tray_menu(app_handle) {
+        println!("{}", e);
+    }
 }
 
 #[tauri::command]
@@ -55,119 +114,128 @@ fn handle_tray_icon_event<R: Runtime>(tray_handle: &TrayIcon<R>, event: TrayIcon
     }
 }
 
-// 新增：用于更新托盘菜单的命令
-#[tauri::command]
-async fn update_tray_menu<R: Runtime>(
-    app_handle: AppHandle<R>,
-    items: Vec<PromptMenuItem>,
-) -> Result<(), String> {
-    // 构建菜单
-    let mut menu_builder = MenuBuilder::new(&app_handle);
-
-    // 添加"显示主窗口"选项
-    menu_builder = menu_builder.item(
-        &MenuItem::with_id(&app_handle, "show-window", "显示主窗口", true, None::<&str>)
-            .map_err(|e| e.to_string())?,
-    );
+// 判断提示词是否包含需要填写的模板变量
+pub(crate) fn prompt_has_variables<R: Runtime>(app_handle: &AppHandle<R>, prompt_id: &str) -> Result<bool, String> {
+    let store = app_handle.state::<PromptStore>();
+    let conn = store.open().map_err(|e| e.to_string())?;
+    let prompt = store::get_prompt(&conn, prompt_id).map_err(|e| e.to_string())?;
+    let content = snippets::expand(&conn, &prompt.content).map_err(|e| e.to_string())?;
+    Ok(!template::variables(&content).is_empty())
+}
 
-    // 添加分隔线
-    menu_builder = menu_builder.separator();
-
-    // 添加最近提示词的标题（不可点击）
-    menu_builder = menu_builder.item(
-        &MenuItem::with_id(
-            &app_handle,
-            "recent-title",
-            "最近使用的提示词",
-            false, // 设为false表示不可点击
-            None::<&str>,
-        )
-        .map_err(|e| e.to_string())?,
-    );
+// 渲染提示词并写入剪贴板，同时记录一次使用，返回提示词标题
+//
+// 完全在 Rust 端完成，不依赖主窗口的 webview 是否存在。
+pub(crate) fn copy_prompt_to_clipboard<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    prompt_id: &str,
+    source: UsageSource,
+) -> Result<String, String> {
+    let store = app_handle.state::<PromptStore>();
+    let conn = store.open().map_err(|e| e.to_string())?;
+    let prompt = store::get_prompt(&conn, prompt_id).map_err(|e| e.to_string())?;
+    let text = template::render_stored_prompt(app_handle, &conn, prompt_id, &Default::default())?;
+    app_handle
+        .clipboard()
+        .write_text(text)
+        .map_err(|e| format!("写入剪贴板失败: {}", e))?;
+    usage::record_usage(&conn, prompt_id, UsageAction::Copy, source).map_err(|e| e.to_string())?;
+    drop(conn);
 
-    // 添加最近使用的提示词
-    if items.is_empty() {
-        menu_builder = menu_builder.item(
-            &MenuItem::with_id(
-                &app_handle,
-                "no-recent",
-                "  无记录", // 增加缩进以表示层级关系
-                false,
-                None::<&str>,
-            )
-            .map_err(|e| e.to_string())?,
-        );
-    } else {
-        for item in items {
-            let title = if item.title.chars().count() > 30 {
-                format!("  {}...", item.title.chars().take(27).collect::<String>())
-            // 增加缩进
-            } else {
-                format!("  {}", item.title) // 增加缩进
-            };
-            menu_builder = menu_builder.item(
-                &MenuItem::with_id(&app_handle, &item.id, title, true, None::<&str>)
-                    .map_err(|e| e.to_string())?,
-            );
-        }
-    }
+    notify_prompts_changed(app_handle, &[prompt_id.to_string()]);
+    Ok(prompt.title)
+}
 
-    let menu = menu_builder
-        .separator()
-        .item(
-            &MenuItem::with_id(&app_handle, "quit", "退出", true, None::<&str>)
-                .map_err(|e| e.to_string())?,
-        )
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    // 获取现有托盘图标并更新菜单，而不是创建新图标
-    if let Some(tray_icon) = app_handle.tray_by_id("default") {
-        tray_icon.set_menu(Some(menu)).map_err(|e| e.to_string())?;
-        Ok(())
-    } else {
-        // 如果找不到默认托盘图标，打印信息并返回错误
-        eprintln!("找不到默认托盘图标");
-        Err("找不到默认托盘图标".to_string())
+// 打开变量填写窗口，填写完成后由该窗口负责渲染、复制并按来源记录使用
+pub(crate) fn open_fill_window<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    prompt_id: &str,
+    source: UsageSource,
+) -> tauri::Result<()> {
+    template::clear_dropped_file(app_handle);
+    if let Some(window) = app_handle.get_webview_window("fill") {
+        // 窗口已存在时切换到新的提示词
+        window.emit(
+            "fill-prompt",
+            serde_json::json!({ "promptId": prompt_id, "source": source }),
+        )?;
+        window.show()?;
+        window.set_focus()?;
+        return Ok(());
     }
+
+    let url = WebviewUrl::App(
+        format!("index.html?view=fill&promptId={}&source={}", prompt_id, source.as_str()).into(),
+    );
+    WebviewWindowBuilder::new(app_handle, "fill", url)
+        .title("填写变量 - PromptGenie")
+        .inner_size(420.0, 520.0)
+        .resizable(false)
+        .always_on_top(true)
+        .center()
+        .focused(true)
+        .build()?;
+    Ok(())
 }
 
 // 定义插件入口函数
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let migrations = vec![Migration {
-        version: 1,
-        description: "create initial tables",
-        sql: include_str!("../db/schema.sql"),
-        kind: MigrationKind::Up,
-    }];
-
     let builder = tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
-            println!("检测到第二个实例启动尝试，显示现有窗口");
+            println!("检测到第二个实例启动尝试");
             println!("参数: {:?}, 工作目录: {:?}", argv, cwd);
-            
-            // 尝试显示现有的主窗口
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.unminimize();
-                let _ = window.show();
-                let _ = window.set_focus();
-                println!("已将现有窗口带到前台");
-            } else {
-                println!("警告：找不到主窗口");
-            }
+
+            // 带子命令或链接时在当前实例中执行，否则显示现有窗口
+            cli::handle_argv(app, argv, cwd);
         }))
         .plugin(
-            tauri_plugin_sql::Builder::new()
-                .add_migrations("sqlite:promptgenie.db", migrations)
-                .build(),
+            // 表结构由 Rust 端的 migrations 模块在启动时维护
+            tauri_plugin_sql::Builder::new().build(),
         )
         .setup(|app| {
             // 初始化数据库目录（如果需要）
             init_db(&app.handle());
 
+            // 升级数据库结构，失败时记录错误，由前端提示用户
+            migrations::run_on_startup(app.handle());
+
+            // 启动时备份数据库，之后每天备份一次
+            backup::start(app.handle());
+
+            // 按保留策略清理历史版本，并自动清空过期的回收站
+            versions::prune_with_settings(app.handle());
+            trash::empty_expired_with_settings(app.handle());
+
+            // 填写窗口拖入的文件，供 selection_file 读取
+            app.manage(DroppedFile::default());
+
+            // 注册快捷搜索面板的全局快捷键
+            app.manage(PaletteShortcut::default());
+            if let Err(e) = palette::register_shortcut(app.handle()) {
+                eprintln!("{}", e);
+            }
+
+            // 按设置启动本地 HTTP API（默认关闭）
+            app.manage(ApiServer::default());
+            if let Err(e) = api::restart_server(app.handle()) {
+                eprintln!("{}", e);
+            }
+
+            // 处理 promptgenie:// 链接
+            app.manage(PendingPrompt::default());
+            app.manage(PendingImport::default());
+            deeplink::register(app.handle());
+
+            // 双击 .pgenie 分享文件时打开导入预览
+            app.manage(PendingBundle::default());
+
             // --- 动态调整窗口大小 ---
             if let Some(window) = app.get_webview_window("main") {
                 // 获取主显示器的尺寸
@@ -220,54 +288,35 @@ pub fn run() {
                 .clone();
 
             // 构建托盘图标并设置 ID
-            TrayIconBuilder::with_id("default")
+            TrayIconBuilder::with_id(tray::TRAY_ID)
                 .icon(icon)
                 .tooltip("PromptGenie")
                 .menu(&initial_menu)
-                .on_menu_event(move |app_handle_for_event, event| {
-                    let id = event.id().0.as_str();
-                    match id {
-                        "quit" => {
-                            std::process::exit(0);
-                        }
-                        "show-window" => {
-                            // 显示主窗口
-                            if let Some(window) = app_handle_for_event.get_webview_window("main") {
-                                let _ = window.unminimize();
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
-                        }
-                        "recent-title" | "no-recent" => {
-                            // 这些是不可点击或无操作的菜单项
-                        }
-                        _ => {
-                            println!("Clicked prompt menu item ID: {}", id);
-                            // 处理提示词菜单项点击
-                            if id.starts_with("no-") {
-                                return; // 无操作项，如"无记录"
-                            }
-
-                            // 把 ID 作为 payload 发送到前端，让前端获取提示词内容并复制到剪贴板
-                            let payload = id.to_string();
-
-                            // 发送事件到前端，表示要将此提示词内容复制到剪贴板
-                            if let Some(window) = app_handle_for_event.get_webview_window("main") {
-                                match window.emit("copy-prompt-to-clipboard", payload) {
-                                    Ok(_) => {
-                                        println!("已发送复制提示词内容到剪贴板事件");
-                                    }
-                                    Err(e) => {
-                                        eprintln!("发送复制提示词内容到剪贴板事件失败: {}", e);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                })
+                .on_menu_event(tray::handle_menu_event)
                 .on_tray_icon_event(handle_tray_icon_event)
                 .build(&app_handle)?;
 
+            // 从数据库构建完整菜单，之后数据库有变化时自动刷新
+            // 首次启动时数据表可能尚未由前端创建，等待后台刷新即可
+            if let Err(e) = tray::refresh_tray_menu(&app_handle) {
+                println!("{}", e);
+            }
+            tray::watch_database(app_handle.clone());
+
+            // 按设置与 Markdown 目录双向同步（默认关闭）
+            app.manage(FolderSync::default());
+            folder_sync::apply_settings(&app_handle);
+            folder_sync::watch(app_handle.clone());
+
+            // 首个实例也可能是由命令行子命令启动的
+            let argv: Vec<String> = std::env::args().collect();
+            if argv.len() > 1 {
+                let cwd = std::env::current_dir()
+                    .map(|dir| dir.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                cli::handle_argv(&app_handle, argv, cwd);
+            }
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -279,7 +328,56 @@ pub fn run() {
             get_feishu_table_fields,
             test_feishu_connection,
             trigger_sync,
-            sync_with_local_data
+            sync_with_local_data,
+            get_app_settings,
+            save_app_settings,
+            list_prompt_versions,
+            diff_prompt_versions,
+            restore_prompt_version,
+            list_trash,
+            restore_from_trash,
+            purge_from_trash,
+            empty_trash,
+            get_prompt_variables,
+            render_prompt,
+            list_snippets,
+            save_snippet,
+            delete_snippet,
+            get_snippet_backlinks,
+            search_prompts,
+            palette_copy_prompt,
+            record_prompt_usage,
+            usage_stats,
+            regenerate_api_token,
+            take_pending_prompt,
+            preview_link_import,
+            confirm_link_import,
+            cancel_link_import,
+            create_share_link,
+            export_library,
+            import_library,
+            export_markdown,
+            import_markdown,
+            sync_markdown_folder,
+            pick_sync_folder,
+            export_csv,
+            preview_csv,
+            import_csv,
+            list_importers,
+            preview_import,
+            confirm_import,
+            export_bundle,
+            preview_bundle,
+            import_bundle,
+            get_bundle_fingerprint,
+            take_pending_bundle,
+            list_backups,
+            create_backup,
+            verify_backup,
+            restore_backup,
+            get_migration_error,
+            check_database,
+            repair_database
         ]);
 
     // 构建应用实例
@@ -306,6 +404,26 @@ pub fn run() {
                     }
                 }
             }
+            // 拖入填写窗口的文件只在 Rust 端记录，渲染时作为 selection_file
+            tauri::RunEvent::WindowEvent {
+                label,
+                event: tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }),
+                ..
+            } if label == "fill" => {
+                template::record_dropped_file(app_handle, &paths);
+            }
+            // macOS 通过系统事件传入双击打开的文件，其他平台由单实例插件转发命令行参数
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            tauri::RunEvent::Opened { urls } => {
+                for path in urls.iter().filter_map(|url| url.to_file_path().ok()) {
+                    let is_bundle = path
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case(bundle::BUNDLE_EXTENSION));
+                    if is_bundle {
+                        bundle::open_file(app_handle, &path);
+                    }
+                }
+            }
             _ => {}
         }
     });
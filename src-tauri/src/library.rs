@@ -0,0 +1,756 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Runtime, State};
+use tauri_plugin_dialog::DialogExt;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::backup::{self, BackupReason};
+use crate::feishu_sync::parse_local_time;
+use crate::integrity;
+use crate::store::{now_timestamp, PromptStore, StoreError, DEFAULT_TAG_COLOR};
+
+/// 导出文件的格式标识和版本，结构变化时递增版本并在导入时兼容旧版本
+pub const LIBRARY_FORMAT: &str = "promptgenie-library";
+pub const LIBRARY_VERSION: u32 = 1;
+/// 没有标题时，从内容第一行截取标题的最大长度
+const DERIVED_TITLE_MAX_CHARS: usize = 40;
+
+#[derive(Debug, Error)]
+pub enum LibraryError {
+    #[error("文件读写失败: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON 格式错误: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{0}")]
+    Store(#[from] StoreError),
+    #[error("数据库操作失败: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("不是 PromptGenie 导出的文件（format: {0}）")]
+    UnknownFormat(String),
+    #[error("导出文件版本 {0} 高于当前支持的版本 {LIBRARY_VERSION}，请升级应用")]
+    UnsupportedVersion(u32),
+}
+
+/// 导出的提示词库
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryDocument {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    pub prompts: Vec<LibraryPrompt>,
+    pub tags: Vec<LibraryTag>,
+    pub prompt_tags: Vec<LibraryPromptTag>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryPrompt {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    #[serde(default)]
+    pub is_favorite: bool,
+    pub created_at: String,
+    pub updated_at: String,
+    #[serde(default)]
+    pub last_used_at: Option<String>,
+    #[serde(default)]
+    pub use_count: i64,
+    #[serde(default)]
+    pub deleted_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryTag {
+    pub id: String,
+    pub name: String,
+    pub color: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryPromptTag {
+    pub prompt_id: String,
+    pub tag_id: String,
+}
+
+/// ID 冲突时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStrategy {
+    /// 保留本地的
+    Skip,
+    /// 用导入的覆盖本地的
+    Overwrite,
+    /// 以新 ID 另存一份
+    Duplicate,
+    /// 保留修改时间较新的一方
+    #[default]
+    NewerWins,
+}
+
+/// 单条提示词的导入结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+    Created,
+    Updated,
+    Duplicated,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportChange {
+    /// 导入后在本地的 ID，另存时与文件中的不同
+    pub id: String,
+    pub source_id: String,
+    pub title: String,
+    pub action: ImportAction,
+}
+
+/// 导入报告
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    pub created: u32,
+    pub updated: u32,
+    pub duplicated: u32,
+    pub skipped: u32,
+    pub tags_created: u32,
+    pub changes: Vec<ImportChange>,
+}
+
+impl ImportReport {
+    /// 本地有变化的提示词 ID
+    pub fn changed_ids(&self) -> Vec<String> {
+        self.changes
+            .iter()
+            .filter(|change| change.action != ImportAction::Skipped)
+            .map(|change| change.id.clone())
+            .collect()
+    }
+
+    fn record(&mut self, id: String, prompt: &LibraryPrompt, action: ImportAction) {
+        match action {
+            ImportAction::Created => self.created += 1,
+            ImportAction::Updated => self.updated += 1,
+            ImportAction::Duplicated => self.duplicated += 1,
+            ImportAction::Skipped => self.skipped += 1,
+        }
+        self.changes.push(ImportChange {
+            id,
+            source_id: prompt.id.clone(),
+            title: prompt.title.clone(),
+            action,
+        });
+    }
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub prompts: usize,
+    pub tags: usize,
+}
+
+/// 读取整个提示词库
+pub fn export_document(
+    conn: &Connection,
+    include_trash: bool,
+) -> Result<LibraryDocument, LibraryError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, title, content, is_favorite, created_at, updated_at, last_used_at, use_count, deleted_at
+         FROM prompts WHERE ?1 OR deleted_at IS NULL ORDER BY created_at, id",
+    )?;
+    let prompts = stmt
+        .query_map(params![include_trash], |row| {
+            Ok(LibraryPrompt {
+                id: row.get("id")?,
+                title: row.get("title")?,
+                content: row.get("content")?,
+                is_favorite: row.get::<_, Option<i64>>("is_favorite")?.unwrap_or(0) == 1,
+                created_at: row.get("created_at")?,
+                updated_at: row.get("updated_at")?,
+                last_used_at: row.get("last_used_at")?,
+                use_count: row.get("use_count")?,
+                deleted_at: row.get("deleted_at")?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare("SELECT id, name, color FROM tags ORDER BY name, id")?;
+    let tags = stmt
+        .query_map([], |row| {
+            Ok(LibraryTag {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT pt.prompt_id, pt.tag_id FROM prompt_tags pt
+         JOIN prompts p ON p.id = pt.prompt_id
+         WHERE ?1 OR p.deleted_at IS NULL ORDER BY pt.prompt_id, pt.tag_id",
+    )?;
+    let prompt_tags = stmt
+        .query_map(params![include_trash], |row| {
+            Ok(LibraryPromptTag {
+                prompt_id: row.get(0)?,
+                tag_id: row.get(1)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(LibraryDocument {
+        format: LIBRARY_FORMAT.to_string(),
+        version: LIBRARY_VERSION,
+        exported_at: now_timestamp(),
+        prompts,
+        tags,
+        prompt_tags,
+    })
+}
+
+/// 解析导出文件并检查格式和版本
+pub fn parse_document(content: &str) -> Result<LibraryDocument, LibraryError> {
+    let document: LibraryDocument = serde_json::from_str(content)?;
+    if document.format != LIBRARY_FORMAT {
+        return Err(LibraryError::UnknownFormat(document.format));
+    }
+    if document.version > LIBRARY_VERSION {
+        return Err(LibraryError::UnsupportedVersion(document.version));
+    }
+    let mut document = document;
+    normalize_times(&mut document);
+    Ok(document)
+}
+
+/// 把文件中的时间统一为 RFC3339
+///
+/// 无法识别的修改时间取文件的导出时间（也无法识别时取当前时间），创建时间和删除时间
+/// 取修改时间，使用时间丢弃。
+fn normalize_times(document: &mut LibraryDocument) {
+    let fallback =
+        integrity::normalize_timestamp(&document.exported_at).unwrap_or_else(now_timestamp);
+    for prompt in &mut document.prompts {
+        prompt.updated_at =
+            integrity::normalize_timestamp(&prompt.updated_at).unwrap_or_else(|| fallback.clone());
+        prompt.created_at = integrity::normalize_timestamp(&prompt.created_at)
+            .unwrap_or_else(|| prompt.updated_at.clone());
+        prompt.last_used_at = normalize_time(&prompt.last_used_at);
+        prompt.deleted_at = prompt.deleted_at.as_deref().map(|value| {
+            integrity::normalize_timestamp(value).unwrap_or_else(|| prompt.updated_at.clone())
+        });
+    }
+}
+
+/// 导入的一方是否比本地的新，无法解析时间时按字符串比较
+fn is_newer(imported: &str, local: &str) -> bool {
+    match (parse_local_time(imported), parse_local_time(local)) {
+        (Some(imported), Some(local)) => imported > local,
+        _ => imported > local,
+    }
+}
+
+/// 把文件中的标签映射到本地：同名标签直接复用，否则新建
+fn import_tags(
+    conn: &Connection,
+    tags: &[LibraryTag],
+    report: &mut ImportReport,
+) -> Result<HashMap<String, String>, LibraryError> {
+    let mut mapping = HashMap::new();
+    for tag in tags {
+        let name = tag.name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        let existing: Option<String> = conn
+            .query_row(
+                "SELECT id FROM tags WHERE name = ?1",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let id = match existing {
+            Some(id) => id,
+            None => {
+                let id_taken: bool = conn.query_row(
+                    "SELECT EXISTS (SELECT 1 FROM tags WHERE id = ?1)",
+                    params![tag.id],
+                    |row| row.get(0),
+                )?;
+                let id = if id_taken {
+                    Uuid::now_v7().to_string()
+                } else {
+                    tag.id.clone()
+                };
+                conn.execute(
+                    "INSERT INTO tags (id, name, color) VALUES (?1, ?2, ?3)",
+                    params![id, name, tag.color],
+                )?;
+                report.tags_created += 1;
+                id
+            }
+        };
+        mapping.insert(tag.id.clone(), id);
+    }
+    Ok(mapping)
+}
+
+fn insert_prompt(
+    conn: &Connection,
+    id: &str,
+    prompt: &LibraryPrompt,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO prompts (id, title, content, is_favorite, created_at, updated_at, last_used_at, use_count, deleted_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            id,
+            prompt.title,
+            prompt.content,
+            prompt.is_favorite,
+            prompt.created_at,
+            prompt.updated_at,
+            prompt.last_used_at.as_deref().unwrap_or(&prompt.updated_at),
+            prompt.use_count,
+            prompt.deleted_at,
+        ],
+    )?;
+    Ok(())
+}
+
+fn overwrite_prompt(conn: &Connection, prompt: &LibraryPrompt) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "UPDATE prompts SET title = ?1, content = ?2, is_favorite = ?3, created_at = ?4, updated_at = ?5,
+                last_used_at = COALESCE(?6, last_used_at), deleted_at = ?7
+         WHERE id = ?8",
+        params![
+            prompt.title,
+            prompt.content,
+            prompt.is_favorite,
+            prompt.created_at,
+            prompt.updated_at,
+            prompt.last_used_at,
+            prompt.deleted_at,
+            prompt.id,
+        ],
+    )?;
+    Ok(())
+}
+
+/// 用文件中的关联替换提示词的标签
+fn replace_prompt_tags(
+    conn: &Connection,
+    id: &str,
+    tag_ids: &[String],
+) -> Result<(), rusqlite::Error> {
+    conn.execute("DELETE FROM prompt_tags WHERE prompt_id = ?1", params![id])?;
+    for tag_id in tag_ids {
+        conn.execute(
+            "INSERT OR IGNORE INTO prompt_tags (prompt_id, tag_id) VALUES (?1, ?2)",
+            params![id, tag_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// 把导出文件合并到本地，整个导入在一个事务中完成
+pub fn import_document(
+    conn: &Connection,
+    document: &LibraryDocument,
+    strategy: ImportStrategy,
+) -> Result<ImportReport, LibraryError> {
+    let tx = conn.unchecked_transaction()?;
+    let report = merge_document(&tx, document, strategy)?;
+    tx.commit()?;
+    Ok(report)
+}
+
+/// 把导出文件合并到本地，不开启事务，由调用方在同一个事务中完成其他修改
+pub(crate) fn merge_document(
+    conn: &Connection,
+    document: &LibraryDocument,
+    strategy: ImportStrategy,
+) -> Result<ImportReport, LibraryError> {
+    let mut report = ImportReport::default();
+
+    let tag_mapping = import_tags(conn, &document.tags, &mut report)?;
+    let mut prompt_tags: HashMap<&str, Vec<String>> = HashMap::new();
+    for link in &document.prompt_tags {
+        if let Some(tag_id) = tag_mapping.get(&link.tag_id) {
+            prompt_tags
+                .entry(link.prompt_id.as_str())
+                .or_default()
+                .push(tag_id.clone());
+        }
+    }
+
+    for prompt in &document.prompts {
+        let local_updated_at: Option<String> = conn
+            .query_row(
+                "SELECT updated_at FROM prompts WHERE id = ?1",
+                params![prompt.id],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        let (id, action) = match local_updated_at {
+            None => {
+                insert_prompt(conn, &prompt.id, prompt)?;
+                (prompt.id.clone(), ImportAction::Created)
+            }
+            Some(local) => match strategy {
+                ImportStrategy::Skip => (prompt.id.clone(), ImportAction::Skipped),
+                ImportStrategy::NewerWins if !is_newer(&prompt.updated_at, &local) => {
+                    (prompt.id.clone(), ImportAction::Skipped)
+                }
+                ImportStrategy::Overwrite | ImportStrategy::NewerWins => {
+                    overwrite_prompt(conn, prompt)?;
+                    (prompt.id.clone(), ImportAction::Updated)
+                }
+                ImportStrategy::Duplicate => {
+                    let id = Uuid::now_v7().to_string();
+                    insert_prompt(conn, &id, prompt)?;
+                    (id, ImportAction::Duplicated)
+                }
+            },
+        };
+
+        if action != ImportAction::Skipped {
+            let tag_ids = prompt_tags
+                .get(prompt.id.as_str())
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            replace_prompt_tags(conn, &id, tag_ids)?;
+        }
+        report.record(id, prompt, action);
+    }
+
+    Ok(report)
+}
+
+/// 从其他格式转换来的提示词，缺少的字段在组成导出文档时补齐
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DraftPrompt {
+    pub id: Option<String>,
+    pub title: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub is_favorite: bool,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+/// 内容中第一个非空行作为标题
+pub fn title_from_content(content: &str) -> String {
+    let line = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    line.chars().take(DERIVED_TITLE_MAX_CHARS).collect()
+}
+
+fn normalize_time(value: &Option<String>) -> Option<String> {
+    value.as_deref().and_then(integrity::normalize_timestamp)
+}
+
+/// 把转换来的提示词组成导出文档，之后按 JSON 导入的规则合并
+///
+/// 没有 id 的作为新提示词，标签按名称合并。时间统一转换为 RFC3339，
+/// 没有时间或无法识别（例如表格中随手写的日期）时使用当前时间。
+pub fn document_from_drafts(drafts: &[DraftPrompt]) -> LibraryDocument {
+    let now = now_timestamp();
+    let mut tags: HashMap<String, LibraryTag> = HashMap::new();
+    let mut prompts = Vec::with_capacity(drafts.len());
+    let mut prompt_tags = Vec::new();
+
+    for draft in drafts {
+        let id = draft
+            .id
+            .clone()
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or_else(|| Uuid::now_v7().to_string());
+        let updated_at = normalize_time(&draft.updated_at).unwrap_or_else(|| now.clone());
+
+        for name in &draft.tags {
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            let tag = tags.entry(name.to_string()).or_insert_with(|| LibraryTag {
+                id: Uuid::now_v7().to_string(),
+                name: name.to_string(),
+                color: DEFAULT_TAG_COLOR.to_string(),
+            });
+            prompt_tags.push(LibraryPromptTag {
+                prompt_id: id.clone(),
+                tag_id: tag.id.clone(),
+            });
+        }
+
+        prompts.push(LibraryPrompt {
+            id,
+            title: draft.title.clone(),
+            content: draft.content.clone(),
+            is_favorite: draft.is_favorite,
+            created_at: normalize_time(&draft.created_at).unwrap_or_else(|| updated_at.clone()),
+            updated_at,
+            last_used_at: None,
+            use_count: 0,
+            deleted_at: None,
+        });
+    }
+
+    LibraryDocument {
+        format: LIBRARY_FORMAT.to_string(),
+        version: LIBRARY_VERSION,
+        exported_at: now,
+        prompts,
+        tags: tags.into_values().collect(),
+        prompt_tags,
+    }
+}
+
+/// 写入导出文件
+pub fn export_to_file(
+    conn: &Connection,
+    path: &Path,
+    include_trash: bool,
+) -> Result<ExportSummary, LibraryError> {
+    let document = export_document(conn, include_trash)?;
+    std::fs::write(path, serde_json::to_string_pretty(&document)?)?;
+    Ok(ExportSummary {
+        path: path.to_string_lossy().into_owned(),
+        prompts: document.prompts.len(),
+        tags: document.tags.len(),
+    })
+}
+
+/// 读取导出文件并导入
+pub fn import_from_file(
+    conn: &Connection,
+    path: &Path,
+    strategy: ImportStrategy,
+) -> Result<ImportReport, LibraryError> {
+    let document = parse_document(&std::fs::read_to_string(path)?)?;
+    import_document(conn, &document, strategy)
+}
+
+/// 导出文件的默认名称，例如 promptgenie-20250101.json
+fn default_file_name() -> String {
+    format!("promptgenie-{}.json", chrono::Local::now().format("%Y%m%d"))
+}
+
+/// 导出提示词库，未指定路径时弹出保存对话框，取消时返回 None
+#[tauri::command]
+pub async fn export_library<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+    path: Option<String>,
+    include_trash: bool,
+) -> Result<Option<ExportSummary>, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => match app_handle
+            .dialog()
+            .file()
+            .add_filter("JSON", &["json"])
+            .set_file_name(default_file_name())
+            .blocking_save_file()
+            .and_then(|path| path.into_path().ok())
+        {
+            Some(path) => path,
+            None => return Ok(None),
+        },
+    };
+
+    let conn = store.open().map_err(|e| e.to_string())?;
+    let summary =
+        export_to_file(&conn, &path, include_trash).map_err(|e| format!("导出失败: {}", e))?;
+    println!("已导出 {} 条提示词到 {}", summary.prompts, summary.path);
+    Ok(Some(summary))
+}
+
+/// 导入提示词库，未指定路径时弹出打开对话框，取消时返回 None
+#[tauri::command]
+pub async fn import_library<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+    path: Option<String>,
+    strategy: ImportStrategy,
+) -> Result<Option<ImportReport>, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => match app_handle
+            .dialog()
+            .file()
+            .add_filter("JSON", &["json"])
+            .blocking_pick_file()
+            .and_then(|path| path.into_path().ok())
+        {
+            Some(path) => path,
+            None => return Ok(None),
+        },
+    };
+
+    backup::before_change(&store, BackupReason::Import)?;
+    let report = {
+        let conn = store.open().map_err(|e| e.to_string())?;
+        import_from_file(&conn, &path, strategy).map_err(|e| format!("导入失败: {}", e))?
+    };
+    println!(
+        "导入完成: 新建 {}，更新 {}，另存 {}，跳过 {}",
+        report.created, report.updated, report.duplicated, report.skipped
+    );
+    crate::notify_prompts_changed(&app_handle, &report.changed_ids());
+    Ok(Some(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::open_in_memory;
+
+    const LOCAL_TIME: &str = "2024-06-01T00:00:00.000Z";
+
+    fn prompt(id: &str, content: &str, updated_at: &str) -> LibraryPrompt {
+        LibraryPrompt {
+            id: id.to_string(),
+            title: id.to_string(),
+            content: content.to_string(),
+            is_favorite: false,
+            created_at: updated_at.to_string(),
+            updated_at: updated_at.to_string(),
+            last_used_at: None,
+            use_count: 0,
+            deleted_at: None,
+        }
+    }
+
+    fn document(prompts: Vec<LibraryPrompt>) -> LibraryDocument {
+        LibraryDocument {
+            format: LIBRARY_FORMAT.to_string(),
+            version: LIBRARY_VERSION,
+            exported_at: LOCAL_TIME.to_string(),
+            prompts,
+            tags: Vec::new(),
+            prompt_tags: Vec::new(),
+        }
+    }
+
+    /// 本地只有一条提示词 a
+    fn local_library() -> Connection {
+        let conn = open_in_memory();
+        import_document(
+            &conn,
+            &document(vec![prompt("a", "本地", LOCAL_TIME)]),
+            ImportStrategy::Skip,
+        )
+        .unwrap();
+        conn
+    }
+
+    fn contents(conn: &Connection) -> Vec<(String, String)> {
+        let mut stmt = conn
+            .prepare("SELECT id, content FROM prompts ORDER BY content")
+            .unwrap();
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    fn local_content(conn: &Connection, id: &str) -> String {
+        conn.query_row("SELECT content FROM prompts WHERE id = ?1", [id], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    fn actions(report: &ImportReport) -> Vec<(&str, ImportAction)> {
+        report
+            .changes
+            .iter()
+            .map(|change| (change.source_id.as_str(), change.action))
+            .collect()
+    }
+
+    #[test]
+    fn skip_keeps_local_prompt() {
+        let conn = local_library();
+        let imported = document(vec![
+            prompt("a", "导入", "2025-01-01T00:00:00.000Z"),
+            prompt("b", "新的", LOCAL_TIME),
+        ]);
+        let report = import_document(&conn, &imported, ImportStrategy::Skip).unwrap();
+        assert_eq!(
+            actions(&report),
+            vec![("a", ImportAction::Skipped), ("b", ImportAction::Created)]
+        );
+        assert_eq!(local_content(&conn, "a"), "本地");
+    }
+
+    #[test]
+    fn overwrite_replaces_local_prompt() {
+        let conn = local_library();
+        let imported = document(vec![prompt("a", "导入", "2020-01-01T00:00:00.000Z")]);
+        let report = import_document(&conn, &imported, ImportStrategy::Overwrite).unwrap();
+        assert_eq!(actions(&report), vec![("a", ImportAction::Updated)]);
+        assert_eq!(local_content(&conn, "a"), "导入");
+    }
+
+    #[test]
+    fn newer_wins_compares_update_times() {
+        let conn = local_library();
+        let older = document(vec![prompt("a", "较旧", "2024-05-31T23:59:59.000Z")]);
+        let report = import_document(&conn, &older, ImportStrategy::NewerWins).unwrap();
+        assert_eq!(actions(&report), vec![("a", ImportAction::Skipped)]);
+        assert_eq!(local_content(&conn, "a"), "本地");
+
+        // 不同时区的写法按实际时间比较
+        let newer = document(vec![prompt("a", "较新", "2024-06-01T08:00:01+08:00")]);
+        let report = import_document(&conn, &newer, ImportStrategy::NewerWins).unwrap();
+        assert_eq!(actions(&report), vec![("a", ImportAction::Updated)]);
+        assert_eq!(local_content(&conn, "a"), "较新");
+    }
+
+    #[test]
+    fn duplicate_saves_a_copy_with_new_id() {
+        let conn = local_library();
+        let imported = document(vec![prompt("a", "导入", LOCAL_TIME)]);
+        let report = import_document(&conn, &imported, ImportStrategy::Duplicate).unwrap();
+        assert_eq!(actions(&report), vec![("a", ImportAction::Duplicated)]);
+        assert_ne!(report.changes[0].id, "a");
+
+        let contents = contents(&conn);
+        assert_eq!(contents.len(), 2);
+        assert!(contents.contains(&("a".to_string(), "本地".to_string())));
+        assert!(contents.contains(&(report.changes[0].id.clone(), "导入".to_string())));
+    }
+
+    #[test]
+    fn parse_document_normalizes_times() {
+        let mut file = document(vec![prompt("a", "内容", "2024-06-01 08:00:00")]);
+        file.prompts[0].created_at = "不是时间".to_string();
+        file.prompts[0].last_used_at = Some("1717228800000".to_string());
+        file.prompts[0].deleted_at = Some("昨天".to_string());
+        let mut broken = prompt("b", "内容", "garbage");
+        broken.created_at = "2024/01/02".to_string();
+        file.prompts.push(broken);
+
+        let parsed = parse_document(&serde_json::to_string(&file).unwrap()).unwrap();
+        let a = &parsed.prompts[0];
+        assert_eq!(a.updated_at, "2024-06-01T08:00:00.000Z");
+        assert_eq!(a.created_at, a.updated_at);
+        assert_eq!(a.last_used_at.as_deref(), Some("2024-06-01T08:00:00.000Z"));
+        assert_eq!(a.deleted_at.as_deref(), Some("2024-06-01T08:00:00.000Z"));
+        let b = &parsed.prompts[1];
+        assert_eq!(b.updated_at, "2024-06-01T00:00:00.000Z");
+        assert_eq!(b.created_at, "2024-01-02T00:00:00.000Z");
+    }
+}
@@ -2,5 +2,13 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // --mcp：作为 MCP 服务通过标准输入输出通信，不启动窗口
+    if std::env::args().any(|arg| arg == "--mcp") {
+        std::process::exit(prompt_genie_lib::mcp::run_stdio());
+    }
+    // 命令行子命令交给运行中的实例执行，这里只等待结果
+    if let Some(code) = prompt_genie_lib::cli::forward_to_instance() {
+        std::process::exit(code);
+    }
     prompt_genie_lib::run()
 }
@@ -0,0 +1,372 @@
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Runtime, State};
+use tauri_plugin_dialog::DialogExt;
+use thiserror::Error;
+
+use crate::backup::{self, BackupReason};
+use crate::library::{
+    self, DraftPrompt, ImportReport, ImportStrategy, LibraryDocument, LibraryError,
+};
+use crate::store::{self, PromptStore};
+
+/// 文件名中 slug 部分的最大长度
+const SLUG_MAX_CHARS: usize = 60;
+
+#[derive(Debug, Error)]
+pub enum MarkdownError {
+    #[error("文件读写失败: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{}: frontmatter 格式错误: {}", .0.display(), .1)]
+    Frontmatter(PathBuf, serde_yaml::Error),
+    #[error("{0}")]
+    Library(#[from] LibraryError),
+    #[error("{0}")]
+    Store(#[from] store::StoreError),
+}
+
+/// 文件头部的 YAML frontmatter
+///
+/// 不包含 last_used_at 和使用次数，避免每次使用提示词后重新导出都产生改动。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Frontmatter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    pub favorite: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+/// 一个 Markdown 提示词文件
+#[derive(Debug, Clone)]
+pub(crate) struct MarkdownPrompt {
+    pub path: PathBuf,
+    pub frontmatter: Frontmatter,
+    pub content: String,
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MarkdownExportSummary {
+    pub path: String,
+    /// 新写入或内容有变化的文件
+    pub written: u32,
+    pub unchanged: u32,
+    /// 本地已不存在的提示词对应的文件
+    pub removed: u32,
+}
+
+/// 生成文件内容：frontmatter、空行、正文
+pub(crate) fn render_file(
+    frontmatter: &Frontmatter,
+    content: &str,
+) -> Result<String, serde_yaml::Error> {
+    let yaml = serde_yaml::to_string(frontmatter)?;
+    Ok(format!("---\n{}---\n\n{}\n", yaml, content))
+}
+
+/// 解析文件内容，没有 frontmatter 时整个文件都是正文
+pub(crate) fn parse_file(path: &Path, text: &str) -> Result<MarkdownPrompt, MarkdownError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let (frontmatter, body) = match split_frontmatter(text) {
+        Some((yaml, body)) => {
+            let frontmatter = if yaml.trim().is_empty() {
+                Frontmatter::default()
+            } else {
+                serde_yaml::from_str(yaml)
+                    .map_err(|e| MarkdownError::Frontmatter(path.to_path_buf(), e))?
+            };
+            (frontmatter, body)
+        }
+        None => (Frontmatter::default(), text),
+    };
+
+    // 与 render_file 对应：去掉正文前的空行和末尾补上的换行
+    let body = body
+        .strip_prefix("\r\n")
+        .or_else(|| body.strip_prefix('\n'))
+        .unwrap_or(body);
+    let body = body
+        .strip_suffix("\r\n")
+        .or_else(|| body.strip_suffix('\n'))
+        .unwrap_or(body);
+
+    Ok(MarkdownPrompt {
+        path: path.to_path_buf(),
+        frontmatter,
+        content: body.to_string(),
+    })
+}
+
+/// 拆分出 `---` 包围的 frontmatter 和正文
+fn split_frontmatter(text: &str) -> Option<(&str, &str)> {
+    let rest = text
+        .strip_prefix("---\r\n")
+        .or_else(|| text.strip_prefix("---\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// 由标题生成文件名（不含扩展名），中文转为拼音
+pub(crate) fn slugify_title(title: &str) -> String {
+    let slug: String = slug::slugify(title).chars().take(SLUG_MAX_CHARS).collect();
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "prompt".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// 目录（含子目录）中的所有 .md 文件
+pub(crate) fn markdown_files(dir: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let hidden = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with('.'));
+            if hidden {
+                continue;
+            }
+            if path.is_dir() {
+                pending.push(path);
+            } else if path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+            {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// 读取目录中的提示词文件，无法解析的文件跳过并打印原因
+pub(crate) fn read_folder(dir: &Path) -> Result<Vec<MarkdownPrompt>, MarkdownError> {
+    let mut prompts = Vec::new();
+    for path in markdown_files(dir)? {
+        let text = std::fs::read_to_string(&path)?;
+        match parse_file(&path, &text) {
+            Ok(prompt) => prompts.push(prompt),
+            Err(e) => eprintln!("跳过 {}", e),
+        }
+    }
+    Ok(prompts)
+}
+
+/// 导出到目录，每条提示词一个文件
+///
+/// 已经导出过的提示词沿用原来的文件（按 frontmatter 中的 id 匹配），改标题也不会改文件名；
+/// 内容没有变化的文件不会重写。`prune` 为 true 时删除本地已不存在的提示词对应的文件。
+pub fn export_folder(
+    conn: &Connection,
+    dir: &Path,
+    prune: bool,
+) -> Result<MarkdownExportSummary, MarkdownError> {
+    std::fs::create_dir_all(dir)?;
+    let existing = read_folder(dir)?;
+    let mut paths_by_id: HashMap<String, PathBuf> = HashMap::new();
+    let mut used_paths: HashSet<PathBuf> = HashSet::new();
+    for file in &existing {
+        used_paths.insert(file.path.clone());
+        if let Some(id) = &file.frontmatter.id {
+            paths_by_id
+                .entry(id.clone())
+                .or_insert_with(|| file.path.clone());
+        }
+    }
+
+    let document = library::export_document(conn, false)?;
+    let tag_names: HashMap<&str, &str> = document
+        .tags
+        .iter()
+        .map(|tag| (tag.id.as_str(), tag.name.as_str()))
+        .collect();
+    let mut prompt_tags: HashMap<&str, Vec<String>> = HashMap::new();
+    for link in &document.prompt_tags {
+        if let Some(name) = tag_names.get(link.tag_id.as_str()) {
+            prompt_tags
+                .entry(link.prompt_id.as_str())
+                .or_default()
+                .push(name.to_string());
+        }
+    }
+
+    let mut summary = MarkdownExportSummary {
+        path: dir.to_string_lossy().into_owned(),
+        ..Default::default()
+    };
+    let mut exported_ids = HashSet::new();
+    for prompt in &document.prompts {
+        exported_ids.insert(prompt.id.as_str());
+        let mut tags = prompt_tags.remove(prompt.id.as_str()).unwrap_or_default();
+        tags.sort();
+        let frontmatter = Frontmatter {
+            id: Some(prompt.id.clone()),
+            title: Some(prompt.title.clone()),
+            tags,
+            favorite: prompt.is_favorite,
+            created_at: Some(prompt.created_at.clone()),
+            updated_at: Some(prompt.updated_at.clone()),
+        };
+        let text = render_file(&frontmatter, &prompt.content)
+            .map_err(|e| MarkdownError::Frontmatter(dir.to_path_buf(), e))?;
+
+        let path = match paths_by_id.get(&prompt.id) {
+            Some(path) => path.clone(),
+            None => {
+                let path = unique_path(dir, &slugify_title(&prompt.title), &used_paths);
+                used_paths.insert(path.clone());
+                path
+            }
+        };
+        if std::fs::read_to_string(&path).is_ok_and(|current| current == text) {
+            summary.unchanged += 1;
+        } else {
+            std::fs::write(&path, text)?;
+            summary.written += 1;
+        }
+    }
+
+    if prune {
+        for file in &existing {
+            let Some(id) = &file.frontmatter.id else {
+                continue;
+            };
+            if !exported_ids.contains(id.as_str()) && paths_by_id.get(id) == Some(&file.path) {
+                std::fs::remove_file(&file.path)?;
+                summary.removed += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// 同名时依次追加 -2、-3……
+fn unique_path(dir: &Path, slug: &str, used: &HashSet<PathBuf>) -> PathBuf {
+    let mut path = dir.join(format!("{}.md", slug));
+    let mut index = 2;
+    while used.contains(&path) || path.exists() {
+        path = dir.join(format!("{}-{}.md", slug, index));
+        index += 1;
+    }
+    path
+}
+
+/// 文件的标题，frontmatter 中没有时使用文件名
+pub(crate) fn file_title(file: &MarkdownPrompt) -> String {
+    file.frontmatter
+        .title
+        .clone()
+        .filter(|title| !title.trim().is_empty())
+        .or_else(|| {
+            file.path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default()
+}
+
+/// 把 Markdown 文件转换为导出文档，之后按 JSON 导入的规则合并
+///
+/// 没有 id 的文件作为新提示词，没有标题时使用文件名，没有时间时使用当前时间。
+pub(crate) fn to_document(files: &[MarkdownPrompt]) -> LibraryDocument {
+    let drafts: Vec<DraftPrompt> = files
+        .iter()
+        .map(|file| DraftPrompt {
+            id: file.frontmatter.id.clone(),
+            title: file_title(file),
+            content: file.content.clone(),
+            tags: file.frontmatter.tags.clone(),
+            is_favorite: file.frontmatter.favorite,
+            created_at: file.frontmatter.created_at.clone(),
+            updated_at: file.frontmatter.updated_at.clone(),
+        })
+        .collect();
+    library::document_from_drafts(&drafts)
+}
+
+/// 从目录导入，ID 冲突时的处理与 JSON 导入相同
+pub fn import_folder(
+    conn: &Connection,
+    dir: &Path,
+    strategy: ImportStrategy,
+) -> Result<ImportReport, MarkdownError> {
+    let files = read_folder(dir)?;
+    Ok(library::import_document(
+        conn,
+        &to_document(&files),
+        strategy,
+    )?)
+}
+
+fn pick_folder<R: Runtime>(app_handle: &AppHandle<R>, path: Option<String>) -> Option<PathBuf> {
+    match path {
+        Some(path) => Some(PathBuf::from(path)),
+        None => app_handle
+            .dialog()
+            .file()
+            .blocking_pick_folder()
+            .and_then(|path| path.into_path().ok()),
+    }
+}
+
+/// 导出为 Markdown 目录，未指定路径时弹出选择目录对话框，取消时返回 None
+#[tauri::command]
+pub async fn export_markdown<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+    path: Option<String>,
+    prune: bool,
+) -> Result<Option<MarkdownExportSummary>, String> {
+    let Some(dir) = pick_folder(&app_handle, path) else {
+        return Ok(None);
+    };
+    let conn = store.open().map_err(|e| e.to_string())?;
+    let summary = export_folder(&conn, &dir, prune).map_err(|e| format!("导出失败: {}", e))?;
+    println!(
+        "已导出到 {}: 写入 {}，未变化 {}，删除 {}",
+        summary.path, summary.written, summary.unchanged, summary.removed
+    );
+    Ok(Some(summary))
+}
+
+/// 从 Markdown 目录导入，未指定路径时弹出选择目录对话框，取消时返回 None
+#[tauri::command]
+pub async fn import_markdown<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+    path: Option<String>,
+    strategy: ImportStrategy,
+) -> Result<Option<ImportReport>, String> {
+    let Some(dir) = pick_folder(&app_handle, path) else {
+        return Ok(None);
+    };
+    backup::before_change(&store, BackupReason::Import)?;
+    let report = {
+        let conn = store.open().map_err(|e| e.to_string())?;
+        import_folder(&conn, &dir, strategy).map_err(|e| format!("导入失败: {}", e))?
+    };
+    crate::notify_prompts_changed(&app_handle, &report.changed_ids());
+    Ok(Some(report))
+}
@@ -0,0 +1,409 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use crate::builtins::BuiltinKind;
+use crate::headless::{default_config_dir, open_store};
+use crate::store::{self, PromptFilter, PromptStore, StoreError};
+use crate::template::{self, VariableKind};
+use crate::usage::{self, UsageAction, UsageSource};
+use crate::{palette, snippets};
+
+/// 支持的 MCP 协议版本，新的在前
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// 提示词描述的最大字符数
+const DESCRIPTION_CHARS: usize = 100;
+
+// JSON-RPC 错误码
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// MCP 请求处理出错，转换为 JSON-RPC 错误响应
+struct McpError {
+    code: i64,
+    message: String,
+}
+
+impl McpError {
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<StoreError> for McpError {
+    fn from(e: StoreError) -> Self {
+        let code = match e {
+            StoreError::PromptNotFound(_) => INVALID_PARAMS,
+            _ => INTERNAL_ERROR,
+        };
+        Self {
+            code,
+            message: e.to_string(),
+        }
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// 处理一条 JSON-RPC 消息，通知类消息没有响应
+///
+/// stdio 和 streamable HTTP 共用。`on_change` 在提示词被新建或使用后调用，
+/// 应用内运行时用来通知前端刷新。
+pub fn handle_message(store: &PromptStore, message: Value, on_change: &dyn Fn(&[String])) -> Option<Value> {
+    let id = message.get("id").cloned();
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        // 客户端发来的响应（本服务不发请求），直接忽略
+        if message.get("result").is_some() || message.get("error").is_some() {
+            return None;
+        }
+        return Some(error_response(id.unwrap_or(Value::Null), INVALID_REQUEST, "缺少 method"));
+    };
+    // 没有 id 的是通知（如 notifications/initialized），不需要响应
+    let id = id?;
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => Ok(initialize(&params)),
+        "ping" => Ok(json!({})),
+        "prompts/list" => list_prompts(store),
+        "prompts/get" => get_prompt(store, &params, on_change),
+        "tools/list" => Ok(list_tools()),
+        "tools/call" => call_tool(store, &params, on_change),
+        _ => Err(McpError {
+            code: METHOD_NOT_FOUND,
+            message: format!("不支持的方法: {}", method),
+        }),
+    };
+
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => error_response(id, e.code, &e.message),
+    })
+}
+
+/// 解析并处理一行文本（stdio 传输每行一条消息）
+pub fn handle_line(store: &PromptStore, line: &str, on_change: &dyn Fn(&[String])) -> Option<Value> {
+    match serde_json::from_str::<Value>(line) {
+        Ok(message) => handle_message(store, message, on_change),
+        Err(e) => Some(error_response(
+            Value::Null,
+            PARSE_ERROR,
+            &format!("JSON 解析失败: {}", e),
+        )),
+    }
+}
+
+fn initialize(params: &Value) -> Value {
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .filter(|version| PROTOCOL_VERSIONS.contains(version))
+        .unwrap_or(PROTOCOL_VERSIONS[0]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": {
+            "prompts": { "listChanged": false },
+            "tools": { "listChanged": false },
+        },
+        "serverInfo": {
+            "name": "promptgenie",
+            "title": "PromptGenie",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "instructions": "PromptGenie 提示词库：prompts/list 列出提示词，模板变量对应提示词参数；search_prompts 搜索，save_prompt 保存新的提示词。",
+    })
+}
+
+/// 提示词描述：标签加内容开头
+fn describe(content: &str, tags: &[String]) -> String {
+    let preview: String = content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(DESCRIPTION_CHARS)
+        .collect();
+    if tags.is_empty() {
+        preview
+    } else {
+        format!("[{}] {}", tags.join(", "), preview)
+    }
+}
+
+/// 模板变量映射为提示词参数，内置变量自动填充不在其中
+///
+/// selection_file 会读取本地文件，只能在应用的填写窗口中拖入，不作为参数提供。
+/// 引用的片段不存在或循环引用时使用未展开的内容，不影响其他提示词的列表，
+/// 获取这条提示词时再报告错误。
+fn prompt_arguments(conn: &rusqlite::Connection, content: &str) -> Vec<Value> {
+    let content = snippets::expand(conn, content).unwrap_or_else(|_| content.to_string());
+    template::variables(&content)
+        .into_iter()
+        .filter(|variable| variable.kind != VariableKind::File)
+        .map(|variable| {
+            let mut description = variable.description.unwrap_or_default();
+            match &variable.kind {
+                VariableKind::Choice(options) => {
+                    description = format!("{} 可选: {}", description, options.join(" / "));
+                }
+                VariableKind::Text | VariableKind::Multiline | VariableKind::File => {}
+            }
+            if let Some(default) = &variable.default {
+                description = format!("{} 默认值: {}", description, default);
+            }
+            json!({
+                "name": variable.name,
+                "description": description.trim(),
+                "required": variable.default.is_none(),
+            })
+        })
+        .collect()
+}
+
+fn list_prompts(store: &PromptStore) -> Result<Value, McpError> {
+    let conn = store.open()?;
+    let prompts = store::list_prompts(&conn, &PromptFilter::default())?;
+    let mut result = Vec::with_capacity(prompts.len());
+    for item in prompts {
+        result.push(json!({
+            "name": item.prompt.id,
+            "title": item.prompt.title,
+            "description": describe(&item.prompt.content, &item.tags),
+            "arguments": prompt_arguments(&conn, &item.prompt.content),
+        }));
+    }
+    Ok(json!({ "prompts": result }))
+}
+
+fn get_prompt(store: &PromptStore, params: &Value, on_change: &dyn Fn(&[String])) -> Result<Value, McpError> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| McpError::invalid_params("缺少提示词 name"))?;
+    let values: HashMap<String, String> = params
+        .get("arguments")
+        .and_then(Value::as_object)
+        .map(|arguments| {
+            arguments
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+    let selection_file = BuiltinKind::SelectionFile.name();
+    if values.contains_key(selection_file) {
+        return Err(McpError::invalid_params(format!("不支持通过参数提供 {}", selection_file)));
+    }
+
+    let conn = store.open()?;
+    let prompt = store::get_prompt(&conn, name)?;
+    if prompt.deleted_at.is_some() {
+        return Err(StoreError::PromptNotFound(name.to_string()).into());
+    }
+    // 剪贴板属于用户桌面环境，这里不读取，使用模板中的默认值
+    let text = template::render_stored_prompt_with(&conn, name, &values, || None)
+        .map_err(McpError::invalid_params)?;
+    usage::record_usage(&conn, name, UsageAction::Render, UsageSource::Mcp)?;
+    drop(conn);
+    on_change(&[name.to_string()]);
+
+    Ok(json!({
+        "description": prompt.title,
+        "messages": [
+            { "role": "user", "content": { "type": "text", "text": text } },
+        ],
+    }))
+}
+
+fn list_tools() -> Value {
+    json!({
+        "tools": [
+            {
+                "name": "search_prompts",
+                "title": "搜索提示词",
+                "description": "在 PromptGenie 提示词库中按标题、标签和内容模糊搜索",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string", "description": "搜索关键词" },
+                        "limit": { "type": "integer", "description": "最多返回的条数，默认 10", "minimum": 1 },
+                    },
+                    "required": ["query"],
+                },
+            },
+            {
+                "name": "save_prompt",
+                "title": "保存提示词",
+                "description": "把一条新的提示词保存到 PromptGenie，内容可以使用 {{变量}} 模板语法",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "title": { "type": "string", "description": "标题" },
+                        "content": { "type": "string", "description": "提示词内容" },
+                        "tags": { "type": "array", "items": { "type": "string" }, "description": "标签名" },
+                    },
+                    "required": ["title", "content"],
+                },
+            },
+        ],
+    })
+}
+
+/// 工具结果，同时提供文本和结构化内容
+fn tool_result(data: Value) -> Value {
+    json!({
+        "content": [
+            { "type": "text", "text": serde_json::to_string_pretty(&data).unwrap_or_default() },
+        ],
+        "structuredContent": data,
+        "isError": false,
+    })
+}
+
+/// 工具执行失败时返回给模型的结果，而不是协议错误
+fn tool_error(message: String) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": message }],
+        "isError": true,
+    })
+}
+
+fn call_tool(store: &PromptStore, params: &Value, on_change: &dyn Fn(&[String])) -> Result<Value, McpError> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| McpError::invalid_params("缺少工具 name"))?;
+    let arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
+    let text_argument = |key: &str| arguments.get(key).and_then(Value::as_str).map(str::trim);
+
+    match name {
+        "search_prompts" => {
+            let query = text_argument("query").unwrap_or_default();
+            let limit = arguments
+                .get("limit")
+                .and_then(Value::as_u64)
+                .unwrap_or(10) as usize;
+            let conn = store.open()?;
+            let items = palette::search(&conn, query, limit)?;
+            Ok(tool_result(json!({ "results": items })))
+        }
+        "save_prompt" => {
+            let title = text_argument("title").unwrap_or_default();
+            let content = arguments.get("content").and_then(Value::as_str).unwrap_or_default();
+            if title.is_empty() || content.trim().is_empty() {
+                return Ok(tool_error("标题和内容不能为空".to_string()));
+            }
+            let tags: Vec<String> = arguments
+                .get("tags")
+                .and_then(Value::as_array)
+                .map(|tags| {
+                    tags.iter()
+                        .filter_map(|tag| tag.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let conn = store.open()?;
+            let prompt = match store::create_prompt(&conn, title, content, &tags) {
+                Ok(prompt) => prompt,
+                Err(e) => return Ok(tool_error(format!("保存提示词失败: {}", e))),
+            };
+            drop(conn);
+            on_change(std::slice::from_ref(&prompt.id));
+            Ok(tool_result(json!({ "id": prompt.id, "title": prompt.title })))
+        }
+        _ => Err(McpError::invalid_params(format!("未知的工具: {}", name))),
+    }
+}
+
+/// stdio 模式（`--mcp`）：不启动窗口，直接读写数据库
+///
+/// 每行一条 JSON-RPC 消息，日志只能写到标准错误输出。可用 `--db` 指定数据库文件。
+pub fn run_stdio() -> i32 {
+    let args: Vec<String> = std::env::args().collect();
+    let db_path = match args.iter().position(|arg| arg == "--db") {
+        Some(index) => match args.get(index + 1) {
+            Some(path) => Ok(PathBuf::from(path)),
+            None => Err("--db 需要指定数据库文件".to_string()),
+        },
+        None => default_config_dir().map(|dir| dir.join("promptgenie.db")),
+    };
+    let store = match db_path.and_then(open_store) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    eprintln!("PromptGenie MCP 服务已启动（stdio）");
+
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("读取标准输入失败: {}", e);
+                return 1;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_line(&store, &line, &|_| {}) {
+            if writeln!(stdout, "{}", response).and_then(|_| stdout.flush()).is_err() {
+                return 1;
+            }
+        }
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::open_in_memory;
+
+    fn argument_names(arguments: &[Value]) -> Vec<&str> {
+        arguments
+            .iter()
+            .filter_map(|argument| argument["name"].as_str())
+            .collect()
+    }
+
+    #[test]
+    fn broken_includes_fall_back_to_unexpanded_content() {
+        let conn = open_in_memory();
+        conn.execute_batch(
+            "INSERT INTO snippets (id, name, content, description, created_at, updated_at)
+             VALUES ('s1', 'loop', '{{> loop}}', '', '', '')",
+        )
+        .unwrap();
+
+        let arguments = prompt_arguments(&conn, "{{> missing}} {{topic}}");
+        assert_eq!(argument_names(&arguments), vec!["topic"]);
+        let arguments = prompt_arguments(&conn, "{{> loop}} {{tone|formal}}");
+        assert_eq!(argument_names(&arguments), vec!["tone"]);
+        assert_eq!(arguments[0]["required"], json!(false));
+    }
+
+    #[test]
+    fn selection_file_is_not_an_argument() {
+        let conn = open_in_memory();
+        let arguments = prompt_arguments(&conn, "{{selection_file}} {{question}}");
+        assert_eq!(argument_names(&arguments), vec!["question"]);
+    }
+}
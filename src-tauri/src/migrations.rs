@@ -0,0 +1,476 @@
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime, State};
+use thiserror::Error;
+
+use crate::backup::{self, BackupError, BackupReason};
+use crate::integrity;
+use crate::store::{now_timestamp, PromptStore, StoreError};
+use crate::tray;
+
+#[derive(Debug, Error)]
+pub enum MigrationError {
+    #[error("数据库操作失败: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("{0}")]
+    Store(#[from] StoreError),
+    #[error("迁移前备份数据库失败: {0}")]
+    Backup(#[from] BackupError),
+    #[error("数据库版本 {current} 高于应用支持的版本 {latest}，请升级 PromptGenie")]
+    TooNew { current: i64, latest: i64 },
+    #[error("不存在的数据库版本: {0}")]
+    UnknownVersion(i64),
+    #[error("执行迁移 {version}（{description}）失败: {source}")]
+    Failed {
+        version: i64,
+        description: &'static str,
+        source: rusqlite::Error,
+    },
+}
+
+/// 迁移的一个方向，可以是 SQL 脚本，也可以是需要逐行处理数据的 Rust 函数
+pub enum Step {
+    Sql(&'static str),
+    Rust(fn(&Connection) -> rusqlite::Result<()>),
+}
+
+impl Step {
+    fn apply(&self, conn: &Connection) -> rusqlite::Result<()> {
+        match self {
+            Step::Sql(sql) => conn.execute_batch(sql),
+            Step::Rust(migrate) => migrate(conn),
+        }
+    }
+}
+
+/// 一次数据库结构变更，版本号连续递增
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    up: Step,
+    down: Step,
+}
+
+/// 引用 migrations 目录下成对的 `<编号>_<名称>.up.sql` / `.down.sql`
+macro_rules! sql_migration {
+    ($version:expr, $name:literal, $description:literal) => {
+        Migration {
+            version: $version,
+            description: $description,
+            up: Step::Sql(include_str!(concat!("../migrations/", $name, ".up.sql"))),
+            down: Step::Sql(include_str!(concat!("../migrations/", $name, ".down.sql"))),
+        }
+    };
+}
+
+/// 所有迁移，按版本号排列
+///
+/// 版本 1-6 原先由 tauri-plugin-sql 执行，编号和描述保持不变，
+/// 已安装的数据库会从 `_sqlx_migrations` 接管版本记录。
+const MIGRATIONS: &[Migration] = &[
+    sql_migration!(1, "0001_create_initial_tables", "create initial tables"),
+    sql_migration!(2, "0002_create_prompt_versions", "create prompt versions"),
+    sql_migration!(3, "0003_add_soft_delete", "add soft delete"),
+    sql_migration!(4, "0004_create_snippets", "create snippets"),
+    sql_migration!(5, "0005_add_prompt_use_count", "add prompt use count"),
+    sql_migration!(6, "0006_create_prompt_usage", "create prompt usage"),
+    Migration {
+        version: 7,
+        description: "add indexes and unique tag names",
+        up: Step::Rust(add_indexes),
+        down: Step::Sql(include_str!("../migrations/0007_add_indexes.down.sql")),
+    },
+];
+
+/// 先合并同名标签，否则无法在 tags.name 上建立唯一索引
+fn add_indexes(conn: &Connection) -> rusqlite::Result<()> {
+    let merged = integrity::merge_duplicate_tags(conn)?;
+    if merged > 0 {
+        println!("合并了 {} 个同名标签", merged);
+    }
+    conn.execute_batch(include_str!("../migrations/0007_add_indexes.up.sql"))
+}
+
+/// 迁移结果
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationReport {
+    pub from: i64,
+    pub to: i64,
+    /// 依次执行的迁移版本，回退时为被撤销的版本
+    pub applied: Vec<i64>,
+    /// 迁移前生成的备份文件名
+    pub backup: Option<String>,
+}
+
+/// 应用支持的最新数据库版本
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+/// 目标版本必须是某个迁移的版本，0 表示撤销全部迁移
+fn check_target(target: i64) -> Result<(), MigrationError> {
+    if target != 0
+        && !MIGRATIONS
+            .iter()
+            .any(|migration| migration.version == target)
+    {
+        return Err(MigrationError::UnknownVersion(target));
+    }
+    Ok(())
+}
+
+/// 是否存在版本表和 tauri-plugin-sql 的迁移记录表
+fn version_tables(conn: &Connection) -> rusqlite::Result<(bool, bool)> {
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_version'),
+                EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+}
+
+/// 创建版本表，旧版本安装的数据库从 tauri-plugin-sql 的迁移记录接管
+///
+/// 只在版本表不存在时接管一次，之后回退迁移不会再读取 `_sqlx_migrations`。
+fn ensure_version_table(conn: &Connection) -> rusqlite::Result<()> {
+    let (exists, has_sqlx) = version_tables(conn)?;
+    if exists {
+        return Ok(());
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(
+        "CREATE TABLE schema_version (
+           version INTEGER PRIMARY KEY,
+           description TEXT NOT NULL,
+           applied_at TEXT NOT NULL
+         )",
+    )?;
+    if has_sqlx {
+        tx.execute(
+            "INSERT INTO schema_version (version, description, applied_at)
+             SELECT version, description, installed_on FROM _sqlx_migrations WHERE success = 1",
+            [],
+        )?;
+    }
+    tx.commit()
+}
+
+/// 当前数据库版本，空数据库为 0
+pub fn current_version(conn: &Connection) -> rusqlite::Result<i64> {
+    ensure_version_table(conn)?;
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// 读取数据库版本但不创建版本表，可用于只读打开的数据库（例如备份）
+pub fn stored_version(conn: &Connection) -> rusqlite::Result<i64> {
+    let sql = match version_tables(conn)? {
+        (true, _) => "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        (false, true) => "SELECT COALESCE(MAX(version), 0) FROM _sqlx_migrations WHERE success = 1",
+        (false, false) => return Ok(0),
+    };
+    conn.query_row(sql, [], |row| row.get(0))
+}
+
+/// 在单独的事务中执行一个迁移，失败时整体回滚
+fn apply(conn: &mut Connection, migration: &Migration, up: bool) -> Result<(), MigrationError> {
+    let failed = |source| MigrationError::Failed {
+        version: migration.version,
+        description: migration.description,
+        source,
+    };
+    let tx = conn.transaction().map_err(failed)?;
+    let step = if up { &migration.up } else { &migration.down };
+    step.apply(&tx).map_err(failed)?;
+    if up {
+        tx.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, ?2, ?3)",
+            params![migration.version, migration.description, now_timestamp()],
+        )
+        .map_err(failed)?;
+    } else {
+        tx.execute(
+            "DELETE FROM schema_version WHERE version = ?1",
+            params![migration.version],
+        )
+        .map_err(failed)?;
+    }
+    tx.commit().map_err(failed)
+}
+
+/// 升级或回退到指定版本，返回执行过的迁移版本
+///
+/// 某个迁移失败时停在上一个版本，之前已完成的迁移不会撤销。
+pub fn migrate_to(conn: &mut Connection, target: i64) -> Result<Vec<i64>, MigrationError> {
+    check_target(target)?;
+    let current = current_version(conn)?;
+    let latest = latest_version();
+    if current > latest {
+        return Err(MigrationError::TooNew { current, latest });
+    }
+
+    let mut applied = Vec::new();
+    if target >= current {
+        for migration in MIGRATIONS
+            .iter()
+            .filter(|migration| migration.version > current && migration.version <= target)
+        {
+            apply(conn, migration, true)?;
+            applied.push(migration.version);
+        }
+    } else {
+        for migration in MIGRATIONS
+            .iter()
+            .rev()
+            .filter(|migration| migration.version <= current && migration.version > target)
+        {
+            apply(conn, migration, false)?;
+            applied.push(migration.version);
+        }
+    }
+    Ok(applied)
+}
+
+/// 迁移数据库到指定版本，已有数据时先备份
+pub fn migrate_store(store: &PromptStore, target: i64) -> Result<MigrationReport, MigrationError> {
+    let (mut conn, mut report) = prepare(store, target)?;
+    if report.from != target {
+        report.applied = migrate_to(&mut conn, target)?;
+        report.to = target;
+    }
+    Ok(report)
+}
+
+/// 检查版本，需要迁移且已有数据时先备份，返回尚未执行迁移的报告
+fn prepare(
+    store: &PromptStore,
+    target: i64,
+) -> Result<(Connection, MigrationReport), MigrationError> {
+    check_target(target)?;
+    let conn = store.open()?;
+    let current = current_version(&conn)?;
+    let latest = latest_version();
+    if current > latest {
+        return Err(MigrationError::TooNew { current, latest });
+    }
+    let mut report = MigrationReport {
+        from: current,
+        to: current,
+        applied: Vec::new(),
+        backup: None,
+    };
+
+    // 全新的数据库没有需要保护的数据
+    if current != target && current > 0 {
+        let dir = backup::backup_dir(store.db_path());
+        report.backup = backup::snapshot(store.db_path(), &dir, BackupReason::PreMigration)?
+            .map(|info| info.file_name);
+    }
+    Ok((conn, report))
+}
+
+/// 启动时的迁移结果，失败时前端据此提示用户
+#[derive(Default)]
+pub struct MigrationStatus(Mutex<Option<String>>);
+
+/// 启动时把数据库升级到最新版本
+///
+/// 失败时不中断启动，记录错误并通知用户，数据库保持在失败前的版本。
+pub fn run_on_startup<R: Runtime>(app_handle: &AppHandle<R>) {
+    let store = app_handle.state::<PromptStore>();
+    let latest = latest_version();
+    // 分两步执行，失败时才能知道升级前是否已生成备份
+    let error = match prepare(&store, latest) {
+        Err(e) => Some(format!("数据库升级失败: {}", e)),
+        Ok((mut conn, report)) => match migrate_to(&mut conn, latest) {
+            Ok(applied) => {
+                if !applied.is_empty() {
+                    println!("数据库已从版本 {} 升级到 {}", report.from, latest);
+                }
+                None
+            }
+            Err(e) => Some(match &report.backup {
+                Some(backup) => format!(
+                    "数据库升级失败: {}。升级前的数据已备份到 {}，可在设置中恢复",
+                    e, backup
+                ),
+                None => format!("数据库升级失败: {}", e),
+            }),
+        },
+    };
+    if let Some(message) = &error {
+        eprintln!("{}", message);
+        tray::show_notification(app_handle, message);
+    }
+    app_handle.manage(MigrationStatus(Mutex::new(error)));
+}
+
+/// 获取启动时数据库迁移的错误，没有错误时返回 None
+#[tauri::command]
+pub fn get_migration_error(status: State<'_, MigrationStatus>) -> Option<String> {
+    status.0.lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions(conn: &Connection) -> Vec<i64> {
+        let mut stmt = conn
+            .prepare("SELECT version FROM schema_version ORDER BY version")
+            .unwrap();
+        stmt.query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    fn has_table(conn: &Connection, name: &str) -> bool {
+        conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+            [name],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    fn has_column(conn: &Connection, table: &str, column: &str) -> bool {
+        conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2)",
+            [table, column],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    fn prompt_count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM prompts", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn migrates_up_and_down_keeping_data() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert_eq!(migrate_to(&mut conn, 1).unwrap(), vec![1]);
+        conn.execute_batch(
+            "INSERT INTO prompts (id, title, content, created_at, updated_at)
+             VALUES ('a', '周报', '总结本周工作', '2024-06-01T00:00:00.000Z', '2024-06-01T00:00:00.000Z');
+             INSERT INTO tags (id, name, color) VALUES ('t1', '写作', '#000'), ('t2', '写作', '#000');
+             INSERT INTO prompt_tags (prompt_id, tag_id) VALUES ('a', 't1'), ('a', 't2');",
+        )
+        .unwrap();
+
+        assert_eq!(
+            migrate_to(&mut conn, latest_version()).unwrap(),
+            vec![2, 3, 4, 5, 6, 7]
+        );
+        assert_eq!(versions(&conn), vec![1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(current_version(&conn).unwrap(), 7);
+        assert!(has_table(&conn, "snippets"));
+        assert!(has_table(&conn, "prompt_usage"));
+        assert!(has_column(&conn, "prompts", "deleted_at"));
+        assert!(has_column(&conn, "prompts", "use_count"));
+        assert_eq!(prompt_count(&conn), 1);
+        // 版本 7 建立唯一索引前合并了同名标签
+        let tags: i64 = conn
+            .query_row("SELECT COUNT(*) FROM tags", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tags, 1);
+
+        assert_eq!(migrate_to(&mut conn, 1).unwrap(), vec![7, 6, 5, 4, 3, 2]);
+        assert_eq!(versions(&conn), vec![1]);
+        assert!(!has_table(&conn, "snippets"));
+        assert!(!has_table(&conn, "prompt_usage"));
+        assert!(!has_table(&conn, "prompt_versions"));
+        assert!(!has_column(&conn, "prompts", "deleted_at"));
+        assert!(!has_column(&conn, "prompts", "use_count"));
+        assert_eq!(prompt_count(&conn), 1);
+
+        // 回退后可以再次升级
+        assert_eq!(migrate_to(&mut conn, 7).unwrap(), vec![2, 3, 4, 5, 6, 7]);
+        assert_eq!(migrate_to(&mut conn, 0).unwrap(), vec![7, 6, 5, 4, 3, 2, 1]);
+        assert!(versions(&conn).is_empty());
+        assert!(!has_table(&conn, "prompts"));
+    }
+
+    #[test]
+    fn rejects_unknown_and_newer_versions() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert!(matches!(
+            migrate_to(&mut conn, 99),
+            Err(MigrationError::UnknownVersion(99))
+        ));
+        migrate_to(&mut conn, 1).unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (99, 'future', '')",
+            [],
+        )
+        .unwrap();
+        assert!(matches!(
+            migrate_to(&mut conn, 7),
+            Err(MigrationError::TooNew {
+                current: 99,
+                latest: 7
+            })
+        ));
+    }
+
+    /// tauri-plugin-sql 执行到版本 5，版本 6 失败
+    fn sqlx_library() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE _sqlx_migrations (
+               version BIGINT PRIMARY KEY,
+               description TEXT NOT NULL,
+               installed_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+               success BOOLEAN NOT NULL,
+               checksum BLOB NOT NULL,
+               execution_time BIGINT NOT NULL
+             )",
+        )
+        .unwrap();
+        for migration in &MIGRATIONS[..5] {
+            migration.up.apply(&conn).unwrap();
+        }
+        for migration in &MIGRATIONS[..6] {
+            conn.execute(
+                "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+                 VALUES (?1, ?2, ?3, x'00', 0)",
+                params![migration.version, migration.description, migration.version <= 5],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    #[test]
+    fn takes_over_sqlx_migrations() {
+        let mut conn = sqlx_library();
+        assert_eq!(stored_version(&conn).unwrap(), 5);
+        assert!(!has_table(&conn, "schema_version"));
+
+        assert_eq!(current_version(&conn).unwrap(), 5);
+        assert_eq!(versions(&conn), vec![1, 2, 3, 4, 5]);
+        let description: String = conn
+            .query_row(
+                "SELECT description FROM schema_version WHERE version = 4",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(description, "create snippets");
+
+        assert_eq!(migrate_to(&mut conn, 7).unwrap(), vec![6, 7]);
+        assert_eq!(stored_version(&conn).unwrap(), 7);
+
+        // 接管只发生一次，回退后不会再读取 _sqlx_migrations
+        assert_eq!(migrate_to(&mut conn, 3).unwrap(), vec![7, 6, 5, 4]);
+        assert_eq!(current_version(&conn).unwrap(), 3);
+        assert_eq!(stored_version(&conn).unwrap(), 3);
+    }
+}
@@ -0,0 +1,233 @@
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::settings::load_app_settings;
+use crate::store::{PromptStore, StoreError};
+use crate::usage::UsageSource;
+use crate::{copy_prompt_to_clipboard, open_fill_window, prompt_has_variables};
+
+const PALETTE_LABEL: &str = "palette";
+
+/// 预览文本的最大字符数
+const PREVIEW_CHARS: usize = 120;
+
+/// 快捷搜索面板设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaletteSettings {
+    pub enabled: bool,
+    /// 全局快捷键，格式如 CommandOrControl+Shift+Space
+    pub shortcut: String,
+}
+
+impl Default for PaletteSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            shortcut: "CommandOrControl+Shift+Space".to_string(),
+        }
+    }
+}
+
+impl PaletteSettings {
+    /// 解析设置中的快捷键，关闭或未设置时返回 None
+    pub fn parse_shortcut(&self) -> Result<Option<Shortcut>, String> {
+        let shortcut = self.shortcut.trim();
+        if !self.enabled || shortcut.is_empty() {
+            return Ok(None);
+        }
+        shortcut
+            .parse()
+            .map(Some)
+            .map_err(|e| format!("快捷键 {} 格式无效: {}", self.shortcut, e))
+    }
+}
+
+/// 当前注册的面板快捷键，修改设置时注册新的之后再注销旧的
+#[derive(Default)]
+pub struct PaletteShortcut(Mutex<Option<Shortcut>>);
+
+/// 搜索结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PaletteItem {
+    pub id: String,
+    pub title: String,
+    pub preview: String,
+    pub tags: Vec<String>,
+    pub is_favorite: bool,
+    pub score: i64,
+}
+
+/// 模糊搜索提示词，标题匹配权重最高，其次是标签和内容
+///
+/// 查询为空时按最近使用排序。
+pub fn search(conn: &Connection, query: &str, limit: usize) -> Result<Vec<PaletteItem>, StoreError> {
+    let mut stmt = conn.prepare(
+        "SELECT p.id, p.title, p.content, p.is_favorite,
+                (SELECT GROUP_CONCAT(t.name, char(31)) FROM prompt_tags pt
+                 JOIN tags t ON t.id = pt.tag_id WHERE pt.prompt_id = p.id) AS tag_names
+         FROM prompts p
+         WHERE p.deleted_at IS NULL
+         ORDER BY p.last_used_at DESC",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<i64>>(3)?.unwrap_or(0) == 1,
+            row.get::<_, Option<String>>(4)?,
+        ))
+    })?;
+
+    let query = query.trim();
+    let matcher = SkimMatcherV2::default().ignore_case();
+    let mut items = Vec::new();
+
+    for row in rows {
+        let (id, title, content, is_favorite, tag_names) = row?;
+        let tags: Vec<String> = tag_names
+            .map(|names| names.split('\u{1f}').map(str::to_string).collect())
+            .unwrap_or_default();
+
+        let score = if query.is_empty() {
+            0
+        } else {
+            let title_score = matcher.fuzzy_match(&title, query).map(|s| s * 3);
+            let tag_score = tags
+                .iter()
+                .filter_map(|tag| matcher.fuzzy_match(tag, query))
+                .max()
+                .map(|s| s * 2);
+            let content_score = matcher.fuzzy_match(&content, query);
+            match [title_score, tag_score, content_score].into_iter().flatten().max() {
+                Some(score) => score,
+                None => continue,
+            }
+        };
+
+        let preview = content
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(PREVIEW_CHARS)
+            .collect();
+        items.push(PaletteItem {
+            id,
+            title,
+            preview,
+            tags,
+            is_favorite,
+            score,
+        });
+    }
+
+    // 稳定排序，分数相同时保持最近使用的顺序
+    items.sort_by_key(|item| std::cmp::Reverse(item.score));
+    items.truncate(limit);
+    Ok(items)
+}
+
+/// 显示或隐藏快捷搜索面板
+pub fn toggle_palette<R: Runtime>(app_handle: &AppHandle<R>) -> tauri::Result<()> {
+    if let Some(window) = app_handle.get_webview_window(PALETTE_LABEL) {
+        if window.is_visible()? {
+            window.hide()?;
+        } else {
+            // 通知面板清空上次的搜索内容
+            window.emit("palette-opened", ())?;
+            window.center()?;
+            window.show()?;
+            window.set_focus()?;
+        }
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(
+        app_handle,
+        PALETTE_LABEL,
+        WebviewUrl::App("index.html?view=palette".into()),
+    )
+    .title("PromptGenie")
+    .inner_size(640.0, 420.0)
+    .decorations(false)
+    .resizable(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .center()
+    .focused(true)
+    .build()?;
+    Ok(())
+}
+
+/// 按设置注册全局快捷键（启动时和保存设置后调用）
+///
+/// 新的快捷键注册成功后才注销旧的，快捷键无效或被占用时保留原来的快捷键。
+pub fn register_shortcut<R: Runtime>(app_handle: &AppHandle<R>) -> Result<(), String> {
+    let settings = load_app_settings(app_handle).palette;
+    let shortcut = settings.parse_shortcut()?;
+    let state = app_handle.state::<PaletteShortcut>();
+    let mut current = state.0.lock().map_err(|e| e.to_string())?;
+    if *current == shortcut {
+        return Ok(());
+    }
+    let global_shortcut = app_handle.global_shortcut();
+
+    if let Some(shortcut) = shortcut {
+        global_shortcut
+            .on_shortcut(shortcut, |app_handle, _shortcut, event| {
+                if event.state() == ShortcutState::Pressed {
+                    if let Err(e) = toggle_palette(app_handle) {
+                        eprintln!("打开快捷搜索面板失败: {}", e);
+                    }
+                }
+            })
+            .map_err(|e| format!("注册快捷键 {} 失败: {}", settings.shortcut, e))?;
+        println!("已注册快捷搜索快捷键: {}", settings.shortcut);
+    }
+    if let Some(previous) = std::mem::replace(&mut *current, shortcut) {
+        if let Err(e) = global_shortcut.unregister(previous) {
+            println!("注销快捷键失败: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// 在快捷搜索面板中搜索提示词
+#[tauri::command]
+pub async fn search_prompts(
+    store: State<'_, PromptStore>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<PaletteItem>, String> {
+    let conn = store.open().map_err(|e| e.to_string())?;
+    search(&conn, &query, limit.unwrap_or(20)).map_err(|e| format!("搜索提示词失败: {}", e))
+}
+
+/// 复制面板中选中的提示词并隐藏面板
+///
+/// 含有需要填写的变量时改为打开填写窗口，返回 false。
+#[tauri::command]
+pub async fn palette_copy_prompt<R: Runtime>(
+    app_handle: AppHandle<R>,
+    prompt_id: String,
+) -> Result<bool, String> {
+    if let Some(window) = app_handle.get_webview_window(PALETTE_LABEL) {
+        let _ = window.hide();
+    }
+
+    if prompt_has_variables(&app_handle, &prompt_id)? {
+        open_fill_window(&app_handle, &prompt_id, UsageSource::Palette)
+            .map_err(|e| format!("打开变量填写窗口失败: {}", e))?;
+        return Ok(false);
+    }
+
+    copy_prompt_to_clipboard(&app_handle, &prompt_id, UsageSource::Palette)?;
+    Ok(true)
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::api::{restart_server, ApiSettings};
+use crate::backup::{self, BackupSettings};
+use crate::folder_sync::{self, FolderSyncSettings};
+use crate::palette::{register_shortcut, PaletteSettings};
+use crate::trash::{empty_expired_with_settings, TrashSettings};
+use crate::tray::{refresh_tray_menu, TraySettings};
+use crate::versions::{prune_with_settings, VersionSettings};
+
+pub(crate) const SETTINGS_FILE: &str = "app_settings.json";
+
+/// 应用设置，保存在配置目录下的 app_settings.json
+///
+/// 每个功能一个分区，缺失的字段使用默认值，便于后续扩展。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub versions: VersionSettings,
+    pub trash: TrashSettings,
+    pub palette: PaletteSettings,
+    pub tray: TraySettings,
+    pub api: ApiSettings,
+    pub folder_sync: FolderSyncSettings,
+    pub backup: BackupSettings,
+}
+
+impl AppSettings {
+    /// 从文件读取设置，文件不存在时返回默认值
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content =
+            std::fs::read_to_string(path).map_err(|e| format!("读取设置文件失败: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("设置反序列化失败: {}", e))
+    }
+
+    /// 写入设置文件
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| format!("设置序列化失败: {}", e))?;
+        std::fs::write(path, content).map_err(|e| format!("写入设置文件失败: {}", e))
+    }
+}
+
+/// 获取设置文件路径
+pub(crate) fn settings_path<R: Runtime>(app_handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    let config_dir = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("获取配置目录失败: {}", e))?;
+    std::fs::create_dir_all(&config_dir).map_err(|e| format!("创建配置目录失败: {}", e))?;
+    Ok(config_dir.join(SETTINGS_FILE))
+}
+
+/// 读取应用设置，出错时打印日志并回退到默认值
+pub fn load_app_settings<R: Runtime>(app_handle: &AppHandle<R>) -> AppSettings {
+    match settings_path(app_handle).and_then(|path| AppSettings::load_from(&path)) {
+        Ok(settings) => settings,
+        Err(e) => {
+            println!("加载应用设置失败，使用默认设置: {}", e);
+            AppSettings::default()
+        }
+    }
+}
+
+/// 获取应用设置
+#[tauri::command]
+pub async fn get_app_settings<R: Runtime>(app_handle: AppHandle<R>) -> Result<AppSettings, String> {
+    let path = settings_path(&app_handle)?;
+    AppSettings::load_from(&path)
+}
+
+/// 保存应用设置并立即生效
+///
+/// 保存前先检查快捷键格式；之后各分区分别生效，某一项失败不影响其余各项，
+/// 所有错误合并后返回。
+#[tauri::command]
+pub async fn save_app_settings<R: Runtime>(
+    app_handle: AppHandle<R>,
+    settings: AppSettings,
+) -> Result<(), String> {
+    settings.palette.parse_shortcut()?;
+    let path = settings_path(&app_handle)?;
+    settings.save_to(&path)?;
+    println!("应用设置已保存");
+
+    prune_with_settings(&app_handle);
+    empty_expired_with_settings(&app_handle);
+    folder_sync::apply_settings(&app_handle);
+    backup::prune_with_settings(&app_handle);
+    let errors: Vec<String> = [
+        register_shortcut(&app_handle),
+        refresh_tray_menu(&app_handle),
+        restart_server(&app_handle),
+    ]
+    .into_iter()
+    .filter_map(Result::err)
+    .collect();
+    if !errors.is_empty() {
+        return Err(errors.join("；"));
+    }
+    Ok(())
+}
@@ -0,0 +1,337 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashSet;
+use tauri::State;
+use uuid::Uuid;
+
+use crate::store::{now_timestamp, PromptStore, StoreError};
+use crate::template::{self, TemplateError};
+
+/// 可复用片段，通过 {{> 名称}} 在提示词中引用
+#[derive(Debug, Clone, Serialize)]
+pub struct Snippet {
+    pub id: String,
+    pub name: String,
+    pub content: String,
+    pub description: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// 引用了某个片段的提示词
+#[derive(Debug, Clone, Serialize)]
+pub struct SnippetBacklink {
+    pub prompt_id: String,
+    pub title: String,
+    /// 是否直接引用；为 false 时表示通过其他片段间接引用
+    pub direct: bool,
+}
+
+fn snippet_from_row(row: &rusqlite::Row) -> rusqlite::Result<Snippet> {
+    Ok(Snippet {
+        id: row.get("id")?,
+        name: row.get("name")?,
+        content: row.get("content")?,
+        description: row.get("description")?,
+        created_at: row.get("created_at")?,
+        updated_at: row.get("updated_at")?,
+    })
+}
+
+/// 获取所有片段
+pub fn list(conn: &Connection) -> Result<Vec<Snippet>, StoreError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, content, description, created_at, updated_at
+         FROM snippets ORDER BY name ASC",
+    )?;
+    let snippets = stmt
+        .query_map([], snippet_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(snippets)
+}
+
+/// 解析引用名称：先查找片段，再按 ID 或标题查找未删除的提示词
+pub fn resolve(conn: &Connection, name: &str) -> Result<Option<String>, StoreError> {
+    let snippet: Option<String> = conn
+        .query_row(
+            "SELECT content FROM snippets WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if snippet.is_some() {
+        return Ok(snippet);
+    }
+
+    let prompt = conn
+        .query_row(
+            "SELECT content FROM prompts
+             WHERE (id = ?1 OR title = ?1) AND deleted_at IS NULL
+             ORDER BY id = ?1 DESC LIMIT 1",
+            params![name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(prompt)
+}
+
+/// 使用数据库中的片段和提示词展开模板引用
+pub fn expand(conn: &Connection, content: &str) -> Result<String, TemplateError> {
+    template::expand_includes(content, &|name: &str| resolve(conn, name))
+}
+
+/// 查找引用了指定片段的提示词，包括通过其他片段间接引用的
+pub fn backlinks(conn: &Connection, name: &str) -> Result<Vec<SnippetBacklink>, StoreError> {
+    // 先找出所有（直接或间接）引用了目标的片段名称
+    let snippets = list(conn)?;
+    let mut referencing: HashSet<String> = HashSet::new();
+    referencing.insert(name.to_string());
+    loop {
+        let before = referencing.len();
+        for snippet in &snippets {
+            if template::includes(&snippet.content)
+                .iter()
+                .any(|included| referencing.contains(included))
+            {
+                referencing.insert(snippet.name.clone());
+            }
+        }
+        if referencing.len() == before {
+            break;
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, title, content FROM prompts WHERE deleted_at IS NULL ORDER BY title ASC",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        let (prompt_id, title, content) = row?;
+        let included = template::includes(&content);
+        if included.iter().any(|n| n == name) {
+            result.push(SnippetBacklink {
+                prompt_id,
+                title,
+                direct: true,
+            });
+        } else if included.iter().any(|n| referencing.contains(n)) {
+            result.push(SnippetBacklink {
+                prompt_id,
+                title,
+                direct: false,
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// 获取所有片段
+#[tauri::command]
+pub async fn list_snippets(store: State<'_, PromptStore>) -> Result<Vec<Snippet>, String> {
+    let conn = store.open().map_err(|e| e.to_string())?;
+    list(&conn).map_err(|e| format!("获取片段失败: {}", e))
+}
+
+/// 仍有提示词引用片段时返回错误，列出这些提示词
+fn ensure_unreferenced(conn: &Connection, name: &str, action: &str) -> Result<(), String> {
+    let linked = backlinks(conn, name).map_err(|e| format!("查询片段引用失败: {}", e))?;
+    if linked.is_empty() {
+        return Ok(());
+    }
+    let titles = linked
+        .iter()
+        .map(|link| link.title.as_str())
+        .collect::<Vec<_>>()
+        .join("、");
+    Err(format!(
+        "片段「{}」仍被 {} 个提示词引用：{}，{}后这些提示词将无法渲染",
+        name,
+        linked.len(),
+        titles,
+        action
+    ))
+}
+
+/// 创建或更新片段
+///
+/// 形成循环引用时拒绝保存；改名时如果仍有提示词使用旧名称引用，除非指定 force 否则拒绝。
+pub fn save(
+    conn: &Connection,
+    id: Option<&str>,
+    name: &str,
+    content: &str,
+    description: &str,
+    force: bool,
+) -> Result<Snippet, String> {
+    let name = name.trim();
+    if !template::is_valid_name(name) {
+        return Err("片段名称只能包含字母、数字、下划线、连字符和点".to_string());
+    }
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let now = now_timestamp();
+
+    let id = match id {
+        Some(id) => {
+            let old_name: Option<String> = tx
+                .query_row("SELECT name FROM snippets WHERE id = ?1", params![id], |row| {
+                    row.get(0)
+                })
+                .optional()
+                .map_err(|e| format!("读取片段失败: {}", e))?;
+            let Some(old_name) = old_name else {
+                return Err("片段不存在".to_string());
+            };
+            if old_name != name && !force {
+                ensure_unreferenced(&tx, &old_name, "改名")?;
+            }
+            let updated = tx
+                .execute(
+                    "UPDATE snippets SET name = ?1, content = ?2, description = ?3, updated_at = ?4
+                     WHERE id = ?5",
+                    params![name, content, description, now, id],
+                )
+                .map_err(|e| format!("更新片段失败: {}", e))?;
+            if updated == 0 {
+                return Err("片段不存在".to_string());
+            }
+            id.to_string()
+        }
+        None => {
+            let id = Uuid::now_v7().to_string();
+            tx.execute(
+                "INSERT INTO snippets (id, name, content, description, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+                params![id, name, content, description, now],
+            )
+            .map_err(|e| format!("创建片段失败: {}", e))?;
+            id
+        }
+    };
+
+    // 形成循环引用时拒绝保存；引用尚不存在的片段是允许的
+    if let Err(e @ TemplateError::IncludeCycle(_)) =
+        expand(&tx, &format!("{{{{> {}}}}}", name))
+    {
+        return Err(format!("保存片段失败: {}", e));
+    }
+
+    let snippet = tx
+        .query_row(
+            "SELECT id, name, content, description, created_at, updated_at
+             FROM snippets WHERE id = ?1",
+            params![id],
+            snippet_from_row,
+        )
+        .map_err(|e| format!("读取片段失败: {}", e))?;
+    tx.commit().map_err(|e| format!("保存片段失败: {}", e))?;
+    Ok(snippet)
+}
+
+/// 创建或更新片段
+#[tauri::command]
+pub async fn save_snippet(
+    store: State<'_, PromptStore>,
+    id: Option<String>,
+    name: String,
+    content: String,
+    description: Option<String>,
+    force: Option<bool>,
+) -> Result<Snippet, String> {
+    let conn = store.open().map_err(|e| e.to_string())?;
+    save(
+        &conn,
+        id.as_deref(),
+        &name,
+        &content,
+        &description.unwrap_or_default(),
+        force.unwrap_or(false),
+    )
+}
+
+/// 删除片段；仍有提示词引用时拒绝删除，除非指定 force
+#[tauri::command]
+pub async fn delete_snippet(
+    store: State<'_, PromptStore>,
+    id: String,
+    force: Option<bool>,
+) -> Result<(), String> {
+    let conn = store.open().map_err(|e| e.to_string())?;
+    let name: Option<String> = conn
+        .query_row("SELECT name FROM snippets WHERE id = ?1", params![id], |row| {
+            row.get(0)
+        })
+        .optional()
+        .map_err(|e| format!("读取片段失败: {}", e))?;
+    let Some(name) = name else {
+        return Ok(());
+    };
+    if !force.unwrap_or(false) {
+        ensure_unreferenced(&conn, &name, "删除")?;
+    }
+
+    conn.execute("DELETE FROM snippets WHERE id = ?1", params![id])
+        .map_err(|e| format!("删除片段失败: {}", e))?;
+    Ok(())
+}
+
+/// 查询哪些提示词使用了指定片段
+#[tauri::command]
+pub async fn get_snippet_backlinks(
+    store: State<'_, PromptStore>,
+    name: String,
+) -> Result<Vec<SnippetBacklink>, String> {
+    let conn = store.open().map_err(|e| e.to_string())?;
+    backlinks(&conn, &name).map_err(|e| format!("查询片段引用失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::open_in_memory;
+
+    #[test]
+    fn rejects_include_cycles() {
+        let conn = open_in_memory();
+        save(&conn, None, "a", "A {{> b}}", "", false).unwrap();
+        let b = save(&conn, None, "b", "B", "", false).unwrap();
+
+        let result = save(&conn, Some(&b.id), "b", "B {{> a}}", "", false);
+        assert!(result.unwrap_err().contains("循环"));
+        // 拒绝后保持原内容
+        assert_eq!(expand(&conn, "{{> a}}").unwrap(), "A B");
+
+        let result = save(&conn, None, "c", "{{> c}}", "", false);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn renaming_referenced_snippet_requires_force() {
+        let conn = open_in_memory();
+        let header = save(&conn, None, "header", "你是助理", "", false).unwrap();
+        crate::store::create_prompt(&conn, "周报", "{{> header}} 写周报", &[]).unwrap();
+
+        let error = save(&conn, Some(&header.id), "intro", "你是助理", "", false).unwrap_err();
+        assert!(error.contains("周报"), "{}", error);
+        // 只修改内容不受影响
+        save(&conn, Some(&header.id), "header", "你是写作助理", "", false).unwrap();
+
+        let renamed = save(&conn, Some(&header.id), "intro", "你是写作助理", "", true).unwrap();
+        assert_eq!(renamed.name, "intro");
+    }
+
+    #[test]
+    fn updating_unknown_snippet_fails() {
+        let conn = open_in_memory();
+        let error = save(&conn, Some("missing"), "header", "", "", false).unwrap_err();
+        assert_eq!(error, "片段不存在");
+    }
+}
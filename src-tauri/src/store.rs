@@ -0,0 +1,243 @@
+use chrono::{SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error)]
+pub enum StoreError {
+    #[error("数据库操作失败: {0}")]
+    SqliteError(#[from] rusqlite::Error),
+    #[error("提示词不存在: {0}")]
+    PromptNotFound(String),
+    #[error("提示词 {prompt_id} 的版本 {version} 不存在")]
+    VersionNotFound { prompt_id: String, version: i64 },
+}
+
+/// Rust 端的提示词存储
+///
+/// 与前端的 tauri-plugin-sql 共用同一个 promptgenie.db 文件，
+/// 每次操作单独打开连接，避免长时间持有锁。
+pub struct PromptStore {
+    db_path: PathBuf,
+}
+
+/// 数据库中的提示词行
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredPrompt {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    pub is_favorite: bool,
+    pub created_at: String,
+    pub updated_at: String,
+    pub last_used_at: Option<String>,
+    pub deleted_at: Option<String>,
+}
+
+impl PromptStore {
+    pub fn new(db_path: PathBuf) -> Self {
+        Self { db_path }
+    }
+
+    pub fn db_path(&self) -> &Path {
+        &self.db_path
+    }
+
+    /// 打开一个新的数据库连接
+    ///
+    /// 每个连接都开启外键约束，schema 中的 ON DELETE CASCADE 才会生效；
+    /// WAL 模式记录在数据库文件中，命令行工具与应用同时读写时读操作不会被写锁阻塞。
+    pub fn open(&self) -> Result<Connection, StoreError> {
+        let conn = Connection::open(&self.db_path)?;
+        // 前端可能同时在写入，等待锁释放而不是立即失败
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+        Ok(conn)
+    }
+}
+
+/// 测试用的内存数据库，已执行全部迁移
+#[cfg(test)]
+pub(crate) fn open_in_memory() -> Connection {
+    let mut conn = Connection::open_in_memory().unwrap();
+    conn.pragma_update(None, "foreign_keys", true).unwrap();
+    crate::migrations::migrate_to(&mut conn, crate::migrations::latest_version()).unwrap();
+    conn
+}
+
+/// 当前时间，格式与前端 `new Date().toISOString()` 保持一致
+pub fn now_timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// 读取 StoredPrompt 所需的列
+pub(crate) const PROMPT_COLUMNS: &str =
+    "id, title, content, is_favorite, created_at, updated_at, last_used_at, deleted_at";
+
+pub(crate) fn prompt_from_row(row: &rusqlite::Row) -> rusqlite::Result<StoredPrompt> {
+    Ok(StoredPrompt {
+        id: row.get("id")?,
+        title: row.get("title")?,
+        content: row.get("content")?,
+        is_favorite: row.get::<_, Option<i64>>("is_favorite")?.unwrap_or(0) == 1,
+        created_at: row.get("created_at")?,
+        updated_at: row.get("updated_at")?,
+        last_used_at: row.get("last_used_at")?,
+        deleted_at: row.get("deleted_at")?,
+    })
+}
+
+/// 带标签的提示词
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptWithTags {
+    #[serde(flatten)]
+    pub prompt: StoredPrompt,
+    pub tags: Vec<String>,
+}
+
+/// 提示词列表的筛选条件
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PromptFilter {
+    /// 只列出带有该标签的提示词
+    pub tag: Option<String>,
+    /// 只列出收藏的提示词
+    pub favorites: bool,
+    /// 列出回收站中的提示词
+    pub trash: bool,
+    pub limit: Option<usize>,
+}
+
+/// 按条件列出提示词，最近修改的在前
+pub fn list_prompts(conn: &Connection, filter: &PromptFilter) -> Result<Vec<PromptWithTags>, StoreError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM prompts p
+         WHERE (p.deleted_at IS NOT NULL) = ?1
+           AND (?2 = 0 OR p.is_favorite = 1)
+           AND (?3 IS NULL OR EXISTS (
+                SELECT 1 FROM prompt_tags pt JOIN tags t ON t.id = pt.tag_id
+                WHERE pt.prompt_id = p.id AND t.name = ?3))
+         ORDER BY p.updated_at DESC LIMIT ?4",
+        PROMPT_COLUMNS
+    ))?;
+    let limit = filter.limit.map(|limit| limit as i64).unwrap_or(-1);
+    let prompts = stmt
+        .query_map(
+            params![filter.trash, filter.favorites, filter.tag, limit],
+            prompt_from_row,
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    prompts
+        .into_iter()
+        .map(|prompt| {
+            let tags = prompt_tag_names(conn, &prompt.id)?;
+            Ok(PromptWithTags { prompt, tags })
+        })
+        .collect()
+}
+
+/// 按 ID 获取提示词
+pub fn get_prompt(conn: &Connection, id: &str) -> Result<StoredPrompt, StoreError> {
+    conn.query_row(
+        &format!("SELECT {} FROM prompts WHERE id = ?1", PROMPT_COLUMNS),
+        params![id],
+        prompt_from_row,
+    )
+    .optional()?
+    .ok_or_else(|| StoreError::PromptNotFound(id.to_string()))
+}
+
+/// 更新提示词的标题和内容，同时刷新 updated_at
+pub fn update_prompt_content(
+    conn: &Connection,
+    id: &str,
+    title: &str,
+    content: &str,
+) -> Result<(), StoreError> {
+    let affected = conn.execute(
+        "UPDATE prompts SET title = ?1, content = ?2, updated_at = ?3 WHERE id = ?4",
+        params![title, content, now_timestamp(), id],
+    )?;
+    if affected == 0 {
+        return Err(StoreError::PromptNotFound(id.to_string()));
+    }
+    Ok(())
+}
+
+/// 新建标签的默认颜色，与前端同步时创建标签保持一致
+pub const DEFAULT_TAG_COLOR: &str = "#6366f1";
+
+/// 按名称查找标签，不存在时创建，返回标签 ID
+pub fn ensure_tag(conn: &Connection, name: &str) -> Result<String, StoreError> {
+    let existing: Option<String> = conn
+        .query_row("SELECT id FROM tags WHERE name = ?1", params![name], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let id = Uuid::now_v7().to_string();
+    conn.execute(
+        "INSERT INTO tags (id, name, color) VALUES (?1, ?2, ?3)",
+        params![id, name, DEFAULT_TAG_COLOR],
+    )?;
+    Ok(id)
+}
+
+/// 获取提示词的标签名
+pub fn prompt_tag_names(conn: &Connection, prompt_id: &str) -> Result<Vec<String>, StoreError> {
+    let mut stmt = conn.prepare(
+        "SELECT t.name FROM tags t JOIN prompt_tags pt ON t.id = pt.tag_id
+         WHERE pt.prompt_id = ?1 ORDER BY t.name",
+    )?;
+    let names = stmt
+        .query_map(params![prompt_id], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(names)
+}
+
+/// 用给定的标签名替换提示词的全部标签，缺少的标签会自动创建
+pub fn set_prompt_tags(conn: &Connection, prompt_id: &str, names: &[String]) -> Result<(), StoreError> {
+    conn.execute("DELETE FROM prompt_tags WHERE prompt_id = ?1", params![prompt_id])?;
+    for name in names {
+        let name = name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        let tag_id = ensure_tag(conn, name)?;
+        conn.execute(
+            "INSERT OR IGNORE INTO prompt_tags (prompt_id, tag_id) VALUES (?1, ?2)",
+            params![prompt_id, tag_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// 新建提示词，第一个版本由触发器写入
+pub fn create_prompt(
+    conn: &Connection,
+    title: &str,
+    content: &str,
+    tags: &[String],
+) -> Result<StoredPrompt, StoreError> {
+    let id = Uuid::now_v7().to_string();
+    let now = now_timestamp();
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT INTO prompts (id, title, content, is_favorite, created_at, updated_at, last_used_at)
+         VALUES (?1, ?2, ?3, 0, ?4, ?4, ?4)",
+        params![id, title, content, now],
+    )?;
+    set_prompt_tags(&tx, &id, tags)?;
+    tx.commit()?;
+
+    get_prompt(conn, &id)
+}
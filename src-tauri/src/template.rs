@@ -0,0 +1,663 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use thiserror::Error;
+
+use crate::builtins::{self, BuiltinContext, BuiltinKind, BuiltinVariable};
+use crate::snippets;
+use crate::store::{self, PromptStore};
+
+// 模板变量语法：{{名称[:类型][|默认值][#说明]}}
+//
+//   {{language}}                       普通文本
+//   {{tone|formal}}                    带默认值
+//   {{code:multiline#需要审查的代码}}    多行文本并附带说明
+//   {{tone:choice(formal,casual)|formal}} 单选
+//
+// 默认值中的 # 只有前后都有内容时才开始说明，因此 {{color|#ff0000}}、{{tag|C#}} 按原样作为默认值；
+// 默认值中间的 # 需要写成 \# 才不会被当作说明，例如 {{issue|bug\#123}}。
+//
+// {{> 名称}} 引用片段或其他提示词，渲染前递归展开。
+// date、time、clipboard、selection_file、uuid 为内置变量，见 builtins 模块。
+// 以反斜杠开头的 \{{ 会原样输出 {{，不作为变量解析。
+
+/// 引用的最大嵌套深度
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+#[derive(Debug, Error)]
+pub enum TemplateError {
+    #[error("缺少变量的值: {}", .0.join(", "))]
+    MissingValues(Vec<String>),
+    #[error("变量 {name} 的值 \"{value}\" 不在可选项中")]
+    InvalidChoice { name: String, value: String },
+    #[error("找不到引用的片段或提示词: {0}")]
+    IncludeNotFound(String),
+    #[error("引用形成循环: {}", .0.join(" -> "))]
+    IncludeCycle(Vec<String>),
+    #[error("引用嵌套超过 {0} 层")]
+    IncludeTooDeep(usize),
+    #[error("读取引用 {name} 失败: {reason}")]
+    IncludeFailed { name: String, reason: String },
+    #[error("内置变量 {0} 当前没有可用的值")]
+    BuiltinUnavailable(String),
+    #[error("内置变量 {name} 的格式 \"{format}\" 无效")]
+    InvalidFormat { name: String, format: String },
+    #[error("读取文件 {path} 失败: {reason}")]
+    ReadFileFailed { path: String, reason: String },
+}
+
+/// 变量类型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "options", rename_all = "lowercase")]
+pub enum VariableKind {
+    Text,
+    Multiline,
+    Choice(Vec<String>),
+    /// 内置变量 selection_file，值为拖入文件的路径
+    File,
+}
+
+/// 最近一次拖入填写窗口的文件路径，是 selection_file 唯一的取值来源
+///
+/// 由窗口的拖放事件在 Rust 端记录，前端提交的变量值无法指定要读取的文件。
+#[derive(Default)]
+pub struct DroppedFile(Mutex<Option<String>>);
+
+/// 记录拖入填写窗口的文件，多个文件时取第一个
+pub fn record_dropped_file<R: Runtime>(app_handle: &AppHandle<R>, paths: &[PathBuf]) {
+    if let Some(path) = paths.first() {
+        *app_handle.state::<DroppedFile>().0.lock().unwrap() =
+            Some(path.to_string_lossy().into_owned());
+    }
+}
+
+/// 填写窗口切换到新的提示词时清除之前拖入的文件
+pub fn clear_dropped_file<R: Runtime>(app_handle: &AppHandle<R>) {
+    *app_handle.state::<DroppedFile>().0.lock().unwrap() = None;
+}
+
+/// 模板中声明的变量
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateVariable {
+    pub name: String,
+    pub kind: VariableKind,
+    pub default: Option<String>,
+    pub description: Option<String>,
+}
+
+/// 模板解析结果中的片段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Text(String),
+    Variable(TemplateVariable),
+    Include(String),
+    Builtin(BuiltinVariable),
+}
+
+/// 解析模板内容
+pub fn parse(content: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find("{{") {
+        // 转义：\{{ 输出为 {{
+        if rest[..start].ends_with('\\') {
+            text.push_str(&rest[..start - 1]);
+            text.push_str("{{");
+            rest = &rest[start + 2..];
+            continue;
+        }
+
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("}}") else {
+            break;
+        };
+
+        match parse_tag(&after_open[..end]) {
+            Some(segment) => {
+                text.push_str(&rest[..start]);
+                if !text.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(segment);
+            }
+            None => {
+                // 不是合法的变量声明，按普通文本保留
+                text.push_str(&rest[..start + 2 + end + 2]);
+            }
+        }
+        rest = &after_open[end + 2..];
+    }
+
+    text.push_str(rest);
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    segments
+}
+
+/// 解析 {{ }} 之间的内容，不合法时返回 None
+fn parse_tag(inner: &str) -> Option<Segment> {
+    match inner.trim_start().strip_prefix('>') {
+        Some(name) => {
+            let name = name.trim();
+            (!name.is_empty()).then(|| Segment::Include(name.to_string()))
+        }
+        None => parse_variable(inner),
+    }
+}
+
+/// 解析变量声明
+///
+/// 先按 | 分出默认值，再分别在名称和默认值部分中查找说明，避免默认值中的 # 被当作说明。
+fn parse_variable(inner: &str) -> Option<Segment> {
+    let (spec, rest) = match inner.split_once('|') {
+        Some((spec, rest)) => (spec, Some(rest)),
+        None => (inner, None),
+    };
+    let (spec, mut description) = match spec.split_once('#') {
+        Some((spec, desc)) => (spec, non_empty(desc)),
+        None => (spec, None),
+    };
+    let default = rest.map(|rest| {
+        let (default, desc) = split_default(rest);
+        if description.is_none() {
+            description = desc;
+        }
+        default
+    });
+    let (name, kind) = match spec.split_once(':') {
+        Some((name, kind)) => (name.trim(), Some(kind)),
+        None => (spec.trim(), None),
+    };
+
+    // 内置变量冒号后面是格式参数
+    if let Some(builtin) = BuiltinKind::from_name(name) {
+        return Some(Segment::Builtin(BuiltinVariable {
+            kind: builtin,
+            format: kind.map(|f| f.trim().to_string()),
+            default,
+        }));
+    }
+
+    let kind = match kind {
+        Some(kind) => parse_kind(kind.trim())?,
+        None => VariableKind::Text,
+    };
+    if !is_valid_name(name) {
+        return None;
+    }
+
+    Some(Segment::Variable(TemplateVariable {
+        name: name.to_string(),
+        kind,
+        default,
+        description,
+    }))
+}
+
+/// 拆分默认值和说明，\# 表示字面的 #
+///
+/// 未转义的 # 只有前面的默认值和后面的说明都不为空时才作为分隔符。
+fn split_default(text: &str) -> (String, Option<String>) {
+    let mut default = String::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' if chars.peek().is_some_and(|(_, next)| *next == '#') => {
+                default.push('#');
+                chars.next();
+            }
+            '#' if !default.trim().is_empty() && !text[index + 1..].trim().is_empty() => {
+                return (default.trim().to_string(), non_empty(&text[index + 1..]));
+            }
+            _ => default.push(c),
+        }
+    }
+    (default.trim().to_string(), None)
+}
+
+/// 转义默认值中的 #，生成的声明重新解析后得到同样的默认值
+pub(crate) fn escape_default(default: &str) -> String {
+    default.replace('#', "\\#")
+}
+
+fn parse_kind(kind: &str) -> Option<VariableKind> {
+    match kind {
+        "" | "text" => Some(VariableKind::Text),
+        "multiline" => Some(VariableKind::Multiline),
+        _ => {
+            let options = kind.strip_prefix("choice(")?.strip_suffix(')')?;
+            let options: Vec<String> = options
+                .split(',')
+                .map(|o| o.trim().to_string())
+                .filter(|o| !o.is_empty())
+                .collect();
+            if options.is_empty() {
+                return None;
+            }
+            Some(VariableKind::Choice(options))
+        }
+    }
+}
+
+pub(crate) fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    let s = s.trim();
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+/// 提取模板中需要填写的变量，同名变量只保留一个，后出现的声明用于补全缺失的信息
+///
+/// 内置变量中只有 selection_file 需要用户提供文件，其余自动填充，不在此列出。
+pub fn variables(content: &str) -> Vec<TemplateVariable> {
+    let mut result: Vec<TemplateVariable> = Vec::new();
+    for segment in parse(content) {
+        let variable = match segment {
+            Segment::Variable(variable) => variable,
+            Segment::Builtin(BuiltinVariable {
+                kind: BuiltinKind::SelectionFile,
+                default,
+                ..
+            }) => TemplateVariable {
+                name: BuiltinKind::SelectionFile.name().to_string(),
+                kind: VariableKind::File,
+                default,
+                description: Some("拖入一个文本文件".to_string()),
+            },
+            _ => continue,
+        };
+        match result.iter_mut().find(|v| v.name == variable.name) {
+            Some(existing) => {
+                if existing.kind == VariableKind::Text {
+                    existing.kind = variable.kind;
+                }
+                if existing.default.is_none() {
+                    existing.default = variable.default;
+                }
+                if existing.description.is_none() {
+                    existing.description = variable.description;
+                }
+            }
+            None => result.push(variable),
+        }
+    }
+    result
+}
+
+/// 使用给定的值渲染模板，未提供的值使用默认值，内置变量从 `context` 中取值
+///
+/// selection_file 只能由 `context` 提供，`values` 中的同名键不会被当作文件路径。
+pub fn render(
+    content: &str,
+    values: &HashMap<String, String>,
+    context: &BuiltinContext,
+) -> Result<String, TemplateError> {
+    let declared = variables(content);
+    let mut resolved: HashMap<&str, &str> = HashMap::new();
+    let mut missing = Vec::new();
+
+    // selection_file 会读取本地文件，只从 context 取值，values 中的同名键一律忽略
+    for variable in &declared {
+        if variable.kind == VariableKind::File {
+            continue;
+        }
+        let value = values
+            .get(&variable.name)
+            .map(String::as_str)
+            .or(variable.default.as_deref());
+        match value {
+            Some(value) => {
+                if let VariableKind::Choice(options) = &variable.kind {
+                    if !options.iter().any(|o| o == value) {
+                        return Err(TemplateError::InvalidChoice {
+                            name: variable.name.clone(),
+                            value: value.to_string(),
+                        });
+                    }
+                }
+                resolved.insert(&variable.name, value);
+            }
+            None => missing.push(variable.name.clone()),
+        }
+    }
+
+    if !missing.is_empty() {
+        return Err(TemplateError::MissingValues(missing));
+    }
+
+    let mut output = String::with_capacity(content.len());
+    for segment in parse(content) {
+        match segment {
+            Segment::Text(text) => output.push_str(&text),
+            Segment::Variable(variable) => output.push_str(resolved[variable.name.as_str()]),
+            Segment::Builtin(builtin) => output.push_str(&builtins::evaluate(&builtin, context)?),
+            // 渲染前应已调用 expand_includes
+            Segment::Include(name) => return Err(TemplateError::IncludeNotFound(name)),
+        }
+    }
+    Ok(output)
+}
+
+/// 模板是否使用了指定的内置变量
+pub fn uses_builtin(content: &str, kind: BuiltinKind) -> bool {
+    parse(content)
+        .iter()
+        .any(|segment| matches!(segment, Segment::Builtin(builtin) if builtin.kind == kind))
+}
+
+/// 提取模板直接引用的片段或提示词名称
+pub fn includes(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for segment in parse(content) {
+        if let Segment::Include(name) = segment {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+/// 递归展开 {{> 名称}} 引用，返回仍可继续解析变量的模板源码
+///
+/// `resolve` 根据名称返回被引用的内容，找不到时返回 `Ok(None)`。
+pub fn expand_includes<F, E>(content: &str, resolve: &F) -> Result<String, TemplateError>
+where
+    F: Fn(&str) -> Result<Option<String>, E>,
+    E: std::fmt::Display,
+{
+    let mut stack = Vec::new();
+    expand_recursive(content, resolve, &mut stack)
+}
+
+fn expand_recursive<F, E>(
+    content: &str,
+    resolve: &F,
+    stack: &mut Vec<String>,
+) -> Result<String, TemplateError>
+where
+    F: Fn(&str) -> Result<Option<String>, E>,
+    E: std::fmt::Display,
+{
+    if stack.len() > MAX_INCLUDE_DEPTH {
+        return Err(TemplateError::IncludeTooDeep(MAX_INCLUDE_DEPTH));
+    }
+
+    let mut output = String::with_capacity(content.len());
+    for segment in parse(content) {
+        match segment {
+            // 文本中的 {{ 只可能来自转义或非法声明，重新转义以保持原样
+            Segment::Text(text) => output.push_str(&text.replace("{{", "\\{{")),
+            Segment::Variable(variable) => output.push_str(&variable_source(&variable)),
+            Segment::Builtin(builtin) => output.push_str(&builtin_source(&builtin)),
+            Segment::Include(name) => {
+                if stack.contains(&name) {
+                    let mut cycle = stack.clone();
+                    cycle.push(name);
+                    return Err(TemplateError::IncludeCycle(cycle));
+                }
+                let included = resolve(&name)
+                    .map_err(|e| TemplateError::IncludeFailed {
+                        name: name.clone(),
+                        reason: e.to_string(),
+                    })?
+                    .ok_or_else(|| TemplateError::IncludeNotFound(name.clone()))?;
+
+                stack.push(name);
+                output.push_str(&expand_recursive(&included, resolve, stack)?);
+                stack.pop();
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// 将变量声明还原为模板源码
+fn variable_source(variable: &TemplateVariable) -> String {
+    let mut source = format!("{{{{{}", variable.name);
+    match &variable.kind {
+        VariableKind::Text => {}
+        VariableKind::Multiline => source.push_str(":multiline"),
+        VariableKind::Choice(options) => {
+            source.push_str(&format!(":choice({})", options.join(",")));
+        }
+        VariableKind::File => {}
+    }
+    match (&variable.default, &variable.description) {
+        // 默认值为空时 | 后面的 # 不再是分隔符，说明写在 | 之前
+        (Some(default), Some(description)) if default.is_empty() => {
+            source.push('#');
+            source.push_str(description);
+            source.push('|');
+        }
+        (default, description) => {
+            if let Some(default) = default {
+                source.push('|');
+                source.push_str(&escape_default(default));
+            }
+            if let Some(description) = description {
+                source.push('#');
+                source.push_str(description);
+            }
+        }
+    }
+    source.push_str("}}");
+    source
+}
+
+/// 将内置变量还原为模板源码
+fn builtin_source(builtin: &BuiltinVariable) -> String {
+    let mut source = format!("{{{{{}", builtin.kind.name());
+    if let Some(format) = &builtin.format {
+        source.push(':');
+        source.push_str(format);
+    }
+    if let Some(default) = &builtin.default {
+        source.push('|');
+        source.push_str(&escape_default(default));
+    }
+    source.push_str("}}");
+    source
+}
+
+/// 展开引用并渲染提示词，按需读取剪贴板
+pub fn render_stored_prompt<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    conn: &rusqlite::Connection,
+    prompt_id: &str,
+    values: &HashMap<String, String>,
+) -> Result<String, String> {
+    render_stored_prompt_with(conn, prompt_id, values, || {
+        app_handle.clipboard().read_text().ok()
+    })
+}
+
+/// 展开引用并渲染提示词，模板用到剪贴板时才调用 `read_clipboard`
+///
+/// 不依赖 Tauri，命令行工具也通过这里渲染。
+pub fn render_stored_prompt_with<F>(
+    conn: &rusqlite::Connection,
+    prompt_id: &str,
+    values: &HashMap<String, String>,
+    read_clipboard: F,
+) -> Result<String, String>
+where
+    F: FnOnce() -> Option<String>,
+{
+    render_with_context(conn, prompt_id, values, BuiltinContext::default(), read_clipboard)
+}
+
+fn render_with_context<F>(
+    conn: &rusqlite::Connection,
+    prompt_id: &str,
+    values: &HashMap<String, String>,
+    mut context: BuiltinContext,
+    read_clipboard: F,
+) -> Result<String, String>
+where
+    F: FnOnce() -> Option<String>,
+{
+    let prompt = store::get_prompt(conn, prompt_id).map_err(|e| e.to_string())?;
+    let content = snippets::expand(conn, &prompt.content).map_err(|e| e.to_string())?;
+
+    if uses_builtin(&content, BuiltinKind::Clipboard) {
+        context.clipboard = read_clipboard();
+    }
+    render(&content, values, &context).map_err(|e| e.to_string())
+}
+
+/// 获取提示词中的模板变量（用于生成填写表单）
+#[tauri::command]
+pub async fn get_prompt_variables(
+    store: State<'_, PromptStore>,
+    prompt_id: String,
+) -> Result<Vec<TemplateVariable>, String> {
+    let conn = store.open().map_err(|e| e.to_string())?;
+    let prompt = store::get_prompt(&conn, &prompt_id).map_err(|e| e.to_string())?;
+    let content = snippets::expand(&conn, &prompt.content).map_err(|e| e.to_string())?;
+    Ok(variables(&content))
+}
+
+/// 使用填写的变量值渲染提示词，返回最终文本
+///
+/// 内置变量自动填充；selection_file 使用最近一次拖入填写窗口的文件。
+#[tauri::command]
+pub async fn render_prompt<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+    dropped: State<'_, DroppedFile>,
+    prompt_id: String,
+    values: HashMap<String, String>,
+) -> Result<String, String> {
+    let conn = store.open().map_err(|e| e.to_string())?;
+    let context = BuiltinContext {
+        selection_file: dropped.0.lock().unwrap().clone(),
+        ..BuiltinContext::default()
+    };
+    render_with_context(&conn, &prompt_id, &values, context, || {
+        app_handle.clipboard().read_text().ok()
+    })
+    .map_err(|e| format!("渲染提示词失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(content: &str) -> TemplateVariable {
+        match parse(content).as_slice() {
+            [Segment::Variable(variable)] => variable.clone(),
+            other => panic!("{} 解析为 {:?}", content, other),
+        }
+    }
+
+    #[test]
+    fn parses_name_kind_default_and_description() {
+        let parsed = variable("{{tone:choice(formal,casual)|formal#语气}}");
+        assert_eq!(parsed.name, "tone");
+        assert_eq!(
+            parsed.kind,
+            VariableKind::Choice(vec!["formal".to_string(), "casual".to_string()])
+        );
+        assert_eq!(parsed.default.as_deref(), Some("formal"));
+        assert_eq!(parsed.description.as_deref(), Some("语气"));
+
+        let parsed = variable("{{code:multiline#需要审查的代码}}");
+        assert_eq!(parsed.kind, VariableKind::Multiline);
+        assert_eq!(parsed.default, None);
+        assert_eq!(parsed.description.as_deref(), Some("需要审查的代码"));
+    }
+
+    #[test]
+    fn keeps_hash_in_defaults() {
+        let parsed = variable("{{color|#ff0000}}");
+        assert_eq!(parsed.default.as_deref(), Some("#ff0000"));
+        assert_eq!(parsed.description, None);
+
+        let parsed = variable("{{tag|C#}}");
+        assert_eq!(parsed.default.as_deref(), Some("C#"));
+        assert_eq!(parsed.description, None);
+
+        let parsed = variable("{{issue|bug\\#123#编号}}");
+        assert_eq!(parsed.default.as_deref(), Some("bug#123"));
+        assert_eq!(parsed.description.as_deref(), Some("编号"));
+    }
+
+    #[test]
+    fn parses_includes_builtins_and_escapes() {
+        assert_eq!(
+            parse("a {{> header }} b"),
+            vec![
+                Segment::Text("a ".to_string()),
+                Segment::Include("header".to_string()),
+                Segment::Text(" b".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse("{{date:%Y|无}}"),
+            vec![Segment::Builtin(BuiltinVariable {
+                kind: BuiltinKind::Date,
+                format: Some("%Y".to_string()),
+                default: Some("无".to_string()),
+            })]
+        );
+        assert_eq!(parse("\\{{name}}"), vec![Segment::Text("{{name}}".to_string())]);
+        assert_eq!(
+            parse("{{not valid}} {{x:unknown}}"),
+            vec![Segment::Text("{{not valid}} {{x:unknown}}".to_string())]
+        );
+    }
+
+    #[test]
+    fn expanding_includes_preserves_declarations() {
+        let content = "{{color|#ff0000}} {{issue|bug\\#1#编号}} {{name#名字|}} \\{{raw}}";
+        let expanded =
+            expand_includes(content, &|_: &str| Ok::<Option<String>, String>(None)).unwrap();
+        assert_eq!(variables(&expanded), variables(content));
+        assert_eq!(
+            render(&expanded, &HashMap::new(), &BuiltinContext::default()).unwrap(),
+            "#ff0000 bug#1  {{raw}}"
+        );
+    }
+
+    #[test]
+    fn renders_values_and_reports_errors() {
+        let context = BuiltinContext::default();
+        let content = "{{tone:choice(formal,casual)|formal}} {{topic}}";
+
+        let values = HashMap::from([("topic".to_string(), "Rust".to_string())]);
+        assert_eq!(render(content, &values, &context).unwrap(), "formal Rust");
+
+        assert!(matches!(
+            render(content, &HashMap::new(), &context),
+            Err(TemplateError::MissingValues(names)) if names == vec!["topic".to_string()]
+        ));
+
+        let values = HashMap::from([
+            ("tone".to_string(), "angry".to_string()),
+            ("topic".to_string(), "Rust".to_string()),
+        ]);
+        assert!(matches!(
+            render(content, &values, &context),
+            Err(TemplateError::InvalidChoice { .. })
+        ));
+    }
+
+    #[test]
+    fn ignores_selection_file_from_values() {
+        let values = HashMap::from([("selection_file".to_string(), "/etc/passwd".to_string())]);
+        let rendered = render("{{selection_file|无}}", &values, &BuiltinContext::default());
+        assert_eq!(rendered.unwrap(), "无");
+    }
+}
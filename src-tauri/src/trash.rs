@@ -0,0 +1,149 @@
+use chrono::{Duration, SecondsFormat, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::notify_prompts_changed;
+use crate::settings::load_app_settings;
+use crate::store::{PromptStore, StoreError, StoredPrompt, PROMPT_COLUMNS};
+
+/// 回收站设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrashSettings {
+    /// 删除超过多少天后自动清空，0 表示不自动清空
+    pub auto_empty_days: u32,
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        Self { auto_empty_days: 30 }
+    }
+}
+
+/// 获取回收站中的提示词，最近删除的在前
+pub fn list_deleted(conn: &Connection) -> Result<Vec<StoredPrompt>, StoreError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM prompts WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+        PROMPT_COLUMNS
+    ))?;
+    let prompts = stmt
+        .query_map([], crate::store::prompt_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(prompts)
+}
+
+/// 把提示词移入回收站，已在回收站中的不变
+pub fn move_to_trash(conn: &Connection, id: &str, deleted_at: &str) -> Result<bool, StoreError> {
+    let affected = conn.execute(
+        "UPDATE prompts SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+        params![deleted_at, id],
+    )?;
+    Ok(affected > 0)
+}
+
+/// 从回收站恢复提示词
+pub fn restore_deleted(conn: &Connection, id: &str) -> Result<(), StoreError> {
+    let affected = conn.execute(
+        "UPDATE prompts SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+        params![id],
+    )?;
+    if affected == 0 {
+        return Err(StoreError::PromptNotFound(id.to_string()));
+    }
+    Ok(())
+}
+
+/// 彻底删除回收站中的提示词，标签关联由外键级联删除
+pub fn purge(conn: &mut Connection, ids: &[String]) -> Result<usize, StoreError> {
+    let tx = conn.transaction()?;
+    let mut purged = 0;
+    for id in ids {
+        // 只删除已在回收站中的提示词
+        purged += tx.execute(
+            "DELETE FROM prompts WHERE id = ?1 AND deleted_at IS NOT NULL",
+            params![id],
+        )?;
+    }
+    tx.commit()?;
+    Ok(purged)
+}
+
+/// 彻底删除在指定时间之前进入回收站的提示词
+pub fn purge_deleted_before(conn: &mut Connection, cutoff: &str) -> Result<usize, StoreError> {
+    let ids = conn
+        .prepare("SELECT id FROM prompts WHERE deleted_at IS NOT NULL AND deleted_at < ?1")?
+        .query_map(params![cutoff], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    purge(conn, &ids)
+}
+
+/// 按设置自动清空过期的回收站条目（启动时和保存设置后调用）
+pub fn empty_expired_with_settings<R: Runtime>(app_handle: &AppHandle<R>) {
+    let settings = load_app_settings(app_handle).trash;
+    if settings.auto_empty_days == 0 {
+        return;
+    }
+
+    let cutoff = (Utc::now() - Duration::days(settings.auto_empty_days as i64))
+        .to_rfc3339_opts(SecondsFormat::Millis, true);
+    let store = app_handle.state::<PromptStore>();
+    match store
+        .open()
+        .and_then(|mut conn| purge_deleted_before(&mut conn, &cutoff))
+    {
+        Ok(purged) if purged > 0 => println!("回收站已自动清空 {} 个提示词", purged),
+        Ok(_) => {}
+        Err(e) => println!("自动清空回收站失败: {}", e),
+    }
+}
+
+/// 获取回收站列表
+#[tauri::command]
+pub async fn list_trash(store: State<'_, PromptStore>) -> Result<Vec<StoredPrompt>, String> {
+    let conn = store.open().map_err(|e| e.to_string())?;
+    list_deleted(&conn).map_err(|e| format!("获取回收站失败: {}", e))
+}
+
+/// 从回收站恢复提示词
+#[tauri::command]
+pub async fn restore_from_trash<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+    prompt_id: String,
+) -> Result<(), String> {
+    let conn = store.open().map_err(|e| e.to_string())?;
+    restore_deleted(&conn, &prompt_id).map_err(|e| format!("恢复提示词失败: {}", e))?;
+    notify_prompts_changed(&app_handle, &[prompt_id]);
+    Ok(())
+}
+
+/// 彻底删除回收站中的指定提示词
+#[tauri::command]
+pub async fn purge_from_trash<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+    prompt_ids: Vec<String>,
+) -> Result<usize, String> {
+    let mut conn = store.open().map_err(|e| e.to_string())?;
+    let purged = purge(&mut conn, &prompt_ids).map_err(|e| format!("彻底删除失败: {}", e))?;
+    notify_prompts_changed(&app_handle, &prompt_ids);
+    Ok(purged)
+}
+
+/// 清空回收站
+#[tauri::command]
+pub async fn empty_trash<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+) -> Result<usize, String> {
+    let mut conn = store.open().map_err(|e| e.to_string())?;
+    let ids: Vec<String> = list_deleted(&conn)
+        .map_err(|e| format!("获取回收站失败: {}", e))?
+        .into_iter()
+        .map(|p| p.id)
+        .collect();
+    let purged = purge(&mut conn, &ids).map_err(|e| format!("清空回收站失败: {}", e))?;
+    notify_prompts_changed(&app_handle, &ids);
+    Ok(purged)
+}
@@ -0,0 +1,427 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{
+    image::Image,
+    menu::{Menu, MenuBuilder, MenuEvent, MenuItem, Submenu, SubmenuBuilder},
+    AppHandle, Emitter, Manager, Runtime,
+};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::settings::load_app_settings;
+use crate::store::{PromptStore, StoreError};
+use crate::usage::UsageSource;
+use crate::{copy_prompt_to_clipboard, open_fill_window, prompt_has_variables};
+
+/// 托盘图标 ID
+pub const TRAY_ID: &str = "default";
+
+/// 提示词菜单项 ID 的前缀，完整格式为 prompt:<分区>:<提示词ID>
+///
+/// 同一个提示词可能同时出现在多个分区中，菜单项 ID 需要保持唯一。
+const PROMPT_ITEM_PREFIX: &str = "prompt:";
+
+/// 检查数据库是否被其他连接修改的间隔
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 标签颜色图标的边长（像素）
+const TAG_ICON_SIZE: u32 = 16;
+
+/// 托盘菜单设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TraySettings {
+    /// 最近使用的提示词数量
+    pub recent_count: u32,
+    /// 收藏子菜单中的提示词数量，0 表示不显示
+    pub favorite_count: u32,
+    /// "最常使用"子菜单中的提示词数量，0 表示不显示
+    pub most_used_count: u32,
+    /// 是否为每个标签显示子菜单
+    pub show_tags: bool,
+    /// 每个标签子菜单中的提示词数量
+    pub tag_item_count: u32,
+    /// 标题超过该字符数时截断
+    pub title_max_chars: u32,
+}
+
+impl Default for TraySettings {
+    fn default() -> Self {
+        Self {
+            recent_count: 5,
+            favorite_count: 10,
+            most_used_count: 10,
+            show_tags: true,
+            tag_item_count: 10,
+            title_max_chars: 30,
+        }
+    }
+}
+
+/// 菜单中的提示词
+#[derive(Debug, Clone)]
+pub struct MenuPrompt {
+    pub id: String,
+    pub title: String,
+}
+
+/// 菜单中的标签及其提示词
+#[derive(Debug, Clone)]
+pub struct MenuTag {
+    pub id: String,
+    pub name: String,
+    pub color: String,
+    pub prompts: Vec<MenuPrompt>,
+}
+
+/// 构建托盘菜单所需的数据
+#[derive(Debug, Default)]
+pub struct TrayMenuData {
+    pub recent: Vec<MenuPrompt>,
+    pub favorites: Vec<MenuPrompt>,
+    pub most_used: Vec<MenuPrompt>,
+    pub tags: Vec<MenuTag>,
+}
+
+fn query_prompts(
+    conn: &Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> Result<Vec<MenuPrompt>, StoreError> {
+    let mut stmt = conn.prepare(sql)?;
+    let prompts = stmt
+        .query_map(params, |row| {
+            Ok(MenuPrompt {
+                id: row.get(0)?,
+                title: row.get(1)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(prompts)
+}
+
+/// 从数据库读取托盘菜单数据
+pub fn load_menu_data(conn: &Connection, settings: &TraySettings) -> Result<TrayMenuData, StoreError> {
+    let recent = query_prompts(
+        conn,
+        "SELECT id, title FROM prompts WHERE deleted_at IS NULL
+         ORDER BY last_used_at DESC LIMIT ?1",
+        params![settings.recent_count],
+    )?;
+
+    let favorites = query_prompts(
+        conn,
+        "SELECT id, title FROM prompts WHERE deleted_at IS NULL AND is_favorite = 1
+         ORDER BY last_used_at DESC LIMIT ?1",
+        params![settings.favorite_count],
+    )?;
+
+    let most_used = query_prompts(
+        conn,
+        "SELECT id, title FROM prompts WHERE deleted_at IS NULL AND use_count > 0
+         ORDER BY use_count DESC, last_used_at DESC LIMIT ?1",
+        params![settings.most_used_count],
+    )?;
+
+    let mut tags = Vec::new();
+    if settings.show_tags && settings.tag_item_count > 0 {
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT t.id, t.name, t.color FROM tags t
+             JOIN prompt_tags pt ON pt.tag_id = t.id
+             JOIN prompts p ON p.id = pt.prompt_id
+             WHERE p.deleted_at IS NULL
+             ORDER BY t.name ASC",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (id, name, color) in rows {
+            let prompts = query_prompts(
+                conn,
+                "SELECT p.id, p.title FROM prompts p
+                 JOIN prompt_tags pt ON pt.prompt_id = p.id
+                 WHERE pt.tag_id = ?1 AND p.deleted_at IS NULL
+                 ORDER BY p.last_used_at DESC LIMIT ?2",
+                params![id, settings.tag_item_count],
+            )?;
+            tags.push(MenuTag {
+                id,
+                name,
+                color,
+                prompts,
+            });
+        }
+    }
+
+    Ok(TrayMenuData {
+        recent,
+        favorites,
+        most_used,
+        tags,
+    })
+}
+
+/// 按字符数截断标题，超出部分用省略号代替
+pub fn truncate_title(title: &str, max_chars: u32) -> String {
+    let max_chars = max_chars as usize;
+    if max_chars == 0 || title.chars().count() <= max_chars {
+        return title.to_string();
+    }
+    let kept: String = title.chars().take(max_chars.saturating_sub(3)).collect();
+    format!("{}...", kept)
+}
+
+fn prompt_item_id(section: &str, prompt_id: &str) -> String {
+    format!("{}{}:{}", PROMPT_ITEM_PREFIX, section, prompt_id)
+}
+
+/// 从菜单项 ID 中取出提示词 ID，不是提示词菜单项时返回 None
+pub fn prompt_id_from_menu_id(menu_id: &str) -> Option<&str> {
+    menu_id
+        .strip_prefix(PROMPT_ITEM_PREFIX)
+        .and_then(|rest| rest.split_once(':'))
+        .map(|(_, prompt_id)| prompt_id)
+}
+
+/// 解析 #RRGGBB 格式的标签颜色
+fn parse_hex_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.trim().strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// 生成标签颜色的圆点图标
+fn tag_icon(color: &str) -> Option<Image<'static>> {
+    let [r, g, b] = parse_hex_color(color)?;
+    let center = (TAG_ICON_SIZE as f32 - 1.0) / 2.0;
+    let radius = TAG_ICON_SIZE as f32 / 2.0 - 2.0;
+
+    let mut rgba = Vec::with_capacity((TAG_ICON_SIZE * TAG_ICON_SIZE * 4) as usize);
+    for y in 0..TAG_ICON_SIZE {
+        for x in 0..TAG_ICON_SIZE {
+            let distance = ((x as f32 - center).powi(2) + (y as f32 - center).powi(2)).sqrt();
+            // 边缘一个像素做简单的抗锯齿
+            let alpha = (radius + 0.5 - distance).clamp(0.0, 1.0);
+            rgba.extend_from_slice(&[r, g, b, (alpha * 255.0) as u8]);
+        }
+    }
+    Some(Image::new_owned(rgba, TAG_ICON_SIZE, TAG_ICON_SIZE))
+}
+
+/// 构建提示词子菜单
+fn prompt_submenu<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    text: &str,
+    section: &str,
+    prompts: &[MenuPrompt],
+    icon: Option<Image<'static>>,
+    settings: &TraySettings,
+) -> tauri::Result<Submenu<R>> {
+    let mut submenu = SubmenuBuilder::new(app_handle, text);
+    if let Some(icon) = icon {
+        submenu = submenu.submenu_icon(icon);
+    }
+    for prompt in prompts {
+        submenu = submenu.text(
+            prompt_item_id(section, &prompt.id),
+            truncate_title(&prompt.title, settings.title_max_chars),
+        );
+    }
+    submenu.build()
+}
+
+fn build_menu<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    data: &TrayMenuData,
+    settings: &TraySettings,
+) -> tauri::Result<Menu<R>> {
+    let mut menu_builder = MenuBuilder::new(app_handle)
+        .text("show-window", "显示主窗口")
+        .separator()
+        .item(&MenuItem::with_id(app_handle, "recent-title", "最近使用的提示词", false, None::<&str>)?);
+
+    if data.recent.is_empty() {
+        menu_builder = menu_builder.item(&MenuItem::with_id(
+            app_handle,
+            "no-recent",
+            "  无记录", // 增加缩进以表示层级关系
+            false,
+            None::<&str>,
+        )?);
+    }
+    for prompt in &data.recent {
+        menu_builder = menu_builder.text(
+            prompt_item_id("recent", &prompt.id),
+            format!("  {}", truncate_title(&prompt.title, settings.title_max_chars)),
+        );
+    }
+
+    if !data.favorites.is_empty() || !data.most_used.is_empty() || !data.tags.is_empty() {
+        menu_builder = menu_builder.separator();
+    }
+    if !data.favorites.is_empty() {
+        menu_builder = menu_builder.item(&prompt_submenu(
+            app_handle,
+            "★ 收藏",
+            "favorite",
+            &data.favorites,
+            None,
+            settings,
+        )?);
+    }
+    if !data.most_used.is_empty() {
+        menu_builder = menu_builder.item(&prompt_submenu(
+            app_handle,
+            "最常使用",
+            "most-used",
+            &data.most_used,
+            None,
+            settings,
+        )?);
+    }
+    for tag in &data.tags {
+        menu_builder = menu_builder.item(&prompt_submenu(
+            app_handle,
+            &truncate_title(&tag.name, settings.title_max_chars),
+            &format!("tag-{}", tag.id),
+            &tag.prompts,
+            tag_icon(&tag.color),
+            settings,
+        )?);
+    }
+
+    menu_builder.separator().text("quit", "退出").build()
+}
+
+/// 从数据库重新构建托盘菜单
+pub fn refresh_tray_menu<R: Runtime>(app_handle: &AppHandle<R>) -> Result<(), String> {
+    let settings = load_app_settings(app_handle).tray;
+    let store = app_handle.state::<PromptStore>();
+    let data = store
+        .open()
+        .and_then(|conn| load_menu_data(&conn, &settings))
+        .map_err(|e| format!("读取托盘菜单数据失败: {}", e))?;
+    let menu = build_menu(app_handle, &data, &settings).map_err(|e| format!("构建托盘菜单失败: {}", e))?;
+
+    let tray_icon = app_handle
+        .tray_by_id(TRAY_ID)
+        .ok_or_else(|| "找不到默认托盘图标".to_string())?;
+    tray_icon.set_menu(Some(menu)).map_err(|e| e.to_string())
+}
+
+/// 在后台线程中监听数据库变化并刷新托盘菜单
+///
+/// 前端通过 tauri-plugin-sql 直接写库，Rust 端无法逐一感知，
+/// 这里用 PRAGMA data_version 检测其他连接提交的修改。
+pub fn watch_database<R: Runtime>(app_handle: AppHandle<R>) {
+    std::thread::spawn(move || {
+        let mut conn: Option<Connection> = None;
+        let mut last_version: Option<i64> = None;
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+
+            if conn.is_none() {
+                conn = app_handle.state::<PromptStore>().open().ok();
+            }
+            let Some(current) = &conn else {
+                continue;
+            };
+            let version = match current.query_row("PRAGMA data_version", [], |row| row.get::<_, i64>(0)) {
+                Ok(version) => version,
+                Err(e) => {
+                    println!("检查数据库变化失败: {}", e);
+                    conn = None;
+                    continue;
+                }
+            };
+
+            if last_version != Some(version) {
+                last_version = Some(version);
+                if let Err(e) = refresh_tray_menu(&app_handle) {
+                    println!("{}", e);
+                }
+            }
+        }
+    });
+}
+
+/// 处理托盘菜单点击
+pub fn handle_menu_event<R: Runtime>(app_handle: &AppHandle<R>, event: MenuEvent) {
+    let id = event.id().0.as_str();
+    match id {
+        "quit" => {
+            std::process::exit(0);
+        }
+        "show-window" => {
+            // 显示主窗口
+            if let Some(window) = app_handle.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+        _ => {
+            // 标题、"无记录"等不可点击的菜单项
+            let Some(prompt_id) = prompt_id_from_menu_id(id) else {
+                return;
+            };
+            println!("Clicked prompt menu item ID: {}", prompt_id);
+
+            // 含有模板变量时先打开填写表单
+            match prompt_has_variables(app_handle, prompt_id) {
+                Ok(true) => {
+                    if let Err(e) = open_fill_window(app_handle, prompt_id, UsageSource::Tray) {
+                        eprintln!("打开变量填写窗口失败: {}", e);
+                    }
+                    return;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    eprintln!("读取提示词模板变量失败: {}", e);
+                }
+            }
+
+            // 在 Rust 端完成复制，主窗口未创建或被挂起时同样可用
+            match copy_prompt_to_clipboard(app_handle, prompt_id, UsageSource::Tray) {
+                Ok(title) => {
+                    show_notification(app_handle, &format!("已复制「{}」", title));
+                    // 主窗口存在时由前端决定是否自动粘贴
+                    let _ = app_handle.emit_to("main", "tray-prompt-copied", prompt_id);
+                }
+                Err(e) => {
+                    eprintln!("复制提示词失败: {}", e);
+                    show_notification(app_handle, &format!("复制失败: {}", e));
+                }
+            }
+        }
+    }
+}
+
+/// 显示系统通知
+pub(crate) fn show_notification<R: Runtime>(app_handle: &AppHandle<R>, body: &str) {
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title("PromptGenie")
+        .body(body)
+        .show()
+    {
+        println!("显示通知失败: {}", e);
+    }
+}
+
+/// 刷新托盘菜单（前端修改数据后调用，菜单内容由 Rust 端从数据库读取）
+#[tauri::command]
+pub async fn update_tray_menu<R: Runtime>(app_handle: AppHandle<R>) -> Result<(), String> {
+    refresh_tray_menu(&app_handle)
+}
@@ -0,0 +1,323 @@
+use chrono::{Duration, NaiveDate, SecondsFormat, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+use uuid::Uuid;
+
+use crate::store::{now_timestamp, PromptStore, StoreError};
+
+/// 使用方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageAction {
+    /// 复制到剪贴板
+    Copy,
+    /// 渲染模板（不复制）
+    Render,
+    /// 发送给 LLM 运行
+    Run,
+}
+
+/// 使用来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageSource {
+    Tray,
+    Palette,
+    Main,
+    Cli,
+    /// 本地 HTTP API
+    Api,
+    /// MCP 客户端
+    Mcp,
+    /// promptgenie:// 链接
+    Link,
+}
+
+impl UsageAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Copy => "copy",
+            Self::Render => "render",
+            Self::Run => "run",
+        }
+    }
+}
+
+impl UsageSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tray => "tray",
+            Self::Palette => "palette",
+            Self::Main => "main",
+            Self::Cli => "cli",
+            Self::Api => "api",
+            Self::Mcp => "mcp",
+            Self::Link => "link",
+        }
+    }
+}
+
+/// 单个提示词的使用统计
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptUsageCount {
+    pub prompt_id: String,
+    pub title: String,
+    pub count: i64,
+    /// 按来源统计的次数
+    pub by_source: HashMap<String, i64>,
+    pub last_used_at: Option<String>,
+}
+
+/// 某一天的使用次数
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageTrendPoint {
+    /// UTC 日期，格式 YYYY-MM-DD
+    pub date: String,
+    pub count: i64,
+}
+
+/// 标签的使用次数（标签下所有提示词之和）
+#[derive(Debug, Clone, Serialize)]
+pub struct TagUsageCount {
+    pub tag_id: String,
+    pub name: String,
+    pub color: String,
+    pub count: i64,
+}
+
+/// 统计周期内没有被使用过的提示词，可考虑清理
+#[derive(Debug, Clone, Serialize)]
+pub struct UnusedPrompt {
+    pub prompt_id: String,
+    pub title: String,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+}
+
+/// 使用统计
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageStats {
+    /// 统计周期（天），0 表示全部记录
+    pub days: u32,
+    pub total: i64,
+    pub prompts: Vec<PromptUsageCount>,
+    pub trend: Vec<UsageTrendPoint>,
+    pub tags: Vec<TagUsageCount>,
+    pub unused: Vec<UnusedPrompt>,
+}
+
+/// 记录一次使用，last_used_at 和 use_count 由触发器同步更新
+pub fn record_usage(
+    conn: &Connection,
+    prompt_id: &str,
+    action: UsageAction,
+    source: UsageSource,
+) -> Result<(), StoreError> {
+    let affected = conn.execute(
+        "INSERT INTO prompt_usage (id, prompt_id, action, source, used_at)
+         SELECT ?1, id, ?2, ?3, ?4 FROM prompts WHERE id = ?5",
+        params![
+            Uuid::now_v7().to_string(),
+            action.as_str(),
+            source.as_str(),
+            now_timestamp(),
+            prompt_id
+        ],
+    )?;
+    if affected == 0 {
+        return Err(StoreError::PromptNotFound(prompt_id.to_string()));
+    }
+    Ok(())
+}
+
+/// 统计周期的起始时间，0 天表示不限制
+fn period_start(days: u32) -> String {
+    if days == 0 {
+        return String::new();
+    }
+    let start = Utc::now().date_naive() - Duration::days(days as i64 - 1);
+    start
+        .and_hms_opt(0, 0, 0)
+        .map(|time| time.and_utc().to_rfc3339_opts(SecondsFormat::Millis, true))
+        .unwrap_or_default()
+}
+
+/// 按提示词统计使用次数，同时按来源细分
+fn prompt_counts(conn: &Connection, since: &str) -> Result<Vec<PromptUsageCount>, StoreError> {
+    let mut stmt = conn.prepare(
+        "SELECT u.prompt_id, p.title, u.source, COUNT(*), MAX(u.used_at)
+         FROM prompt_usage u JOIN prompts p ON p.id = u.prompt_id
+         WHERE u.used_at >= ?1
+         GROUP BY u.prompt_id, u.source",
+    )?;
+    let rows = stmt.query_map(params![since], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, i64>(3)?,
+            row.get::<_, Option<String>>(4)?,
+        ))
+    })?;
+
+    let mut prompts: Vec<PromptUsageCount> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for row in rows {
+        let (prompt_id, title, source, count, last_used_at) = row?;
+        let position = *index.entry(prompt_id.clone()).or_insert_with(|| {
+            prompts.push(PromptUsageCount {
+                prompt_id,
+                title,
+                count: 0,
+                by_source: HashMap::new(),
+                last_used_at: None,
+            });
+            prompts.len() - 1
+        });
+        let entry = &mut prompts[position];
+        entry.count += count;
+        entry.by_source.insert(source, count);
+        if last_used_at > entry.last_used_at {
+            entry.last_used_at = last_used_at;
+        }
+    }
+
+    prompts.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| b.last_used_at.cmp(&a.last_used_at))
+    });
+    Ok(prompts)
+}
+
+/// 按天统计使用次数
+fn daily_trend(conn: &Connection, since: &str) -> Result<Vec<UsageTrendPoint>, StoreError> {
+    let mut stmt = conn.prepare(
+        "SELECT substr(used_at, 1, 10) AS day, COUNT(*) FROM prompt_usage
+         WHERE used_at >= ?1 GROUP BY day ORDER BY day ASC",
+    )?;
+    let points = stmt
+        .query_map(params![since], |row| {
+            Ok(UsageTrendPoint {
+                date: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(points)
+}
+
+/// 按标签统计使用次数
+fn tag_counts(conn: &Connection, since: &str, limit: u32) -> Result<Vec<TagUsageCount>, StoreError> {
+    let mut stmt = conn.prepare(
+        "SELECT t.id, t.name, t.color, COUNT(*) AS uses
+         FROM prompt_usage u
+         JOIN prompt_tags pt ON pt.prompt_id = u.prompt_id
+         JOIN tags t ON t.id = pt.tag_id
+         WHERE u.used_at >= ?1
+         GROUP BY t.id ORDER BY uses DESC, t.name ASC LIMIT ?2",
+    )?;
+    let tags = stmt
+        .query_map(params![since, limit], |row| {
+            Ok(TagUsageCount {
+                tag_id: row.get(0)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+                count: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(tags)
+}
+
+/// 统计周期内没有使用记录的提示词，最早创建的在前
+fn unused_prompts(conn: &Connection, since: &str, limit: u32) -> Result<Vec<UnusedPrompt>, StoreError> {
+    let mut stmt = conn.prepare(
+        "SELECT p.id, p.title, p.created_at,
+                (SELECT MAX(u.used_at) FROM prompt_usage u WHERE u.prompt_id = p.id)
+         FROM prompts p
+         WHERE p.deleted_at IS NULL
+           AND NOT EXISTS (SELECT 1 FROM prompt_usage u
+                           WHERE u.prompt_id = p.id AND u.used_at >= ?1)
+         ORDER BY p.created_at ASC LIMIT ?2",
+    )?;
+    let unused = stmt
+        .query_map(params![since, limit], |row| {
+            Ok(UnusedPrompt {
+                prompt_id: row.get(0)?,
+                title: row.get(1)?,
+                created_at: row.get(2)?,
+                last_used_at: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(unused)
+}
+
+/// 统计最近 `days` 天（0 表示全部）的使用情况，各列表最多返回 `limit` 条
+pub fn collect_stats(conn: &Connection, days: u32, limit: u32) -> Result<UsageStats, StoreError> {
+    let since = period_start(days);
+
+    let total: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM prompt_usage WHERE used_at >= ?1",
+        params![since],
+        |row| row.get(0),
+    )?;
+
+    let mut prompts = prompt_counts(conn, &since)?;
+    prompts.truncate(limit as usize);
+
+    let mut trend = daily_trend(conn, &since)?;
+    if days > 0 {
+        trend = fill_missing_days(trend, days);
+    }
+
+    Ok(UsageStats {
+        days,
+        total,
+        prompts,
+        trend,
+        tags: tag_counts(conn, &since, limit)?,
+        unused: unused_prompts(conn, &since, limit)?,
+    })
+}
+
+/// 补齐没有使用记录的日期，便于前端直接绘制趋势图
+fn fill_missing_days(points: Vec<UsageTrendPoint>, days: u32) -> Vec<UsageTrendPoint> {
+    let counts: HashMap<String, i64> = points.into_iter().map(|p| (p.date, p.count)).collect();
+    let today: NaiveDate = Utc::now().date_naive();
+    (0..days as i64)
+        .rev()
+        .map(|offset| {
+            let date = (today - Duration::days(offset)).format("%Y-%m-%d").to_string();
+            let count = counts.get(&date).copied().unwrap_or(0);
+            UsageTrendPoint { date, count }
+        })
+        .collect()
+}
+
+/// 记录一次提示词使用（前端复制或渲染后调用）
+#[tauri::command]
+pub async fn record_prompt_usage(
+    store: State<'_, PromptStore>,
+    prompt_id: String,
+    action: UsageAction,
+    source: UsageSource,
+) -> Result<(), String> {
+    let conn = store.open().map_err(|e| e.to_string())?;
+    record_usage(&conn, &prompt_id, action, source).map_err(|e| format!("记录使用失败: {}", e))
+}
+
+/// 获取使用统计，默认统计最近 30 天
+#[tauri::command]
+pub async fn usage_stats(
+    store: State<'_, PromptStore>,
+    days: Option<u32>,
+    limit: Option<u32>,
+) -> Result<UsageStats, String> {
+    let conn = store.open().map_err(|e| e.to_string())?;
+    collect_stats(&conn, days.unwrap_or(30), limit.unwrap_or(20))
+        .map_err(|e| format!("获取使用统计失败: {}", e))
+}
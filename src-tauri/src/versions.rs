@@ -0,0 +1,253 @@
+use chrono::{Duration, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::notify_prompts_changed;
+use crate::settings::load_app_settings;
+use crate::store::{self, PromptStore, StoreError};
+
+/// 版本保留策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VersionSettings {
+    /// 每个提示词最多保留的版本数，0 表示不限制
+    pub max_versions_per_prompt: u32,
+    /// 超过多少天的历史版本会被清理，0 表示不限制
+    pub max_age_days: u32,
+}
+
+impl Default for VersionSettings {
+    fn default() -> Self {
+        Self {
+            max_versions_per_prompt: 50,
+            max_age_days: 0,
+        }
+    }
+}
+
+/// 提示词的一个历史版本
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptVersion {
+    pub prompt_id: String,
+    pub version: i64,
+    pub title: String,
+    pub content: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffKind {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// 差异中的一行
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+/// 两个版本之间的差异
+#[derive(Debug, Serialize)]
+pub struct VersionDiff {
+    pub prompt_id: String,
+    pub from_version: i64,
+    pub to_version: i64,
+    pub from_title: String,
+    pub to_title: String,
+    pub lines: Vec<DiffLine>,
+    pub insertions: u32,
+    pub deletions: u32,
+}
+
+fn version_from_row(row: &rusqlite::Row) -> rusqlite::Result<PromptVersion> {
+    Ok(PromptVersion {
+        prompt_id: row.get("prompt_id")?,
+        version: row.get("version")?,
+        title: row.get("title")?,
+        content: row.get("content")?,
+        created_at: row.get("created_at")?,
+    })
+}
+
+/// 获取提示词的所有版本，最新的在前
+pub fn list_versions(conn: &Connection, prompt_id: &str) -> Result<Vec<PromptVersion>, StoreError> {
+    let mut stmt = conn.prepare(
+        "SELECT prompt_id, version, title, content, created_at
+         FROM prompt_versions WHERE prompt_id = ?1 ORDER BY version DESC",
+    )?;
+    let versions = stmt
+        .query_map(params![prompt_id], version_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(versions)
+}
+
+/// 获取指定版本
+pub fn get_version(
+    conn: &Connection,
+    prompt_id: &str,
+    version: i64,
+) -> Result<PromptVersion, StoreError> {
+    conn.query_row(
+        "SELECT prompt_id, version, title, content, created_at
+         FROM prompt_versions WHERE prompt_id = ?1 AND version = ?2",
+        params![prompt_id, version],
+        version_from_row,
+    )
+    .optional()?
+    .ok_or_else(|| StoreError::VersionNotFound {
+        prompt_id: prompt_id.to_string(),
+        version,
+    })
+}
+
+/// 按行比较两个版本的内容
+pub fn diff_versions(from: &PromptVersion, to: &PromptVersion) -> VersionDiff {
+    let text_diff = TextDiff::from_lines(&from.content, &to.content);
+    let mut lines = Vec::new();
+    let mut insertions = 0;
+    let mut deletions = 0;
+
+    for change in text_diff.iter_all_changes() {
+        let kind = match change.tag() {
+            ChangeTag::Equal => DiffKind::Equal,
+            ChangeTag::Insert => {
+                insertions += 1;
+                DiffKind::Insert
+            }
+            ChangeTag::Delete => {
+                deletions += 1;
+                DiffKind::Delete
+            }
+        };
+        lines.push(DiffLine {
+            kind,
+            text: change.to_string_lossy().trim_end_matches('\n').to_string(),
+        });
+    }
+
+    VersionDiff {
+        prompt_id: to.prompt_id.clone(),
+        from_version: from.version,
+        to_version: to.version,
+        from_title: from.title.clone(),
+        to_title: to.title.clone(),
+        lines,
+        insertions,
+        deletions,
+    }
+}
+
+/// 将提示词恢复到指定版本
+///
+/// 恢复操作本身会生成一个新版本，因此不会丢失恢复前的内容。
+pub fn restore_version(
+    conn: &Connection,
+    prompt_id: &str,
+    version: i64,
+) -> Result<PromptVersion, StoreError> {
+    let target = get_version(conn, prompt_id, version)?;
+    let current = store::get_prompt(conn, prompt_id)?;
+
+    if current.title != target.title || current.content != target.content {
+        store::update_prompt_content(conn, prompt_id, &target.title, &target.content)?;
+    }
+
+    let latest = list_versions(conn, prompt_id)?
+        .into_iter()
+        .next()
+        .unwrap_or(target);
+    Ok(latest)
+}
+
+/// 按保留策略清理历史版本，每个提示词的最新版本始终保留
+pub fn prune_versions(conn: &Connection, settings: &VersionSettings) -> Result<usize, StoreError> {
+    let mut removed = 0;
+
+    if settings.max_versions_per_prompt > 0 {
+        removed += conn.execute(
+            "DELETE FROM prompt_versions WHERE id IN (
+                SELECT id FROM (
+                    SELECT id, ROW_NUMBER() OVER (PARTITION BY prompt_id ORDER BY version DESC) AS rn
+                    FROM prompt_versions
+                ) WHERE rn > ?1
+            )",
+            params![settings.max_versions_per_prompt],
+        )?;
+    }
+
+    if settings.max_age_days > 0 {
+        let cutoff = (Utc::now() - Duration::days(settings.max_age_days as i64))
+            .to_rfc3339_opts(SecondsFormat::Millis, true);
+        removed += conn.execute(
+            "DELETE FROM prompt_versions
+             WHERE created_at < ?1
+               AND version < (SELECT MAX(pv.version) FROM prompt_versions pv
+                              WHERE pv.prompt_id = prompt_versions.prompt_id)",
+            params![cutoff],
+        )?;
+    }
+
+    Ok(removed)
+}
+
+/// 使用当前设置清理历史版本（启动时和保存设置后调用）
+pub fn prune_with_settings<R: Runtime>(app_handle: &AppHandle<R>) {
+    let settings = load_app_settings(app_handle);
+    let store = app_handle.state::<PromptStore>();
+    match store
+        .open()
+        .and_then(|conn| prune_versions(&conn, &settings.versions))
+    {
+        Ok(removed) if removed > 0 => println!("已清理 {} 个历史版本", removed),
+        Ok(_) => {}
+        Err(e) => println!("清理历史版本失败: {}", e),
+    }
+}
+
+/// 获取提示词的版本列表
+#[tauri::command]
+pub async fn list_prompt_versions(
+    store: State<'_, PromptStore>,
+    prompt_id: String,
+) -> Result<Vec<PromptVersion>, String> {
+    let conn = store.open().map_err(|e| e.to_string())?;
+    list_versions(&conn, &prompt_id).map_err(|e| format!("获取版本列表失败: {}", e))
+}
+
+/// 比较提示词的任意两个版本
+#[tauri::command]
+pub async fn diff_prompt_versions(
+    store: State<'_, PromptStore>,
+    prompt_id: String,
+    from_version: i64,
+    to_version: i64,
+) -> Result<VersionDiff, String> {
+    let conn = store.open().map_err(|e| e.to_string())?;
+    let from = get_version(&conn, &prompt_id, from_version).map_err(|e| e.to_string())?;
+    let to = get_version(&conn, &prompt_id, to_version).map_err(|e| e.to_string())?;
+    Ok(diff_versions(&from, &to))
+}
+
+/// 将提示词恢复到某个历史版本
+#[tauri::command]
+pub async fn restore_prompt_version<R: Runtime>(
+    app_handle: AppHandle<R>,
+    store: State<'_, PromptStore>,
+    prompt_id: String,
+    version: i64,
+) -> Result<PromptVersion, String> {
+    let conn = store.open().map_err(|e| e.to_string())?;
+    let restored = restore_version(&conn, &prompt_id, version)
+        .map_err(|e| format!("恢复版本失败: {}", e))?;
+    drop(conn);
+
+    prune_with_settings(&app_handle);
+    notify_prompts_changed(&app_handle, &[prompt_id]);
+    Ok(restored)
+}
@@ -6,6 +6,8 @@ import PromptEditor from './components/PromptEditor';
 import TagManager from './components/TagManager';
 import Settings from './components/Settings';
 import ConfirmDialog from './components/ConfirmDialog';
+import BundleImportDialog from './components/BundleImportDialog';
+import LinkImportDialog from './components/LinkImportDialog';
 import {
   initDatabase,
   getAllPrompts,
@@ -17,8 +19,11 @@ import {
   PromptInput,
   updateTrayMenu,
   copyPromptToClipboard,
-  getPrompt,
-  updatePromptLastUsed,
+  takePendingPrompt,
+  takePendingBundle,
+  previewLinkImport,
+  LinkImportPreview,
+  getMigrationError,
 } from './services/db';
 
 function App() {
@@ -32,6 +37,8 @@ function App() {
   const [searchTerm, setSearchTerm] = useState('');
   const [filterMode, setFilterMode] = useState<'all' | 'favorites'>('all');
   const [selectedTagId, setSelectedTagId] = useState<string | null>(null);
+  const [bundlePath, setBundlePath] = useState<string | null>(null);
+  const [linkImport, setLinkImport] = useState<LinkImportPreview | null>(null);
 
   const filteredPrompts = React.useMemo(() => {
     let result = [...prompts];
@@ -61,6 +68,12 @@ function App() {
       try {
         setIsLoading(true);
         setError(null);
+        // 数据库升级失败时不再读取数据，提示用户升级应用或从备份恢复
+        const migrationError = await getMigrationError();
+        if (migrationError) {
+          setError(migrationError);
+          return;
+        }
         await initDatabase();
         const loadedPrompts = await getAllPrompts();
         setPrompts(loadedPrompts);
@@ -69,34 +82,18 @@ function App() {
         const { listen } = await import('@tauri-apps/api/event');
 
 
-        const unlistenCopyPrompt = await listen('copy-prompt-to-clipboard', async event => {
-          const promptId = event.payload as string;
+        // 托盘已在 Rust 端完成复制，这里只负责自动粘贴
+        const unlistenCopyPrompt = await listen('tray-prompt-copied', async () => {
+          const trayAutoInsert = localStorage.getItem('trayAutoInsert') !== 'false';
+          console.log('自动插入设置:', trayAutoInsert);
+          if (!trayAutoInsert) return;
+          console.log('正在调用 simulate_paste...');
+          const { invoke } = await import('@tauri-apps/api/core');
           try {
-            const prompt = await getPrompt(promptId);
-            if (!prompt) return;
-            const { writeText } = await import('@tauri-apps/plugin-clipboard-manager');
-            await writeText(prompt.content);
-            const trayAutoInsert = localStorage.getItem('trayAutoInsert') !== 'false';
-            console.log('自动插入设置:', trayAutoInsert);
-            if (trayAutoInsert) {
-              console.log('正在调用 simulate_paste...');
-              const { invoke } = await import('@tauri-apps/api/core');
-              try {
-                await invoke('simulate_paste');
-                console.log('simulate_paste 调用完成');
-                toast.success('已自动粘贴到当前输入框');
-              } catch (pasteErr) {
-                console.error('自动粘贴失败:', pasteErr);
-                toast.info('内容已复制到剪贴板，请手动粘贴 (Ctrl+V)');
-              }
-            } else {
-              console.log('自动插入已禁用');
-              toast.info('内容已复制到剪贴板');
-            }
-            await updatePromptLastUsed(promptId);
-          } catch (err) {
-            console.error('复制提示词内容时出错:', err);
-            toast.error('操作失败，请重试');
+            await invoke('simulate_paste');
+            console.log('simulate_paste 调用完成');
+          } catch (pasteErr) {
+            console.error('自动粘贴失败:', pasteErr);
           }
         });
 
@@ -110,6 +107,7 @@ function App() {
             // 刷新提示词列表
             const refreshedPrompts = await getAllPrompts();
             setPrompts(refreshedPrompts);
+            updateTrayMenu().catch(err => console.error('更新托盘菜单失败:', err));
           } catch (err) {
             console.error('处理同步创建事件失败:', err);
             toast.error('同步创建记录失败');
@@ -131,10 +129,64 @@ function App() {
           }
         });
 
+        // 监听 Rust 端对提示词的修改（如恢复历史版本）
+        const unlistenPromptsChanged = await listen('prompts-changed', async () => {
+          try {
+            const refreshedPrompts = await getAllPrompts();
+            setPrompts(refreshedPrompts);
+          } catch (err) {
+            console.error('刷新提示词列表失败:', err);
+          }
+        });
+
+        // promptgenie://prompt/<id> 链接：在编辑器中打开对应的提示词
+        const openPendingPrompt = async () => {
+          const promptId = await takePendingPrompt();
+          if (!promptId) return;
+          const refreshedPrompts = await getAllPrompts();
+          setPrompts(refreshedPrompts);
+          const prompt = refreshedPrompts.find(p => p.id === promptId);
+          if (prompt) {
+            setEditingPrompt(prompt);
+            setIsEditorOpen(true);
+          } else {
+            toast.error('本机没有这条提示词');
+          }
+        };
+        const unlistenOpenPrompt = await listen('open-prompt', () => {
+          openPendingPrompt().catch(err => console.error('打开链接中的提示词失败:', err));
+        });
+        // 应用由链接启动时，事件可能早于监听发出
+        openPendingPrompt().catch(err => console.error('打开链接中的提示词失败:', err));
+
+        // promptgenie://import 链接：预览后确认导入
+        const openLinkImport = async () => {
+          const preview = await previewLinkImport();
+          if (preview) setLinkImport(preview);
+        };
+        const unlistenImportLink = await listen('import-link', () => {
+          openLinkImport().catch(err => console.error('读取链接中的导入内容失败:', err));
+        });
+        openLinkImport().catch(err => console.error('读取链接中的导入内容失败:', err));
+
+        // 双击 .pgenie 分享文件：预览后确认导入
+        const openPendingBundle = async () => {
+          const path = await takePendingBundle();
+          if (path) setBundlePath(path);
+        };
+        const unlistenOpenBundle = await listen('open-bundle', () => {
+          openPendingBundle().catch(err => console.error('打开分享文件失败:', err));
+        });
+        openPendingBundle().catch(err => console.error('打开分享文件失败:', err));
+
         return () => {
           unlistenCopyPrompt();
+          unlistenOpenPrompt();
+          unlistenImportLink();
+          unlistenOpenBundle();
           unlistenSyncCreateLocal();
           unlistenSyncUpdateLocal();
+          unlistenPromptsChanged();
         };
       } catch (err) {
         console.error('App: 加载数据失败:', err);
@@ -333,12 +385,26 @@ function App() {
         />
       )}
       {isSettingsOpen && <Settings isOpen={isSettingsOpen} onClose={closeSettings} />}
+      {bundlePath && (
+        <BundleImportDialog
+          path={bundlePath}
+          onClose={() => setBundlePath(null)}
+          onImported={() => setBundlePath(null)}
+        />
+      )}
+      {linkImport && (
+        <LinkImportDialog
+          preview={linkImport}
+          onClose={() => setLinkImport(null)}
+          onImported={() => setLinkImport(null)}
+        />
+      )}
       <ConfirmDialog
         isOpen={isConfirmOpen}
         onClose={cancelDeletion}
         onConfirm={confirmDeletion}
         title="确认删除"
-        message="确定要删除这个提示词吗？此操作无法撤销。"
+        message="确定要删除这个提示词吗？删除后可在回收站中恢复。"
       />
     </div>
   );
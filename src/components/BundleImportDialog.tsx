@@ -0,0 +1,164 @@
+import React, { useEffect, useState } from 'react';
+import { toast } from 'sonner';
+import {
+  BundlePreview,
+  BundleImportReport,
+  ImportStrategy,
+  previewBundle,
+  importBundle,
+} from '../services/db';
+
+type BundleImportDialogProps = {
+  path: string;
+  onClose: () => void;
+  onImported: (report: BundleImportReport) => void;
+};
+
+const SNIPPET_STATUS_LABELS: Record<BundlePreview['snippets'][number]['status'], string> = {
+  new: '新建',
+  same: '本地已有',
+  conflict: '与本地不同，保留本地',
+};
+
+// 双击 .pgenie 文件或从设置中选择文件后，预览内容并确认导入
+const BundleImportDialog: React.FC<BundleImportDialogProps> = ({ path, onClose, onImported }) => {
+  const [preview, setPreview] = useState<BundlePreview | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [strategy, setStrategy] = useState<ImportStrategy>('newer_wins');
+  const [isImporting, setIsImporting] = useState(false);
+
+  useEffect(() => {
+    setPreview(null);
+    setError(null);
+    previewBundle(path)
+      .then(setPreview)
+      .catch(err => setError(String(err)));
+  }, [path]);
+
+  const handleImport = async () => {
+    if (!preview) return;
+    setIsImporting(true);
+    try {
+      const report = await importBundle(preview.path, strategy);
+      toast.success(
+        `导入完成：新建 ${report.created}，更新 ${report.updated}，另存 ${report.duplicated}，跳过 ${report.skipped}，新建片段 ${report.snippets_created}`
+      );
+      if (report.snippets_kept.length > 0) {
+        toast.warning(`以下片段与本地内容不同，已保留本地版本：${report.snippets_kept.join('、')}`);
+      }
+      onImported(report);
+    } catch (err) {
+      console.error('导入分享文件失败:', err);
+      toast.error(String(err));
+    } finally {
+      setIsImporting(false);
+    }
+  };
+
+  return (
+    <div className="fixed inset-0 bg-black bg-opacity-30 flex items-center justify-center z-50 p-4">
+      <div className="bg-white rounded-xl shadow-xl w-full max-w-lg max-h-[90vh] flex flex-col overflow-hidden">
+        <div className="px-6 py-4 border-b border-gray-200">
+          <h2 className="text-lg font-semibold text-gray-800">导入分享文件</h2>
+          <p className="text-xs text-gray-500 mt-1 break-all">{path}</p>
+        </div>
+
+        <div className="p-6 overflow-y-auto space-y-4">
+          {error && <p className="text-sm text-red-600">{error}</p>}
+          {!error && !preview && <p className="text-sm text-gray-500">正在读取...</p>}
+          {preview && (
+            <>
+              {preview.signed_by_me ? (
+                <p className="text-sm text-green-700">由本机签名，指纹 {preview.signer}</p>
+              ) : preview.signer ? (
+                <p className="text-sm text-amber-600">
+                  签名者指纹 {preview.signer}（未验证）。签名所用的公钥随文件附带，只能说明文件签名后未被修改，
+                  不能证明发送者身份，请通过其他渠道向对方核对指纹
+                </p>
+              ) : (
+                <p className="text-sm text-amber-600">文件未签名，请确认来源可信</p>
+              )}
+
+              <div>
+                <h3 className="text-sm font-medium text-gray-700 mb-2">
+                  提示词（{preview.prompts.length}）
+                </h3>
+                <ul className="space-y-2">
+                  {preview.prompts.map(prompt => (
+                    <li key={prompt.id} className="text-sm border border-gray-200 rounded-lg px-3 py-2">
+                      <div className="font-medium text-gray-800">{prompt.title}</div>
+                      {prompt.tags.length > 0 && (
+                        <div className="text-xs text-gray-500">标签：{prompt.tags.join('、')}</div>
+                      )}
+                      {prompt.variables.length > 0 && (
+                        <div className="text-xs text-gray-500">
+                          变量：{prompt.variables.map(variable => variable.name).join('、')}
+                        </div>
+                      )}
+                    </li>
+                  ))}
+                </ul>
+              </div>
+
+              {preview.snippets.length > 0 && (
+                <div>
+                  <h3 className="text-sm font-medium text-gray-700 mb-2">
+                    引用的片段（{preview.snippets.length}）
+                  </h3>
+                  <ul className="text-sm text-gray-600 space-y-1">
+                    {preview.snippets.map(snippet => (
+                      <li key={snippet.name}>
+                        {snippet.name}
+                        <span className={snippet.status === 'conflict' ? 'text-amber-600' : 'text-gray-400'}>
+                          {' '}· {SNIPPET_STATUS_LABELS[snippet.status]}
+                        </span>
+                      </li>
+                    ))}
+                  </ul>
+                </div>
+              )}
+
+              {preview.existing > 0 && (
+                <div>
+                  <label className="block text-sm text-gray-700 mb-1">
+                    {preview.existing} 条提示词本地已存在，处理方式
+                  </label>
+                  <select
+                    className="w-full px-3 py-2 text-sm border border-gray-300 rounded-lg"
+                    value={strategy}
+                    onChange={e => setStrategy(e.target.value as ImportStrategy)}
+                  >
+                    <option value="newer_wins">保留较新的</option>
+                    <option value="skip">保留本地的</option>
+                    <option value="overwrite">覆盖本地的</option>
+                    <option value="duplicate">另存一份</option>
+                  </select>
+                </div>
+              )}
+            </>
+          )}
+        </div>
+
+        <div className="px-6 py-4 border-t border-gray-200 flex justify-end gap-3">
+          <button
+            type="button"
+            className="px-4 py-2 text-sm font-medium text-gray-700 bg-white hover:bg-gray-50 border border-gray-300 rounded-lg transition-colors"
+            onClick={onClose}
+          >
+            取消
+          </button>
+          <button
+            type="button"
+            className="px-4 py-2 text-sm font-medium text-white bg-blue-600 hover:bg-blue-700 rounded-lg transition-colors disabled:opacity-50"
+            onClick={handleImport}
+            disabled={!preview || isImporting}
+          >
+            {isImporting ? '正在导入...' : '导入'}
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+};
+
+export default BundleImportDialog;
@@ -0,0 +1,92 @@
+import React, { useState } from 'react';
+import { toast } from 'sonner';
+import { ImportReport, LinkImportPreview, cancelLinkImport, confirmLinkImport } from '../services/db';
+
+type LinkImportDialogProps = {
+  preview: LinkImportPreview;
+  onClose: () => void;
+  onImported: (report: ImportReport) => void;
+};
+
+// 通过 promptgenie://import 链接收到提示词后，预览内容并确认导入
+const LinkImportDialog: React.FC<LinkImportDialogProps> = ({ preview, onClose, onImported }) => {
+  const [isImporting, setIsImporting] = useState(false);
+
+  const handleCancel = async () => {
+    try {
+      await cancelLinkImport();
+    } catch (err) {
+      console.error('取消链接导入失败:', err);
+    }
+    onClose();
+  };
+
+  const handleImport = async () => {
+    setIsImporting(true);
+    try {
+      const report = await confirmLinkImport();
+      toast.success(`已导入 ${report.created} 条提示词`);
+      onImported(report);
+    } catch (err) {
+      console.error('导入链接中的提示词失败:', err);
+      toast.error(String(err));
+    } finally {
+      setIsImporting(false);
+    }
+  };
+
+  return (
+    <div className="fixed inset-0 bg-black bg-opacity-30 flex items-center justify-center z-50 p-4">
+      <div className="bg-white rounded-xl shadow-xl w-full max-w-lg max-h-[90vh] flex flex-col overflow-hidden">
+        <div className="px-6 py-4 border-b border-gray-200">
+          <h2 className="text-lg font-semibold text-gray-800">导入链接中的提示词</h2>
+          <p className="text-xs text-gray-500 mt-1 break-all">
+            {preview.source ? `来源：${preview.source}` : '内容编码在链接中'}
+          </p>
+        </div>
+
+        <div className="p-6 overflow-y-auto space-y-4">
+          <p className="text-sm text-amber-600">链接可能来自任意网页，请确认来源可信后再导入</p>
+          <ul className="space-y-2">
+            {preview.prompts.map((prompt, index) => (
+              <li key={index} className="text-sm border border-gray-200 rounded-lg px-3 py-2">
+                <div className="font-medium text-gray-800">{prompt.title}</div>
+                {prompt.tags.length > 0 && (
+                  <div className="text-xs text-gray-500">标签：{prompt.tags.join('、')}</div>
+                )}
+                {prompt.variables.length > 0 && (
+                  <div className="text-xs text-gray-500">
+                    变量：{prompt.variables.map(variable => variable.name).join('、')}
+                  </div>
+                )}
+                <pre className="mt-1 text-xs text-gray-600 whitespace-pre-wrap break-all max-h-32 overflow-y-auto">
+                  {prompt.content}
+                </pre>
+              </li>
+            ))}
+          </ul>
+        </div>
+
+        <div className="px-6 py-4 border-t border-gray-200 flex justify-end gap-3">
+          <button
+            type="button"
+            className="px-4 py-2 text-sm font-medium text-gray-700 bg-white hover:bg-gray-50 border border-gray-300 rounded-lg transition-colors"
+            onClick={handleCancel}
+          >
+            取消
+          </button>
+          <button
+            type="button"
+            className="px-4 py-2 text-sm font-medium text-white bg-blue-600 hover:bg-blue-700 rounded-lg transition-colors disabled:opacity-50"
+            onClick={handleImport}
+            disabled={isImporting}
+          >
+            {isImporting ? '正在导入...' : `导入 ${preview.prompts.length} 条`}
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+};
+
+export default LinkImportDialog;
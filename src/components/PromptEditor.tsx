@@ -1,7 +1,8 @@
 import React, { useState, useEffect, useRef } from 'react';
 // @ts-ignore
-import { XIcon, TagIcon, PlusIcon } from 'lucide-react';
-import { Tag, Prompt, PromptInput, getAllTags } from '../services/db';
+import { XIcon, TagIcon, PlusIcon, LinkIcon } from 'lucide-react';
+import { toast } from 'sonner';
+import { Tag, Prompt, PromptInput, getAllTags, createShareLink, exportBundle } from '../services/db';
 
 type PromptEditorProps = {
   isOpen: boolean;
@@ -115,6 +116,34 @@ const PromptEditor: React.FC<PromptEditorProps> = ({
     setTags(prev => prev.filter(tag => tag.id && tag.id !== tagId));
   };
 
+  // 复制提示词链接：embed 为 false 时只包含 ID，为 true 时包含内容，对方可以直接导入
+  const handleCopyLink = async (embed: boolean) => {
+    if (!promptToEdit?.id) return;
+    try {
+      const link = await createShareLink(promptToEdit.id, embed);
+      const { writeText } = await import('@tauri-apps/plugin-clipboard-manager');
+      await writeText(link);
+      toast.success(embed ? '已复制分享链接' : '已复制链接');
+    } catch (error) {
+      console.error('复制链接失败:', error);
+      toast.error('复制链接失败');
+    }
+  };
+
+  // 导出为 .pgenie 文件，附带引用的片段和本机签名
+  const handleExportBundle = async () => {
+    if (!promptToEdit?.id) return;
+    try {
+      const summary = await exportBundle([promptToEdit.id], true);
+      if (summary) {
+        toast.success(`已导出到 ${summary.path}`);
+      }
+    } catch (error) {
+      console.error('导出分享文件失败:', error);
+      toast.error(String(error));
+    }
+  };
+
   const handleSubmit = (e: React.FormEvent) => {
     e.preventDefault();
     onSave({
@@ -277,6 +306,35 @@ const PromptEditor: React.FC<PromptEditorProps> = ({
         </form>
 
         <div className="px-6 py-4 border-t border-gray-200 flex justify-end gap-3">
+          {promptToEdit?.id && (
+            <div className="mr-auto flex gap-2">
+              <button
+                type="button"
+                className="flex items-center px-3 py-2 text-sm text-gray-600 hover:bg-gray-100 rounded-lg transition-colors"
+                onClick={() => handleCopyLink(false)}
+                title="复制 promptgenie:// 链接，在已同步的设备上打开这条提示词"
+              >
+                <LinkIcon size={16} className="mr-1" />
+                复制链接
+              </button>
+              <button
+                type="button"
+                className="px-3 py-2 text-sm text-gray-600 hover:bg-gray-100 rounded-lg transition-colors"
+                onClick={() => handleCopyLink(true)}
+                title="链接中包含提示词内容，对方点击即可导入"
+              >
+                复制分享链接
+              </button>
+              <button
+                type="button"
+                className="px-3 py-2 text-sm text-gray-600 hover:bg-gray-100 rounded-lg transition-colors"
+                onClick={handleExportBundle}
+                title="导出为 .pgenie 文件发给同事，包含引用的片段和变量定义"
+              >
+                导出 .pgenie
+              </button>
+            </div>
+          )}
           <button
             type="button"
             className="px-4 py-2 text-sm font-medium text-gray-700 bg-white hover:bg-gray-50 border border-gray-300 rounded-lg transition-colors"
@@ -0,0 +1,127 @@
+import React, { useState, useEffect, useRef } from 'react';
+import { SearchIcon, StarIcon } from 'lucide-react';
+import { Toaster, toast } from 'sonner';
+import { searchPrompts, paletteCopyPrompt, PaletteItem } from '../services/db';
+
+// 全局快捷键打开的快捷搜索面板
+const PromptPalette: React.FC = () => {
+  const [query, setQuery] = useState('');
+  const [results, setResults] = useState<PaletteItem[]>([]);
+  const [selectedIndex, setSelectedIndex] = useState(0);
+  const inputRef = useRef<HTMLInputElement>(null);
+  const listRef = useRef<HTMLUListElement>(null);
+
+  const hidePalette = async () => {
+    const { getCurrentWindow } = await import('@tauri-apps/api/window');
+    await getCurrentWindow().hide();
+  };
+
+  // 每次打开时清空搜索；失去焦点时自动隐藏
+  useEffect(() => {
+    const unlisteners: Array<() => void> = [];
+    (async () => {
+      const { listen } = await import('@tauri-apps/api/event');
+      const { getCurrentWindow } = await import('@tauri-apps/api/window');
+      unlisteners.push(
+        await listen('palette-opened', () => {
+          setQuery('');
+          setSelectedIndex(0);
+          inputRef.current?.focus();
+        })
+      );
+      unlisteners.push(
+        await getCurrentWindow().onFocusChanged(({ payload: focused }) => {
+          if (!focused) hidePalette();
+        })
+      );
+    })();
+    return () => unlisteners.forEach(unlisten => unlisten());
+  }, []);
+
+  useEffect(() => {
+    let cancelled = false;
+    searchPrompts(query, 20)
+      .then(items => {
+        if (cancelled) return;
+        setResults(items);
+        setSelectedIndex(0);
+      })
+      .catch(err => console.error('搜索提示词失败:', err));
+    return () => {
+      cancelled = true;
+    };
+  }, [query]);
+
+  useEffect(() => {
+    listRef.current?.children[selectedIndex]?.scrollIntoView({ block: 'nearest' });
+  }, [selectedIndex]);
+
+  const copyItem = async (item: PaletteItem) => {
+    try {
+      await paletteCopyPrompt(item.id);
+    } catch (err) {
+      console.error('复制提示词失败:', err);
+      toast.error(`${err}`);
+    }
+  };
+
+  const handleKeyDown = (e: React.KeyboardEvent) => {
+    if (e.key === 'ArrowDown') {
+      e.preventDefault();
+      setSelectedIndex(index => Math.min(index + 1, results.length - 1));
+    } else if (e.key === 'ArrowUp') {
+      e.preventDefault();
+      setSelectedIndex(index => Math.max(index - 1, 0));
+    } else if (e.key === 'Enter') {
+      e.preventDefault();
+      const item = results[selectedIndex];
+      if (item) copyItem(item);
+    } else if (e.key === 'Escape') {
+      e.preventDefault();
+      hidePalette();
+    }
+  };
+
+  return (
+    <div className="flex h-screen flex-col overflow-hidden rounded-lg border border-gray-300 bg-white font-sans">
+      <Toaster richColors position="top-center" />
+      <div className="flex items-center px-4 py-3 border-b border-gray-200">
+        <SearchIcon size={18} className="text-gray-400 mr-2" />
+        <input
+          ref={inputRef}
+          autoFocus
+          value={query}
+          onChange={e => setQuery(e.target.value)}
+          onKeyDown={handleKeyDown}
+          placeholder="搜索提示词..."
+          className="flex-1 text-base outline-none"
+        />
+      </div>
+      <ul ref={listRef} className="flex-1 overflow-y-auto py-1">
+        {results.length === 0 && <li className="px-4 py-6 text-sm text-center text-gray-400">没有匹配的提示词</li>}
+        {results.map((item, index) => (
+          <li
+            key={item.id}
+            onMouseEnter={() => setSelectedIndex(index)}
+            onClick={() => copyItem(item)}
+            className={`px-4 py-2 cursor-pointer ${index === selectedIndex ? 'bg-blue-50' : ''}`}
+          >
+            <div className="flex items-center">
+              {item.is_favorite && <StarIcon size={14} className="text-yellow-500 mr-1 flex-shrink-0" />}
+              <span className="text-sm font-medium text-gray-800 truncate">{item.title}</span>
+              {item.tags.map(tag => (
+                <span key={tag} className="ml-2 px-1.5 text-xs text-gray-500 bg-gray-100 rounded flex-shrink-0">
+                  {tag}
+                </span>
+              ))}
+            </div>
+            <p className="text-xs text-gray-500 truncate">{item.preview}</p>
+          </li>
+        ))}
+      </ul>
+      <div className="px-4 py-1.5 text-xs text-gray-400 border-t border-gray-200">↑↓ 选择 · Enter 复制 · Esc 关闭</div>
+    </div>
+  );
+};
+
+export default PromptPalette;
@@ -1,7 +1,59 @@
 import React, { useState, useEffect } from 'react';
-import { XIcon, CloudIcon, TestTubeIcon, SaveIcon, AlertCircleIcon, CheckCircleIcon, InfoIcon } from 'lucide-react';
+import { XIcon, CloudIcon, TestTubeIcon, SaveIcon, AlertCircleIcon, CheckCircleIcon, InfoIcon, SearchIcon, ServerIcon, DatabaseIcon, FolderSyncIcon, ArchiveIcon } from 'lucide-react';
 import { invoke } from '@tauri-apps/api/core';
 import { toast } from 'sonner';
+import BundleImportDialog from './BundleImportDialog';
+import ConfirmDialog from './ConfirmDialog';
+import {
+  getAppSettings,
+  saveAppSettings,
+  regenerateApiToken,
+  exportLibrary,
+  importLibrary,
+  exportMarkdown,
+  importMarkdown,
+  syncMarkdownFolder,
+  pickSyncFolder,
+  exportCsv,
+  previewCsv,
+  importCsv,
+  listImporters,
+  previewImport,
+  confirmImport,
+  getAllPrompts,
+  exportBundle,
+  previewBundle,
+  getBundleFingerprint,
+  listBackups,
+  createBackup,
+  verifyBackup,
+  restoreBackup,
+  checkDatabase,
+  repairDatabase,
+  DatabaseCheck,
+  AppSettings,
+  BackupInfo,
+  BackupReason,
+  CsvEncoding,
+  CsvMapping,
+  CsvPreview,
+  ImporterInfo,
+  ImportPreview,
+  ImportReport,
+  ImportStrategy,
+  Prompt,
+} from '../services/db';
+
+const BACKUP_REASON_LABELS: Record<BackupReason, string> = {
+  startup: '启动时',
+  daily: '每日',
+  sync: '同步前',
+  import: '导入前',
+  manual: '手动',
+  pre_restore: '恢复前',
+  pre_migration: '升级前',
+  pre_repair: '修复前',
+};
 
 // 定义组件Props
 interface SettingsProps {
@@ -39,6 +91,36 @@ const Settings: React.FC<SettingsProps> = ({ isOpen, onClose }) => {
   const [isSavingConfig, setIsSavingConfig] = useState(false);
   const [connectionStatus, setConnectionStatus] = useState<'unknown' | 'testing' | 'success' | 'failed'>('unknown');
 
+  // 应用设置（快捷搜索面板等）
+  const [appSettings, setAppSettings] = useState<AppSettings | null>(null);
+  const [isSavingAppSettings, setIsSavingAppSettings] = useState(false);
+
+  // 提示词库导入导出
+  const [exportIncludeTrash, setExportIncludeTrash] = useState(false);
+  const [markdownPrune, setMarkdownPrune] = useState(true);
+  const [importStrategy, setImportStrategy] = useState<ImportStrategy>('newer_wins');
+  const [importReport, setImportReport] = useState<ImportReport | null>(null);
+  const [isTransferring, setIsTransferring] = useState(false);
+  const [isFolderSyncing, setIsFolderSyncing] = useState(false);
+  const [csvEncoding, setCsvEncoding] = useState<CsvEncoding>('utf-8');
+  const [csvBom, setCsvBom] = useState(true);
+  const [csvPreview, setCsvPreview] = useState<CsvPreview | null>(null);
+  const [importers, setImporters] = useState<ImporterInfo[]>([]);
+  const [importFormat, setImportFormat] = useState<string>('');
+  const [importPreview, setImportPreview] = useState<ImportPreview | null>(null);
+  const [bundleCandidates, setBundleCandidates] = useState<Prompt[] | null>(null);
+  const [bundleSelection, setBundleSelection] = useState<string[]>([]);
+  const [bundleSign, setBundleSign] = useState(true);
+  const [bundleFingerprint, setBundleFingerprint] = useState<string | null>(null);
+  const [bundlePath, setBundlePath] = useState<string | null>(null);
+
+  // 数据库备份
+  const [backups, setBackups] = useState<BackupInfo[]>([]);
+  const [isBackingUp, setIsBackingUp] = useState(false);
+  const [backupToRestore, setBackupToRestore] = useState<BackupInfo | null>(null);
+  const [databaseCheck, setDatabaseCheck] = useState<DatabaseCheck | null>(null);
+  const [isCheckingDatabase, setIsCheckingDatabase] = useState(false);
+
   // 加载设置
   useEffect(() => {
     const loadSettings = async () => {
@@ -65,6 +147,27 @@ const Settings: React.FC<SettingsProps> = ({ isOpen, onClose }) => {
         } catch (err) {
           console.error('加载飞书配置失败:', err);
         }
+
+        // 加载应用设置
+        try {
+          setAppSettings(await getAppSettings());
+        } catch (err) {
+          console.error('加载应用设置失败:', err);
+        }
+
+        // 加载备份列表
+        try {
+          setBackups(await listBackups());
+        } catch (err) {
+          console.error('加载备份列表失败:', err);
+        }
+
+        // 加载可导入的格式
+        try {
+          setImporters(await listImporters());
+        } catch (err) {
+          console.error('加载导入格式失败:', err);
+        }
       } catch (err) {
         console.error('加载设置失败:', err);
       }
@@ -91,6 +194,337 @@ const Settings: React.FC<SettingsProps> = ({ isOpen, onClose }) => {
     }
   };
 
+  // 保存应用设置，保存后立即生效（重新注册快捷键、刷新托盘菜单）
+  const saveAppSettingsSection = async (successMessage: string) => {
+    if (!appSettings) return;
+    setIsSavingAppSettings(true);
+    try {
+      await saveAppSettings(appSettings);
+      toast.success(successMessage);
+    } catch (err) {
+      console.error('保存应用设置失败:', err);
+      toast.error(`保存失败: ${err}`);
+    } finally {
+      setIsSavingAppSettings(false);
+    }
+  };
+
+  const copyApiToken = async () => {
+    if (!appSettings) return;
+    try {
+      const { writeText } = await import('@tauri-apps/plugin-clipboard-manager');
+      await writeText(appSettings.api.token);
+      toast.success('访问令牌已复制');
+    } catch (err) {
+      console.error('复制访问令牌失败:', err);
+      toast.error('复制失败');
+    }
+  };
+
+  // 重新生成后旧令牌立即失效
+  const handleRegenerateApiToken = async () => {
+    if (!appSettings) return;
+    try {
+      const token = await regenerateApiToken();
+      setAppSettings({ ...appSettings, api: { ...appSettings.api, token } });
+      toast.success('已生成新的访问令牌');
+    } catch (err) {
+      console.error('重新生成访问令牌失败:', err);
+      toast.error(`重新生成失败: ${err}`);
+    }
+  };
+
+  const handleExportLibrary = async () => {
+    setIsTransferring(true);
+    try {
+      const summary = await exportLibrary(exportIncludeTrash);
+      if (summary) {
+        toast.success(`已导出 ${summary.prompts} 条提示词到 ${summary.path}`);
+      }
+    } catch (err) {
+      console.error('导出提示词库失败:', err);
+      toast.error(`${err}`);
+    } finally {
+      setIsTransferring(false);
+    }
+  };
+
+  const handleExportMarkdown = async () => {
+    setIsTransferring(true);
+    try {
+      const summary = await exportMarkdown(markdownPrune);
+      if (summary) {
+        toast.success(`已导出到 ${summary.path}：写入 ${summary.written} 个文件，删除 ${summary.removed} 个`);
+      }
+    } catch (err) {
+      console.error('导出 Markdown 失败:', err);
+      toast.error(`${err}`);
+    } finally {
+      setIsTransferring(false);
+    }
+  };
+
+  const handleImportLibrary = async (format: 'json' | 'markdown') => {
+    setIsTransferring(true);
+    try {
+      const report = format === 'json' ? await importLibrary(importStrategy) : await importMarkdown(importStrategy);
+      if (report) {
+        setImportReport(report);
+        toast.success(`导入完成：新建 ${report.created}，更新 ${report.updated}，另存 ${report.duplicated}，跳过 ${report.skipped}`);
+      }
+    } catch (err) {
+      console.error('导入提示词库失败:', err);
+      toast.error(`${err}`);
+    } finally {
+      setIsTransferring(false);
+    }
+  };
+
+  const handleExportCsv = async () => {
+    setIsTransferring(true);
+    try {
+      const summary = await exportCsv(csvEncoding, csvBom);
+      if (summary) {
+        toast.success(`已导出 ${summary.prompts} 条提示词到 ${summary.path}`);
+        if (summary.unmappable) {
+          toast.warning('部分字符无法用 GBK 表示，建议改用 UTF-8 导出');
+        }
+      }
+    } catch (err) {
+      console.error('导出 CSV 失败:', err);
+      toast.error(`${err}`);
+    } finally {
+      setIsTransferring(false);
+    }
+  };
+
+  // 选择文件或修改编码、列映射后重新预览
+  const handlePreviewCsv = async (path: string | null, encoding: CsvEncoding, mapping: CsvMapping | null) => {
+    setIsTransferring(true);
+    try {
+      const preview = await previewCsv(path, encoding, mapping);
+      if (preview) {
+        setCsvPreview(preview);
+      }
+    } catch (err) {
+      console.error('预览 CSV 失败:', err);
+      toast.error(`${err}`);
+    } finally {
+      setIsTransferring(false);
+    }
+  };
+
+  const updateCsvMapping = (key: keyof CsvMapping, value: string) => {
+    if (!csvPreview) return;
+    const mapping = { ...csvPreview.mapping, [key]: key === 'content' || key === 'tag_delimiter' ? value : value || null };
+    handlePreviewCsv(csvPreview.path, csvPreview.encoding, mapping);
+  };
+
+  const handleImportCsv = async () => {
+    if (!csvPreview) return;
+    setIsTransferring(true);
+    try {
+      const report = await importCsv(csvPreview.path, csvPreview.encoding, csvPreview.mapping, importStrategy);
+      setImportReport(report);
+      setCsvPreview(null);
+      toast.success(`导入完成：新建 ${report.created}，更新 ${report.updated}，另存 ${report.duplicated}，跳过 ${report.skipped}`);
+    } catch (err) {
+      console.error('导入 CSV 失败:', err);
+      toast.error(`${err}`);
+    } finally {
+      setIsTransferring(false);
+    }
+  };
+
+  const handlePreviewImport = async () => {
+    setIsTransferring(true);
+    try {
+      const preview = await previewImport(importFormat || null);
+      if (preview) {
+        setImportPreview(preview);
+      }
+    } catch (err) {
+      console.error('预览导入文件失败:', err);
+      toast.error(`${err}`);
+    } finally {
+      setIsTransferring(false);
+    }
+  };
+
+  const handleConfirmImport = async () => {
+    if (!importPreview) return;
+    setIsTransferring(true);
+    try {
+      const paths = importPreview.sources.map(source => source.path);
+      const report = await confirmImport(paths, importFormat || null, importStrategy);
+      setImportReport(report);
+      setImportPreview(null);
+      toast.success(`导入完成：新建 ${report.created}，更新 ${report.updated}，另存 ${report.duplicated}，跳过 ${report.skipped}`);
+    } catch (err) {
+      console.error('导入失败:', err);
+      toast.error(`${err}`);
+    } finally {
+      setIsTransferring(false);
+    }
+  };
+
+  // 选择要放进 .pgenie 文件的提示词
+  const handleStartBundleExport = async () => {
+    try {
+      setBundleCandidates(await getAllPrompts());
+      setBundleSelection([]);
+      setBundleFingerprint(await getBundleFingerprint());
+    } catch (err) {
+      console.error('加载提示词失败:', err);
+      toast.error(`${err}`);
+    }
+  };
+
+  const toggleBundleSelection = (id: string) => {
+    setBundleSelection(prev => (prev.includes(id) ? prev.filter(item => item !== id) : [...prev, id]));
+  };
+
+  const handleExportBundle = async () => {
+    setIsTransferring(true);
+    try {
+      const summary = await exportBundle(bundleSelection, bundleSign);
+      if (summary) {
+        toast.success(`已导出 ${summary.prompts} 条提示词和 ${summary.snippets} 个片段到 ${summary.path}`);
+        setBundleCandidates(null);
+      }
+    } catch (err) {
+      console.error('导出分享文件失败:', err);
+      toast.error(`${err}`);
+    } finally {
+      setIsTransferring(false);
+    }
+  };
+
+  const handleOpenBundle = async () => {
+    try {
+      const preview = await previewBundle();
+      if (preview) {
+        setBundlePath(preview.path);
+      }
+    } catch (err) {
+      console.error('读取分享文件失败:', err);
+      toast.error(`${err}`);
+    }
+  };
+
+  const handleCreateBackup = async () => {
+    setIsBackingUp(true);
+    try {
+      const backup = await createBackup();
+      toast.success(`已备份到 ${backup.file_name}`);
+      setBackups(await listBackups());
+    } catch (err) {
+      console.error('备份失败:', err);
+      toast.error(`${err}`);
+    } finally {
+      setIsBackingUp(false);
+    }
+  };
+
+  const handleVerifyBackup = async (backup: BackupInfo) => {
+    try {
+      const result = await verifyBackup(backup.file_name);
+      if (result.ok) {
+        toast.success(`备份完整，包含 ${result.prompts ?? 0} 条提示词`);
+      } else {
+        toast.error(`备份已损坏：${result.problems.join('；')}`);
+      }
+    } catch (err) {
+      console.error('校验备份失败:', err);
+      toast.error(`${err}`);
+    }
+  };
+
+  const handleRestoreBackup = async () => {
+    if (!backupToRestore) return;
+    const backup = backupToRestore;
+    setBackupToRestore(null);
+    setIsBackingUp(true);
+    try {
+      await restoreBackup(backup.file_name);
+      toast.success('已恢复备份，恢复前的数据也已备份');
+      setBackups(await listBackups());
+    } catch (err) {
+      console.error('恢复备份失败:', err);
+      toast.error(`${err}`);
+    } finally {
+      setIsBackingUp(false);
+    }
+  };
+
+  const handleCheckDatabase = async () => {
+    setIsCheckingDatabase(true);
+    try {
+      const result = await checkDatabase();
+      setDatabaseCheck(result);
+      if (result.ok) {
+        toast.success('数据库没有发现问题');
+      }
+    } catch (err) {
+      console.error('检查数据库失败:', err);
+      toast.error(`${err}`);
+    } finally {
+      setIsCheckingDatabase(false);
+    }
+  };
+
+  const handleRepairDatabase = async () => {
+    setIsCheckingDatabase(true);
+    try {
+      const report = await repairDatabase();
+      setDatabaseCheck({ ok: report.remaining.length === 0, issues: report.remaining });
+      if (report.repaired.length > 0) {
+        toast.success(`已修复：${report.repaired.join('；')}`);
+      }
+      if (report.remaining.length > 0) {
+        toast.warning(`仍有 ${report.remaining.length} 个问题需要手动处理`);
+      }
+      setBackups(await listBackups());
+    } catch (err) {
+      console.error('修复数据库失败:', err);
+      toast.error(`${err}`);
+    } finally {
+      setIsCheckingDatabase(false);
+    }
+  };
+
+  const handlePickSyncFolder = async () => {
+    if (!appSettings) return;
+    const path = await pickSyncFolder();
+    if (path) {
+      setAppSettings({ ...appSettings, folder_sync: { ...appSettings.folder_sync, path } });
+    }
+  };
+
+  // 先保存设置，确保按页面上的目录同步
+  const handleSyncFolder = async () => {
+    if (!appSettings) return;
+    setIsFolderSyncing(true);
+    try {
+      await saveAppSettings(appSettings);
+      const result = await syncMarkdownFolder();
+      toast.success(
+        `同步完成：本地新增 ${result.local_created}，更新 ${result.local_updated}，移入回收站 ${result.local_deleted}；` +
+          `写入文件 ${result.files_written}，删除文件 ${result.files_removed}`
+      );
+    } catch (err) {
+      console.error('目录同步失败:', err);
+      toast.error(`${err}`);
+    } finally {
+      setIsFolderSyncing(false);
+    }
+  };
+
+  const updateTraySetting = (key: keyof AppSettings['tray'], value: number | boolean) => {
+    if (!appSettings) return;
+    setAppSettings({ ...appSettings, tray: { ...appSettings.tray, [key]: value } });
+  };
+
   // 保存飞书配置 - 提取为可复用函数
   const saveFeishuConfigInternal = async (showToast = true) => {
     if (!feishuConfig.app_id.trim() || !feishuConfig.app_secret.trim() || !feishuConfig.base_url.trim()) {
@@ -332,6 +766,717 @@ const Settings: React.FC<SettingsProps> = ({ isOpen, onClose }) => {
             </div>
           </div>
 
+          {/* 快捷搜索设置 */}
+          {appSettings && (
+            <div>
+              <div className="flex items-center mb-2">
+                <SearchIcon size={20} className="text-blue-600 mr-2" />
+                <h3 className="text-lg font-medium text-gray-800">快捷搜索</h3>
+              </div>
+
+              <div className="flex items-center justify-between py-3">
+                <div>
+                  <p className="text-gray-800">启用全局快捷键</p>
+                  <p className="text-sm text-gray-500">在任意应用中按下快捷键打开搜索面板，回车复制提示词</p>
+                </div>
+                <input
+                  type="checkbox"
+                  checked={appSettings.palette.enabled}
+                  onChange={(e) =>
+                    setAppSettings({ ...appSettings, palette: { ...appSettings.palette, enabled: e.target.checked } })
+                  }
+                  className="w-4 h-4"
+                />
+              </div>
+
+              <div className="flex items-center gap-3">
+                <input
+                  type="text"
+                  value={appSettings.palette.shortcut}
+                  onChange={(e) =>
+                    setAppSettings({ ...appSettings, palette: { ...appSettings.palette, shortcut: e.target.value } })
+                  }
+                  placeholder="CommandOrControl+Shift+Space"
+                  className="flex-1 px-3 py-2 border border-gray-300 rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                />
+                <button
+                  onClick={() => saveAppSettingsSection('快捷搜索设置已保存')}
+                  disabled={isSavingAppSettings}
+                  className="flex items-center px-4 py-2 bg-blue-600 hover:bg-blue-700 disabled:bg-blue-400 text-white rounded-lg transition-colors"
+                >
+                  <SaveIcon size={16} className="mr-2" />
+                  {isSavingAppSettings ? '保存中...' : '保存'}
+                </button>
+              </div>
+              <p className="text-xs text-gray-500 mt-1">
+                格式如 CommandOrControl+Shift+Space、Alt+P，修饰键之间用 + 连接
+              </p>
+            </div>
+          )}
+
+          {/* 本地 API 设置 */}
+          {appSettings && (
+            <div>
+              <div className="flex items-center mb-2">
+                <ServerIcon size={20} className="text-blue-600 mr-2" />
+                <h3 className="text-lg font-medium text-gray-800">本地 API</h3>
+              </div>
+
+              <div className="flex items-center justify-between py-3">
+                <div>
+                  <p className="text-gray-800">启用本地 HTTP API</p>
+                  <p className="text-sm text-gray-500">
+                    仅监听 127.0.0.1，供编辑器和脚本读取、渲染、新建提示词，请求需携带访问令牌
+                  </p>
+                </div>
+                <input
+                  type="checkbox"
+                  checked={appSettings.api.enabled}
+                  onChange={(e) =>
+                    setAppSettings({ ...appSettings, api: { ...appSettings.api, enabled: e.target.checked } })
+                  }
+                  className="w-4 h-4"
+                />
+              </div>
+
+              <div className="space-y-3">
+                <label className="block text-sm text-gray-700">
+                  端口
+                  <input
+                    type="number"
+                    min={1024}
+                    max={65535}
+                    value={appSettings.api.port}
+                    onChange={(e) =>
+                      setAppSettings({
+                        ...appSettings,
+                        api: { ...appSettings.api, port: Math.min(65535, Math.max(0, parseInt(e.target.value, 10) || 0)) },
+                      })
+                    }
+                    className="mt-1 w-full px-3 py-2 border border-gray-300 rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                  />
+                </label>
+                <label className="block text-sm text-gray-700">
+                  访问令牌
+                  <div className="mt-1 flex items-center gap-2">
+                    <input
+                      type="text"
+                      readOnly
+                      value={appSettings.api.token}
+                      className="flex-1 px-3 py-2 border border-gray-300 rounded-lg font-mono text-sm bg-gray-50"
+                    />
+                    <button
+                      onClick={copyApiToken}
+                      className="px-3 py-2 border border-gray-300 hover:bg-gray-50 text-sm rounded-lg transition-colors"
+                    >
+                      复制
+                    </button>
+                    <button
+                      onClick={handleRegenerateApiToken}
+                      className="px-3 py-2 border border-gray-300 hover:bg-gray-50 text-sm rounded-lg transition-colors"
+                    >
+                      重新生成
+                    </button>
+                  </div>
+                </label>
+                <div className="flex items-center justify-between">
+                  <p className="text-xs text-gray-500">
+                    请求头 Authorization: Bearer &lt;令牌&gt;，接口地址 http://127.0.0.1:{appSettings.api.port}/api，MCP 地址 http://127.0.0.1:{appSettings.api.port}/mcp
+                  </p>
+                  <button
+                    onClick={() => saveAppSettingsSection('本地 API 设置已保存')}
+                    disabled={isSavingAppSettings}
+                    className="flex items-center px-4 py-2 bg-blue-600 hover:bg-blue-700 disabled:bg-blue-400 text-white rounded-lg transition-colors"
+                  >
+                    <SaveIcon size={16} className="mr-2" />
+                    {isSavingAppSettings ? '保存中...' : '保存'}
+                  </button>
+                </div>
+              </div>
+            </div>
+          )}
+
+          {/* Markdown 目录同步 */}
+          {appSettings && (
+            <div>
+              <div className="flex items-center mb-2">
+                <FolderSyncIcon size={20} className="text-blue-600 mr-2" />
+                <h3 className="text-lg font-medium text-gray-800">Markdown 目录同步</h3>
+              </div>
+
+              <div className="flex items-center justify-between py-3">
+                <div>
+                  <p className="text-gray-800">自动双向同步</p>
+                  <p className="text-sm text-gray-500">
+                    把提示词库镜像为目录中的 .md 文件，在编辑器、git 或 Obsidian 中的修改会自动同步回来
+                  </p>
+                </div>
+                <input
+                  type="checkbox"
+                  checked={appSettings.folder_sync.enabled}
+                  onChange={(e) =>
+                    setAppSettings({
+                      ...appSettings,
+                      folder_sync: { ...appSettings.folder_sync, enabled: e.target.checked },
+                    })
+                  }
+                  className="w-4 h-4"
+                />
+              </div>
+
+              <div className="space-y-3">
+                <label className="block text-sm text-gray-700">
+                  同步目录
+                  <div className="mt-1 flex items-center gap-2">
+                    <input
+                      type="text"
+                      value={appSettings.folder_sync.path}
+                      onChange={(e) =>
+                        setAppSettings({
+                          ...appSettings,
+                          folder_sync: { ...appSettings.folder_sync, path: e.target.value },
+                        })
+                      }
+                      placeholder="建议使用专用目录，例如 Obsidian 库中的 Prompts 子目录"
+                      className="flex-1 px-3 py-2 border border-gray-300 rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                    />
+                    <button
+                      onClick={handlePickSyncFolder}
+                      className="px-3 py-2 border border-gray-300 hover:bg-gray-50 text-sm rounded-lg transition-colors"
+                    >
+                      选择
+                    </button>
+                  </div>
+                </label>
+                <div className="flex items-center justify-between">
+                  <p className="text-xs text-gray-500">
+                    目录中的文件被删除时，对应的提示词会移入回收站；本地删除的提示词也会删除对应的文件
+                  </p>
+                  <div className="flex items-center gap-2">
+                    <button
+                      onClick={handleSyncFolder}
+                      disabled={isFolderSyncing || !appSettings.folder_sync.path.trim()}
+                      className="px-3 py-2 border border-gray-300 hover:bg-gray-50 disabled:opacity-50 text-sm rounded-lg transition-colors"
+                    >
+                      {isFolderSyncing ? '同步中...' : '立即同步'}
+                    </button>
+                    <button
+                      onClick={() => saveAppSettingsSection('目录同步设置已保存')}
+                      disabled={isSavingAppSettings}
+                      className="flex items-center px-4 py-2 bg-blue-600 hover:bg-blue-700 disabled:bg-blue-400 text-white rounded-lg transition-colors"
+                    >
+                      <SaveIcon size={16} className="mr-2" />
+                      {isSavingAppSettings ? '保存中...' : '保存'}
+                    </button>
+                  </div>
+                </div>
+              </div>
+            </div>
+          )}
+
+          {/* 数据库备份 */}
+          {appSettings && (
+            <div>
+              <div className="flex items-center mb-2">
+                <ArchiveIcon size={20} className="text-blue-600 mr-2" />
+                <h3 className="text-lg font-medium text-gray-800">数据库备份</h3>
+              </div>
+
+              <div className="flex items-center justify-between py-3">
+                <div>
+                  <p className="text-gray-800">自动备份</p>
+                  <p className="text-sm text-gray-500">启动时、每天以及同步和导入前备份数据库</p>
+                </div>
+                <input
+                  type="checkbox"
+                  checked={appSettings.backup.enabled}
+                  onChange={(e) =>
+                    setAppSettings({ ...appSettings, backup: { ...appSettings.backup, enabled: e.target.checked } })
+                  }
+                  className="w-4 h-4"
+                />
+              </div>
+
+              <div className="space-y-3">
+                <div className="grid grid-cols-3 gap-3">
+                  {([
+                    ['keep_recent', '保留最近的份数'],
+                    ['keep_daily', '每天一份的天数'],
+                    ['keep_weekly', '每周一份的周数'],
+                  ] as const).map(([key, label]) => (
+                    <label key={key} className="block text-sm text-gray-700">
+                      {label}
+                      <input
+                        type="number"
+                        min={0}
+                        value={appSettings.backup[key]}
+                        onChange={(e) =>
+                          setAppSettings({
+                            ...appSettings,
+                            backup: { ...appSettings.backup, [key]: Math.max(0, parseInt(e.target.value, 10) || 0) },
+                          })
+                        }
+                        className="mt-1 w-full px-3 py-2 border border-gray-300 rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                      />
+                    </label>
+                  ))}
+                </div>
+
+                <ul className="max-h-48 overflow-y-auto divide-y divide-gray-100 border border-gray-200 rounded-lg">
+                  {backups.length === 0 && <li className="px-3 py-2 text-sm text-gray-500">还没有备份</li>}
+                  {backups.map(backup => (
+                    <li key={backup.file_name} className="px-3 py-2 flex items-center justify-between text-sm">
+                      <span className="text-gray-800">
+                        {new Date(backup.created_at).toLocaleString()}
+                        <span className="ml-2 text-xs text-gray-500">
+                          {BACKUP_REASON_LABELS[backup.reason]} · {(backup.size / 1024).toFixed(0)} KB
+                        </span>
+                      </span>
+                      <span className="flex items-center gap-2">
+                        <button
+                          onClick={() => handleVerifyBackup(backup)}
+                          className="px-2 py-1 text-xs border border-gray-300 hover:bg-gray-50 rounded"
+                        >
+                          校验
+                        </button>
+                        <button
+                          onClick={() => setBackupToRestore(backup)}
+                          disabled={isBackingUp}
+                          className="px-2 py-1 text-xs border border-gray-300 hover:bg-gray-50 disabled:opacity-50 rounded"
+                        >
+                          恢复
+                        </button>
+                      </span>
+                    </li>
+                  ))}
+                </ul>
+
+                <div className="flex items-center justify-end gap-2">
+                  <button
+                    onClick={handleCreateBackup}
+                    disabled={isBackingUp}
+                    className="px-3 py-2 border border-gray-300 hover:bg-gray-50 disabled:opacity-50 text-sm rounded-lg transition-colors"
+                  >
+                    立即备份
+                  </button>
+                  <button
+                    onClick={() => saveAppSettingsSection('备份设置已保存')}
+                    disabled={isSavingAppSettings}
+                    className="flex items-center px-4 py-2 bg-blue-600 hover:bg-blue-700 disabled:bg-blue-400 text-white rounded-lg transition-colors"
+                  >
+                    <SaveIcon size={16} className="mr-2" />
+                    {isSavingAppSettings ? '保存中...' : '保存'}
+                  </button>
+                </div>
+
+                <div className="flex items-center justify-between pt-3 border-t border-gray-100">
+                  <div>
+                    <p className="text-gray-800">检查数据库</p>
+                    <p className="text-sm text-gray-500">完整性、外键以及重复标签、时间格式、空标题等问题，修复前会先备份</p>
+                  </div>
+                  <span className="flex items-center gap-2">
+                    <button
+                      onClick={handleCheckDatabase}
+                      disabled={isCheckingDatabase}
+                      className="px-3 py-2 border border-gray-300 hover:bg-gray-50 disabled:opacity-50 text-sm rounded-lg transition-colors"
+                    >
+                      检查
+                    </button>
+                    <button
+                      onClick={handleRepairDatabase}
+                      disabled={isCheckingDatabase || !databaseCheck?.issues.some(issue => issue.repairable)}
+                      className="px-3 py-2 border border-gray-300 hover:bg-gray-50 disabled:opacity-50 text-sm rounded-lg transition-colors"
+                    >
+                      修复
+                    </button>
+                  </span>
+                </div>
+                {databaseCheck && !databaseCheck.ok && (
+                  <ul className="max-h-48 overflow-y-auto space-y-1 text-sm">
+                    {databaseCheck.issues.map((issue, index) => (
+                      <li key={index} className={issue.repairable ? 'text-amber-600' : 'text-red-600'}>
+                        {issue.message}
+                        {issue.repairable ? '（可修复）' : '（需手动处理）'}
+                      </li>
+                    ))}
+                  </ul>
+                )}
+              </div>
+
+              <ConfirmDialog
+                isOpen={backupToRestore !== null}
+                onClose={() => setBackupToRestore(null)}
+                onConfirm={handleRestoreBackup}
+                title="恢复备份"
+                message={`用 ${backupToRestore ? new Date(backupToRestore.created_at).toLocaleString() : ''} 的备份覆盖当前数据库？恢复前会先备份当前数据。`}
+                confirmText="恢复"
+              />
+            </div>
+          )}
+
+          {/* 导入导出 */}
+          <div>
+            <div className="flex items-center mb-2">
+              <DatabaseIcon size={20} className="text-blue-600 mr-2" />
+              <h3 className="text-lg font-medium text-gray-800">导入导出</h3>
+            </div>
+
+            <div className="flex items-center justify-between py-3">
+              <div>
+                <p className="text-gray-800">导出提示词库</p>
+                <p className="text-sm text-gray-500">包含提示词、标签、收藏和时间，保存为 JSON 文件</p>
+              </div>
+              <div className="flex items-center gap-3">
+                <label className="flex items-center text-sm text-gray-700">
+                  <input
+                    type="checkbox"
+                    checked={exportIncludeTrash}
+                    onChange={(e) => setExportIncludeTrash(e.target.checked)}
+                    className="w-4 h-4 mr-2"
+                  />
+                  包含回收站
+                </label>
+                <button
+                  onClick={handleExportLibrary}
+                  disabled={isTransferring}
+                  className="px-3 py-2 border border-gray-300 hover:bg-gray-50 disabled:opacity-50 text-sm rounded-lg transition-colors"
+                >
+                  导出
+                </button>
+              </div>
+            </div>
+
+            <div className="flex items-center justify-between py-3">
+              <div>
+                <p className="text-gray-800">导出为 Markdown 目录</p>
+                <p className="text-sm text-gray-500">每条提示词一个 .md 文件，适合放进 git 仓库；再次导出时沿用原来的文件名</p>
+              </div>
+              <div className="flex items-center gap-3">
+                <label className="flex items-center text-sm text-gray-700">
+                  <input
+                    type="checkbox"
+                    checked={markdownPrune}
+                    onChange={(e) => setMarkdownPrune(e.target.checked)}
+                    className="w-4 h-4 mr-2"
+                  />
+                  删除已不存在的
+                </label>
+                <button
+                  onClick={handleExportMarkdown}
+                  disabled={isTransferring}
+                  className="px-3 py-2 border border-gray-300 hover:bg-gray-50 disabled:opacity-50 text-sm rounded-lg transition-colors"
+                >
+                  导出
+                </button>
+              </div>
+            </div>
+
+            <div className="flex items-center justify-between py-3">
+              <div>
+                <p className="text-gray-800">导出为 CSV</p>
+                <p className="text-sm text-gray-500">可以用 Excel 等表格软件编辑，修改后再导入；标签以逗号分隔</p>
+              </div>
+              <div className="flex items-center gap-3">
+                <select
+                  value={csvEncoding}
+                  onChange={(e) => setCsvEncoding(e.target.value as CsvEncoding)}
+                  className="px-3 py-2 border border-gray-300 rounded-lg text-sm"
+                >
+                  <option value="utf-8">UTF-8</option>
+                  <option value="gbk">GBK</option>
+                </select>
+                {csvEncoding === 'utf-8' && (
+                  <label className="flex items-center text-sm text-gray-700">
+                    <input
+                      type="checkbox"
+                      checked={csvBom}
+                      onChange={(e) => setCsvBom(e.target.checked)}
+                      className="w-4 h-4 mr-2"
+                    />
+                    BOM
+                  </label>
+                )}
+                <button
+                  onClick={handleExportCsv}
+                  disabled={isTransferring}
+                  className="px-3 py-2 border border-gray-300 hover:bg-gray-50 disabled:opacity-50 text-sm rounded-lg transition-colors"
+                >
+                  导出
+                </button>
+              </div>
+            </div>
+
+            <div className="flex items-center justify-between py-3">
+              <div>
+                <p className="text-gray-800">导入提示词库</p>
+                <p className="text-sm text-gray-500">ID 相同的提示词按所选方式处理，同名标签会合并</p>
+              </div>
+              <div className="flex items-center gap-3">
+                <select
+                  value={importStrategy}
+                  onChange={(e) => setImportStrategy(e.target.value as ImportStrategy)}
+                  className="px-3 py-2 border border-gray-300 rounded-lg text-sm"
+                >
+                  <option value="newer_wins">保留较新的</option>
+                  <option value="skip">跳过</option>
+                  <option value="overwrite">覆盖本地</option>
+                  <option value="duplicate">另存一份</option>
+                </select>
+                <button
+                  onClick={() => handleImportLibrary('json')}
+                  disabled={isTransferring}
+                  className="px-3 py-2 border border-gray-300 hover:bg-gray-50 disabled:opacity-50 text-sm rounded-lg transition-colors"
+                >
+                  导入 JSON
+                </button>
+                <button
+                  onClick={() => handleImportLibrary('markdown')}
+                  disabled={isTransferring}
+                  className="px-3 py-2 border border-gray-300 hover:bg-gray-50 disabled:opacity-50 text-sm rounded-lg transition-colors"
+                >
+                  导入 Markdown
+                </button>
+                <button
+                  onClick={() => handlePreviewCsv(null, 'auto', null)}
+                  disabled={isTransferring}
+                  className="px-3 py-2 border border-gray-300 hover:bg-gray-50 disabled:opacity-50 text-sm rounded-lg transition-colors"
+                >
+                  导入 CSV
+                </button>
+              </div>
+            </div>
+
+            {csvPreview && (
+              <div className="p-3 mb-3 bg-gray-50 rounded-lg border border-gray-200 text-sm text-gray-700 space-y-3">
+                <p className="truncate">{csvPreview.path}</p>
+                <div className="grid grid-cols-2 gap-3">
+                  <label className="block">
+                    编码
+                    <select
+                      value={csvPreview.encoding}
+                      onChange={(e) => handlePreviewCsv(csvPreview.path, e.target.value as CsvEncoding, csvPreview.mapping)}
+                      className="mt-1 w-full px-2 py-1 border border-gray-300 rounded-lg"
+                    >
+                      <option value="utf-8">UTF-8</option>
+                      <option value="gbk">GBK</option>
+                    </select>
+                  </label>
+                  {([
+                    ['title', '标题列'],
+                    ['content', '内容列'],
+                    ['tags', '标签列'],
+                  ] as const).map(([key, label]) => (
+                    <label key={key} className="block">
+                      {label}
+                      <select
+                        value={csvPreview.mapping[key] ?? ''}
+                        onChange={(e) => updateCsvMapping(key, e.target.value)}
+                        className="mt-1 w-full px-2 py-1 border border-gray-300 rounded-lg"
+                      >
+                        {key !== 'content' && <option value="">{key === 'title' ? '取内容第一行' : '不导入'}</option>}
+                        {csvPreview.headers.map(header => (
+                          <option key={header} value={header}>{header}</option>
+                        ))}
+                      </select>
+                    </label>
+                  ))}
+                  {csvPreview.mapping.tags && (
+                    <label className="block">
+                      标签分隔符
+                      <input
+                        type="text"
+                        value={csvPreview.mapping.tag_delimiter}
+                        onChange={(e) => updateCsvMapping('tag_delimiter', e.target.value)}
+                        className="mt-1 w-full px-2 py-1 border border-gray-300 rounded-lg"
+                      />
+                    </label>
+                  )}
+                </div>
+                <ul className="max-h-40 overflow-y-auto space-y-1">
+                  {csvPreview.prompts.map((prompt, index) => (
+                    <li key={index} className="text-xs text-gray-500 truncate">
+                      <span className="text-gray-800">{prompt.title}</span>
+                      {prompt.tags.length > 0 && <span className="ml-1">[{prompt.tags.join(', ')}]</span>}
+                      ：{prompt.content}
+                    </li>
+                  ))}
+                </ul>
+                <div className="flex items-center justify-between">
+                  <p className="text-xs text-gray-500">
+                    共 {csvPreview.total} 条{csvPreview.skipped > 0 && `，${csvPreview.skipped} 行内容为空将跳过`}，ID 相同时按上面选择的方式处理
+                  </p>
+                  <div className="flex items-center gap-2">
+                    <button
+                      onClick={() => setCsvPreview(null)}
+                      className="px-3 py-2 border border-gray-300 hover:bg-gray-100 text-sm rounded-lg transition-colors"
+                    >
+                      取消
+                    </button>
+                    <button
+                      onClick={handleImportCsv}
+                      disabled={isTransferring || csvPreview.total === 0}
+                      className="px-3 py-2 bg-blue-600 hover:bg-blue-700 disabled:bg-blue-400 text-white text-sm rounded-lg transition-colors"
+                    >
+                      导入 {csvPreview.total} 条
+                    </button>
+                  </div>
+                </div>
+              </div>
+            )}
+
+            <div className="flex items-center justify-between py-3">
+              <div>
+                <p className="text-gray-800">从其他工具导入</p>
+                <p className="text-sm text-gray-500">Open WebUI、ChatGPT 自定义指令、Raycast AI Commands 的导出文件或纯文本，可以多选</p>
+              </div>
+              <div className="flex items-center gap-3">
+                <select
+                  value={importFormat}
+                  onChange={(e) => setImportFormat(e.target.value)}
+                  className="px-3 py-2 border border-gray-300 rounded-lg text-sm"
+                >
+                  <option value="">自动识别</option>
+                  {importers.map(importer => (
+                    <option key={importer.id} value={importer.id}>{importer.name}</option>
+                  ))}
+                </select>
+                <button
+                  onClick={handlePreviewImport}
+                  disabled={isTransferring}
+                  className="px-3 py-2 border border-gray-300 hover:bg-gray-50 disabled:opacity-50 text-sm rounded-lg transition-colors"
+                >
+                  选择文件
+                </button>
+              </div>
+            </div>
+
+            {importPreview && (
+              <div className="p-3 mb-3 bg-gray-50 rounded-lg border border-gray-200 text-sm text-gray-700 space-y-3">
+                <ul className="space-y-1">
+                  {importPreview.sources.map(source => (
+                    <li key={source.path} className="text-xs text-gray-500 truncate">
+                      {source.format_name}（{source.count} 条）：{source.path}
+                    </li>
+                  ))}
+                </ul>
+                <ul className="max-h-40 overflow-y-auto space-y-1">
+                  {importPreview.prompts.map((prompt, index) => (
+                    <li key={index} className="text-xs text-gray-500 truncate">
+                      <span className="text-gray-800">{prompt.title}</span>：{prompt.content}
+                    </li>
+                  ))}
+                </ul>
+                <div className="flex items-center justify-between">
+                  <p className="text-xs text-gray-500">共 {importPreview.total} 条，导入后会加上来源工具的标签</p>
+                  <div className="flex items-center gap-2">
+                    <button
+                      onClick={() => setImportPreview(null)}
+                      className="px-3 py-2 border border-gray-300 hover:bg-gray-100 text-sm rounded-lg transition-colors"
+                    >
+                      取消
+                    </button>
+                    <button
+                      onClick={handleConfirmImport}
+                      disabled={isTransferring || importPreview.total === 0}
+                      className="px-3 py-2 bg-blue-600 hover:bg-blue-700 disabled:bg-blue-400 text-white text-sm rounded-lg transition-colors"
+                    >
+                      导入 {importPreview.total} 条
+                    </button>
+                  </div>
+                </div>
+              </div>
+            )}
+
+            <div className="flex items-center justify-between py-3">
+              <div>
+                <p className="text-gray-800">分享文件（.pgenie）</p>
+                <p className="text-sm text-gray-500">把选中的提示词连同引用的片段和变量定义打包发给同事，双击文件即可导入</p>
+              </div>
+              <div className="flex items-center gap-3">
+                <button
+                  onClick={handleStartBundleExport}
+                  disabled={isTransferring}
+                  className="px-3 py-2 border border-gray-300 hover:bg-gray-50 disabled:opacity-50 text-sm rounded-lg transition-colors"
+                >
+                  导出
+                </button>
+                <button
+                  onClick={handleOpenBundle}
+                  disabled={isTransferring}
+                  className="px-3 py-2 border border-gray-300 hover:bg-gray-50 disabled:opacity-50 text-sm rounded-lg transition-colors"
+                >
+                  打开
+                </button>
+              </div>
+            </div>
+
+            {bundleCandidates && (
+              <div className="p-3 mb-3 bg-gray-50 rounded-lg border border-gray-200 text-sm text-gray-700 space-y-3">
+                <ul className="max-h-40 overflow-y-auto space-y-1">
+                  {bundleCandidates.map(prompt => (
+                    <li key={prompt.id}>
+                      <label className="flex items-center gap-2 text-xs text-gray-800">
+                        <input
+                          type="checkbox"
+                          checked={bundleSelection.includes(prompt.id)}
+                          onChange={() => toggleBundleSelection(prompt.id)}
+                        />
+                        {prompt.title}
+                      </label>
+                    </li>
+                  ))}
+                </ul>
+                <div className="flex items-center justify-between">
+                  <label className="flex items-center gap-2 text-xs text-gray-500">
+                    <input type="checkbox" checked={bundleSign} onChange={(e) => setBundleSign(e.target.checked)} />
+                    签名{bundleFingerprint ? `（本机指纹 ${bundleFingerprint}）` : ''}
+                  </label>
+                  <div className="flex items-center gap-2">
+                    <button
+                      onClick={() => setBundleCandidates(null)}
+                      className="px-3 py-2 border border-gray-300 hover:bg-gray-100 text-sm rounded-lg transition-colors"
+                    >
+                      取消
+                    </button>
+                    <button
+                      onClick={handleExportBundle}
+                      disabled={isTransferring || bundleSelection.length === 0}
+                      className="px-3 py-2 bg-blue-600 hover:bg-blue-700 disabled:bg-blue-400 text-white text-sm rounded-lg transition-colors"
+                    >
+                      导出 {bundleSelection.length} 条
+                    </button>
+                  </div>
+                </div>
+              </div>
+            )}
+
+            {bundlePath && (
+              <BundleImportDialog
+                path={bundlePath}
+                onClose={() => setBundlePath(null)}
+                onImported={() => setBundlePath(null)}
+              />
+            )}
+
+            {importReport && (
+              <div className="p-3 bg-gray-50 rounded-lg border border-gray-200 text-sm text-gray-700">
+                <p className="mb-2">
+                  新建 {importReport.created} 条，更新 {importReport.updated} 条，另存 {importReport.duplicated} 条，
+                  跳过 {importReport.skipped} 条，新增标签 {importReport.tags_created} 个
+                </p>
+                <ul className="max-h-40 overflow-y-auto space-y-1">
+                  {importReport.changes
+                    .filter(change => change.action !== 'skipped')
+                    .map(change => (
+                      <li key={change.id} className="text-xs text-gray-500">
+                        {{ created: '新建', updated: '更新', duplicated: '另存', skipped: '跳过' }[change.action]}：{change.title}
+                      </li>
+                    ))}
+                </ul>
+              </div>
+            )}
+          </div>
+
           {/* 托盘菜单设置 */}
           <div>
             <h3 className="text-lg font-medium text-gray-800 mb-2">托盘菜单设置</h3>
@@ -354,6 +1499,50 @@ const Settings: React.FC<SettingsProps> = ({ isOpen, onClose }) => {
               </label>
             </div>
 
+            {appSettings && (
+              <div className="py-3 space-y-3">
+                <div className="grid grid-cols-2 gap-3">
+                  {([
+                    ['recent_count', '最近使用数量'],
+                    ['favorite_count', '收藏数量'],
+                    ['most_used_count', '最常使用数量'],
+                    ['tag_item_count', '每个标签的数量'],
+                    ['title_max_chars', '标题最大字符数'],
+                  ] as const).map(([key, label]) => (
+                    <label key={key} className="block text-sm text-gray-700">
+                      {label}
+                      <input
+                        type="number"
+                        min={0}
+                        value={appSettings.tray[key]}
+                        onChange={(e) => updateTraySetting(key, Math.max(0, parseInt(e.target.value, 10) || 0))}
+                        className="mt-1 w-full px-3 py-2 border border-gray-300 rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                      />
+                    </label>
+                  ))}
+                </div>
+                <div className="flex items-center justify-between">
+                  <label className="flex items-center text-sm text-gray-700">
+                    <input
+                      type="checkbox"
+                      checked={appSettings.tray.show_tags}
+                      onChange={(e) => updateTraySetting('show_tags', e.target.checked)}
+                      className="w-4 h-4 mr-2"
+                    />
+                    为每个标签显示子菜单
+                  </label>
+                  <button
+                    onClick={() => saveAppSettingsSection('托盘菜单设置已保存')}
+                    disabled={isSavingAppSettings}
+                    className="flex items-center px-4 py-2 bg-blue-600 hover:bg-blue-700 disabled:bg-blue-400 text-white rounded-lg transition-colors"
+                  >
+                    <SaveIcon size={16} className="mr-2" />
+                    {isSavingAppSettings ? '保存中...' : '保存'}
+                  </button>
+                </div>
+              </div>
+            )}
+
             {trayAutoInsert && (
               <div className="mt-3 p-3 bg-yellow-50 rounded-lg border border-yellow-100">
                 <p className="text-sm text-yellow-700 mb-2">
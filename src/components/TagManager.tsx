@@ -76,6 +76,12 @@ const TagManager: React.FC<TagManagerProps> = ({ isOpen, onClose }) => {
   const saveTagEdit = async () => {
     if (!editingTagId) return;
 
+    // 标签名称唯一，重名时数据库会拒绝更新
+    if (tags.some(tag => tag.id !== editingTagId && tag.name === editName)) {
+      setError(`已存在名为「${editName}」的标签`);
+      return;
+    }
+
     try {
       setError(null);
       setIsLoading(true);
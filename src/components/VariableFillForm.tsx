@@ -0,0 +1,175 @@
+import React, { useState, useEffect } from 'react';
+import { CopyIcon } from 'lucide-react';
+import { Toaster, toast } from 'sonner';
+import {
+  getPrompt,
+  getPromptVariables,
+  renderPrompt,
+  updatePromptLastUsed,
+  TemplateVariable,
+  UsageSource,
+} from '../services/db';
+
+// 托盘复制含变量的提示词时打开的填写窗口
+const VariableFillForm: React.FC = () => {
+  const [promptId, setPromptId] = useState<string | null>(
+    () => new URLSearchParams(window.location.search).get('promptId')
+  );
+  // 打开填写窗口的来源（托盘或快捷搜索面板），用于记录使用统计
+  const [source, setSource] = useState<UsageSource>(
+    () => (new URLSearchParams(window.location.search).get('source') as UsageSource | null) ?? 'tray'
+  );
+  const [title, setTitle] = useState('');
+  const [variables, setVariables] = useState<TemplateVariable[]>([]);
+  const [values, setValues] = useState<Record<string, string>>({});
+  // 拖入的文件仅用于显示，实际读取的路径由 Rust 端从拖放事件记录
+  const [droppedFile, setDroppedFile] = useState('');
+  const [isCopying, setIsCopying] = useState(false);
+
+  // 窗口已打开时，托盘再次选择提示词会通过事件切换
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    (async () => {
+      const { listen } = await import('@tauri-apps/api/event');
+      unlisten = await listen<{ promptId: string; source: UsageSource }>('fill-prompt', event => {
+        setPromptId(event.payload.promptId);
+        setSource(event.payload.source);
+      });
+    })();
+    return () => unlisten?.();
+  }, []);
+
+  // 拖入窗口的文件作为 selection_file 的值（Rust 端同时记录该路径）
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    (async () => {
+      const { getCurrentWebview } = await import('@tauri-apps/api/webview');
+      unlisten = await getCurrentWebview().onDragDropEvent(event => {
+        if (event.payload.type === 'drop' && event.payload.paths.length > 0) {
+          setDroppedFile(event.payload.paths[0]);
+        }
+      });
+    })();
+    return () => unlisten?.();
+  }, []);
+
+  useEffect(() => {
+    if (!promptId) return;
+    const loadVariables = async () => {
+      try {
+        const prompt = await getPrompt(promptId);
+        setTitle(prompt?.title ?? '');
+        const loaded = await getPromptVariables(promptId);
+        setVariables(loaded);
+        const initialValues: Record<string, string> = {};
+        for (const variable of loaded) {
+          if (variable.kind.type === 'file') continue;
+          initialValues[variable.name] = variable.default ?? '';
+        }
+        setValues(initialValues);
+        setDroppedFile('');
+      } catch (err) {
+        console.error('加载模板变量失败:', err);
+        toast.error('加载模板变量失败');
+      }
+    };
+    loadVariables();
+  }, [promptId]);
+
+  const closeWindow = async () => {
+    const { getCurrentWindow } = await import('@tauri-apps/api/window');
+    await getCurrentWindow().close();
+  };
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (!promptId) return;
+    setIsCopying(true);
+    try {
+      const text = await renderPrompt(promptId, values);
+      const { writeText } = await import('@tauri-apps/plugin-clipboard-manager');
+      await writeText(text);
+      await updatePromptLastUsed(promptId, source);
+      await closeWindow();
+    } catch (err) {
+      console.error('渲染提示词失败:', err);
+      toast.error(`${err}`);
+    } finally {
+      setIsCopying(false);
+    }
+  };
+
+  const renderField = (variable: TemplateVariable) => {
+    const value = values[variable.name] ?? '';
+    const onChange = (newValue: string) => setValues({ ...values, [variable.name]: newValue });
+    const className =
+      'w-full px-3 py-2 text-sm border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500';
+
+    if (variable.kind.type === 'choice') {
+      return (
+        <select className={className} value={value} onChange={e => onChange(e.target.value)}>
+          <option value="" disabled>
+            请选择
+          </option>
+          {variable.kind.options.map(option => (
+            <option key={option} value={option}>
+              {option}
+            </option>
+          ))}
+        </select>
+      );
+    }
+    if (variable.kind.type === 'file') {
+      return (
+        <div className="w-full px-3 py-4 text-sm text-center border-2 border-dashed border-gray-300 rounded-md text-gray-500 break-all">
+          {droppedFile || '将文件拖到此窗口'}
+        </div>
+      );
+    }
+    if (variable.kind.type === 'multiline') {
+      return (
+        <textarea className={`${className} h-24`} value={value} onChange={e => onChange(e.target.value)} />
+      );
+    }
+    return <input className={className} value={value} onChange={e => onChange(e.target.value)} />;
+  };
+
+  return (
+    <div className="flex h-screen flex-col bg-white font-sans">
+      <Toaster richColors position="top-center" />
+      <div className="px-5 py-4 border-b border-gray-200">
+        <h2 className="text-lg font-semibold text-gray-800 truncate">{title || '填写变量'}</h2>
+      </div>
+      <form onSubmit={handleSubmit} className="flex flex-1 flex-col overflow-hidden">
+        <div className="flex-1 overflow-y-auto p-5 space-y-4">
+          {variables.map(variable => (
+            <div key={variable.name}>
+              <label className="block text-sm font-medium text-gray-700 mb-1">{variable.name}</label>
+              {variable.description && <p className="text-xs text-gray-500 mb-1">{variable.description}</p>}
+              {renderField(variable)}
+            </div>
+          ))}
+        </div>
+        <div className="flex justify-end p-4 space-x-3 border-t border-gray-200">
+          <button
+            type="button"
+            onClick={closeWindow}
+            className="px-4 py-2 text-sm font-medium text-gray-700 bg-white border border-gray-300 rounded-md shadow-sm hover:bg-gray-50"
+          >
+            取消
+          </button>
+          <button
+            type="submit"
+            disabled={isCopying}
+            className="flex items-center px-4 py-2 text-sm font-medium text-white bg-blue-600 rounded-md shadow-sm hover:bg-blue-700 disabled:opacity-50"
+          >
+            <CopyIcon size={16} className="mr-1" />
+            复制
+          </button>
+        </div>
+      </form>
+    </div>
+  );
+};
+
+export default VariableFillForm;
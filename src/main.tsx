@@ -1,10 +1,15 @@
 import { StrictMode } from 'react';
 import { createRoot } from 'react-dom/client';
 import App from './App.tsx';
+import VariableFillForm from './components/VariableFillForm.tsx';
+import PromptPalette from './components/PromptPalette.tsx';
 import './index.css';
 
+// 变量填写窗口、快捷搜索面板与主窗口共用同一个入口，通过 view 参数区分
+const view = new URLSearchParams(window.location.search).get('view');
+
 createRoot(document.getElementById('root')!).render(
   <StrictMode>
-    <App />
+    {view === 'fill' ? <VariableFillForm /> : view === 'palette' ? <PromptPalette /> : <App />}
   </StrictMode>
 );
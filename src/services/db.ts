@@ -39,7 +39,7 @@ export function initDatabase(): Promise<Database> {
     initPromise = (async () => {
       try {
         console.log('Attempting to load database...');
-        // 连接SQLite数据库
+        // 连接SQLite数据库，sqlx 的每个连接默认开启 PRAGMA foreign_keys，删除时关联由外键级联删除
         const loadedDb = await Database.load('sqlite:promptgenie.db');
         console.log('Database loaded successfully.');
         db = loadedDb; // Assign to the global variable *after* successful load
@@ -97,7 +97,7 @@ async function ensureDbInitialized(): Promise<Database> {
 export async function getAllPrompts(): Promise<Prompt[]> {
   const currentDb = await ensureDbInitialized();
 
-  const result = await currentDb.select<any[]>(`SELECT * FROM prompts ORDER BY updated_at DESC`);
+  const result = await currentDb.select<any[]>(`SELECT * FROM prompts WHERE deleted_at IS NULL ORDER BY updated_at DESC`);
   const prompts: Prompt[] = [];
 
   for (const row of result) {
@@ -339,13 +339,17 @@ export async function updatePrompt(id: string, promptData: PromptInput): Promise
   }
 }
 
-// 删除提示词
+// 删除提示词（移入回收站，标签关联保留以便恢复）
 export async function deletePrompt(id: string): Promise<boolean> {
   const currentDb = await ensureDbInitialized();
 
   try {
-    await currentDb.execute(`DELETE FROM prompt_tags WHERE prompt_id = $1`, [id]);
-    await currentDb.execute(`DELETE FROM prompts WHERE id = $1`, [id]);
+    const now = new Date().toISOString();
+    await currentDb.execute(`UPDATE prompts SET deleted_at = $1 WHERE id = $2`, [now, id]);
+    // 已删除的提示词不再出现在托盘菜单中
+    updateTrayMenu().catch(err => {
+      console.error('Failed to update tray menu after prompt deletion:', err);
+    });
     return true;
   } catch (error: any) { // Type error as any to access stack
     console.error("Error deleting prompt:", error);
@@ -398,6 +402,7 @@ export async function getAllTags(): Promise<Tag[]> {
       COUNT(pt.prompt_id) as prompt_count
     FROM tags t
     LEFT JOIN prompt_tags pt ON t.id = pt.tag_id
+      AND pt.prompt_id IN (SELECT id FROM prompts WHERE deleted_at IS NULL)
     GROUP BY t.id, t.name, t.color
     ORDER BY t.name ASC
   `);
@@ -410,25 +415,30 @@ export async function getAllTags(): Promise<Tag[]> {
   }));
 }
 
-// 更新提示词的最后使用时间
-export async function updatePromptLastUsed(id: string): Promise<boolean> {
-  const currentDb = await ensureDbInitialized();
+// 使用来源
+export type UsageSource = 'tray' | 'palette' | 'main' | 'cli' | 'api' | 'mcp' | 'link';
+
+// 使用方式
+export type UsageAction = 'copy' | 'render' | 'run';
+
+// 记录一次提示词使用，由 Rust 端统一写入，last_used_at 和使用次数由数据库触发器更新
+export async function updatePromptLastUsed(
+  id: string,
+  source: UsageSource = 'main',
+  action: UsageAction = 'copy'
+): Promise<boolean> {
   try {
-    const now = new Date().toISOString(); // 使用ISO格式的时间戳
-    const result = await currentDb.execute(
-      'UPDATE prompts SET last_used_at = $1 WHERE id = $2',
-      [now, id]
-    );
-    console.log(`Updated last_used_at for prompt ${id}, rows affected: ${result.rowsAffected}`);
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('record_prompt_usage', { promptId: id, action, source });
 
     // 更新后立即刷新托盘菜单
     updateTrayMenu().catch(err => {
       console.error('Failed to update tray menu after prompt use:', err);
     });
 
-    return result.rowsAffected > 0;
+    return true;
   } catch (err) {
-    console.error(`Failed to update last_used_at for prompt ${id}:`, err);
+    console.error(`Failed to record usage for prompt ${id}:`, err);
     return false;
   }
 }
@@ -441,7 +451,7 @@ export async function getRecentlyUsedPrompts(limit: number = 5): Promise<Prompt[
     // 获取最近使用的提示词，优先按last_used_at排序，如果为空则按updated_at排序
     const result = await currentDb.select<any[]>(`
       SELECT * FROM prompts 
-      WHERE last_used_at IS NOT NULL
+      WHERE last_used_at IS NOT NULL AND deleted_at IS NULL
       ORDER BY last_used_at DESC
       LIMIT $1
     `, [limit]);
@@ -474,26 +484,11 @@ export async function getRecentlyUsedPrompts(limit: number = 5): Promise<Prompt[
   }
 }
 
-// 更新托盘菜单
+// 更新托盘菜单（菜单内容由 Rust 端从数据库读取）
 export async function updateTrayMenu(): Promise<void> {
   try {
-    // 获取最近使用的提示词
-    const recentPrompts = await getRecentlyUsedPrompts(5);
-
-    // 将提示词转换为托盘菜单项格式
-    const menuItems = recentPrompts.map(prompt => ({
-      id: prompt.id,
-      title: prompt.title
-    }));
-
-    // 动态导入Tauri API并调用后端更新托盘菜单
-    try {
-      const { invoke } = await import('@tauri-apps/api/core');
-      await invoke('update_tray_menu', { items: menuItems });
-      console.log('Tray menu updated with recent prompts:', menuItems);
-    } catch (apiError) {
-      console.error('Error importing Tauri API or updating tray menu:', apiError);
-    }
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('update_tray_menu');
   } catch (err) {
     console.error('Failed to update tray menu:', err);
   }
@@ -553,9 +548,7 @@ export async function updateTag(id: string, name: string, color: string): Promis
 export async function deleteTag(id: string): Promise<boolean> {
   const currentDb = await ensureDbInitialized();
   try {
-    // 删除标签与提示词的关联
-    await currentDb.execute(`DELETE FROM prompt_tags WHERE tag_id = $1`, [id]);
-    // 删除标签本身
+    // 标签与提示词的关联由外键级联删除
     await currentDb.execute(`DELETE FROM tags WHERE id = $1`, [id]);
     return true;
   } catch (error: any) {
@@ -577,6 +570,7 @@ export interface PromptRecord {
   created_at: string;
   updated_at: string;
   last_used: string | null;
+  deleted_at?: string | null; // 已移入回收站的提示词不会推送到云端
 }
 
 export interface SyncResult {
@@ -591,12 +585,13 @@ export interface SyncResult {
 
 /**
  * 获取包含last_used_at字段的完整提示词数据（用于同步）
+ * 回收站中的提示词也会返回，由后端据此跳过推送和云端恢复
  */
-async function getAllPromptsWithLastUsed(): Promise<Array<Prompt & { lastUsed: string | null }>> {
+async function getAllPromptsWithLastUsed(): Promise<Array<Prompt & { lastUsed: string | null; deletedAt: string | null }>> {
   const currentDb = await ensureDbInitialized();
 
   const result = await currentDb.select<any[]>(`SELECT * FROM prompts ORDER BY updated_at DESC`);
-  const prompts: Array<Prompt & { lastUsed: string | null }> = [];
+  const prompts: Array<Prompt & { lastUsed: string | null; deletedAt: string | null }> = [];
 
   for (const row of result) {
     const tagsResult = await currentDb.select<any[]>(`
@@ -614,6 +609,7 @@ async function getAllPromptsWithLastUsed(): Promise<Array<Prompt & { lastUsed: s
       dateCreated: row.created_at,
       dateModified: row.updated_at,
       lastUsed: row.last_used_at, // 包含last_used_at字段
+      deletedAt: row.deleted_at,
       tags: tagsResult.map(tag => ({ id: tag.id, name: tag.name, color: tag.color }))
     });
   }
@@ -624,7 +620,7 @@ async function getAllPromptsWithLastUsed(): Promise<Array<Prompt & { lastUsed: s
 /**
  * 将包含lastUsed字段的Prompt格式转换为后端PromptRecord格式
  */
-function convertPromptWithLastUsedToRecord(prompt: Prompt & { lastUsed: string | null }): PromptRecord {
+function convertPromptWithLastUsedToRecord(prompt: Prompt & { lastUsed: string | null; deletedAt: string | null }): PromptRecord {
   // 将标签数组转换为JSON字符串
   const tagsStr = JSON.stringify(prompt.tags.map(tag => tag.name));
   
@@ -637,6 +633,7 @@ function convertPromptWithLastUsedToRecord(prompt: Prompt & { lastUsed: string |
     created_at: prompt.dateCreated,
     updated_at: prompt.dateModified,
     last_used: prompt.lastUsed || prompt.dateCreated, // 使用实际的lastUsed值，如果为空则使用创建时间
+    deleted_at: prompt.deletedAt,
   };
 }
 
@@ -848,4 +845,695 @@ export async function handleSyncUpdateLocal(records: PromptRecord[]): Promise<vo
       console.error(`更新本地记录失败:`, error);
     }
   }
-} 
\ No newline at end of file
+} 
+// 提示词历史版本
+export interface PromptVersion {
+  prompt_id: string;
+  version: number;
+  title: string;
+  content: string;
+  created_at: string;
+}
+
+export interface VersionDiff {
+  prompt_id: string;
+  from_version: number;
+  to_version: number;
+  from_title: string;
+  to_title: string;
+  lines: Array<{ kind: 'equal' | 'insert' | 'delete'; text: string }>;
+  insertions: number;
+  deletions: number;
+}
+
+/**
+ * 获取提示词的历史版本（最新的在前）
+ */
+export async function listPromptVersions(promptId: string): Promise<PromptVersion[]> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<PromptVersion[]>('list_prompt_versions', { promptId });
+}
+
+/**
+ * 比较提示词的两个版本
+ */
+export async function diffPromptVersions(promptId: string, fromVersion: number, toVersion: number): Promise<VersionDiff> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<VersionDiff>('diff_prompt_versions', { promptId, fromVersion, toVersion });
+}
+
+/**
+ * 恢复提示词到指定版本（会生成一个新版本）
+ */
+export async function restorePromptVersion(promptId: string, version: number): Promise<PromptVersion> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<PromptVersion>('restore_prompt_version', { promptId, version });
+}
+
+// 回收站
+export interface TrashedPrompt {
+  id: string;
+  title: string;
+  content: string;
+  is_favorite: boolean;
+  created_at: string;
+  updated_at: string;
+  last_used_at: string | null;
+  deleted_at: string | null;
+}
+
+/**
+ * 获取回收站中的提示词
+ */
+export async function listTrash(): Promise<TrashedPrompt[]> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<TrashedPrompt[]>('list_trash');
+}
+
+/**
+ * 从回收站恢复提示词
+ */
+export async function restoreFromTrash(promptId: string): Promise<void> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('restore_from_trash', { promptId });
+}
+
+/**
+ * 彻底删除回收站中的提示词
+ */
+export async function purgeFromTrash(promptIds: string[]): Promise<number> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<number>('purge_from_trash', { promptIds });
+}
+
+/**
+ * 清空回收站
+ */
+export async function emptyTrash(): Promise<number> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<number>('empty_trash');
+}
+
+// 模板变量
+export interface TemplateVariable {
+  name: string;
+  kind: { type: 'text' | 'multiline' | 'file' } | { type: 'choice'; options: string[] };
+  default: string | null;
+  description: string | null;
+}
+
+/**
+ * 获取提示词中声明的模板变量
+ */
+export async function getPromptVariables(promptId: string): Promise<TemplateVariable[]> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<TemplateVariable[]>('get_prompt_variables', { promptId });
+}
+
+/**
+ * 使用变量值渲染提示词
+ */
+export async function renderPrompt(promptId: string, values: Record<string, string>): Promise<string> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<string>('render_prompt', { promptId, values });
+}
+
+// 可复用片段（在提示词中通过 {{> 名称}} 引用）
+export interface Snippet {
+  id: string;
+  name: string;
+  content: string;
+  description: string;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface SnippetBacklink {
+  prompt_id: string;
+  title: string;
+  direct: boolean;
+}
+
+/**
+ * 获取所有片段
+ */
+export async function listSnippets(): Promise<Snippet[]> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<Snippet[]>('list_snippets');
+}
+
+/**
+ * 创建或更新片段，形成循环引用时会被拒绝；
+ * 改名时仍有提示词使用旧名称引用也会被拒绝（错误信息列出这些提示词），force 为 true 时强制改名
+ */
+export async function saveSnippet(
+  snippet: { id?: string; name: string; content: string; description?: string },
+  force = false
+): Promise<Snippet> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<Snippet>('save_snippet', {
+    id: snippet.id ?? null,
+    name: snippet.name,
+    content: snippet.content,
+    description: snippet.description ?? null,
+    force,
+  });
+}
+
+/**
+ * 删除片段，仍有提示词引用时会被拒绝（错误信息列出这些提示词），force 为 true 时强制删除
+ */
+export async function deleteSnippet(id: string, force = false): Promise<void> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('delete_snippet', { id, force });
+}
+
+/**
+ * 查询使用了指定片段的提示词
+ */
+export async function getSnippetBacklinks(name: string): Promise<SnippetBacklink[]> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<SnippetBacklink[]>('get_snippet_backlinks', { name });
+}
+
+// 应用设置（保存在 Rust 端的 app_settings.json）
+export interface AppSettings {
+  versions: { max_versions_per_prompt: number; max_age_days: number };
+  trash: { auto_empty_days: number };
+  palette: { enabled: boolean; shortcut: string };
+  tray: {
+    recent_count: number;
+    favorite_count: number;
+    most_used_count: number;
+    show_tags: boolean;
+    tag_item_count: number;
+    title_max_chars: number;
+  };
+  api: { enabled: boolean; port: number; token: string };
+  folder_sync: { enabled: boolean; path: string };
+  backup: { enabled: boolean; keep_recent: number; keep_daily: number; keep_weekly: number };
+}
+
+/**
+ * 读取应用设置
+ */
+export async function getAppSettings(): Promise<AppSettings> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<AppSettings>('get_app_settings');
+}
+
+/**
+ * 保存应用设置，保存后立即生效
+ */
+export async function saveAppSettings(settings: AppSettings): Promise<void> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('save_app_settings', { settings });
+}
+
+/**
+ * 重新生成本地 API 的访问令牌，返回新令牌
+ */
+export async function regenerateApiToken(): Promise<string> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<string>('regenerate_api_token');
+}
+
+/**
+ * 取出由 promptgenie:// 链接请求打开的提示词 ID
+ */
+export async function takePendingPrompt(): Promise<string | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<string | null>('take_pending_prompt');
+}
+
+// promptgenie://import 链接收到的提示词，用户确认后才会导入
+export interface LinkImportPreview {
+  source: string | null;
+  prompts: Array<{
+    title: string;
+    content: string;
+    tags: string[];
+    variables: TemplateVariable[];
+  }>;
+}
+
+/**
+ * 读取等待确认的链接导入内容，没有时返回 null
+ */
+export async function previewLinkImport(): Promise<LinkImportPreview | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<LinkImportPreview | null>('preview_link_import');
+}
+
+/**
+ * 确认导入链接中的提示词
+ */
+export async function confirmLinkImport(): Promise<ImportReport> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<ImportReport>('confirm_link_import');
+}
+
+/**
+ * 放弃链接中的导入内容
+ */
+export async function cancelLinkImport(): Promise<void> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('cancel_link_import');
+}
+
+/**
+ * 生成提示词链接；embed 为 true 时把内容编码在链接中，对方可以直接导入
+ */
+export async function createShareLink(promptId: string, embed: boolean): Promise<string> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<string>('create_share_link', { promptId, embed });
+}
+
+// 提示词库导入导出
+export type ImportStrategy = 'skip' | 'overwrite' | 'duplicate' | 'newer_wins';
+
+export interface ExportSummary {
+  path: string;
+  prompts: number;
+  tags: number;
+}
+
+export interface ImportReport {
+  created: number;
+  updated: number;
+  duplicated: number;
+  skipped: number;
+  tags_created: number;
+  changes: Array<{
+    id: string;
+    source_id: string;
+    title: string;
+    action: 'created' | 'updated' | 'duplicated' | 'skipped';
+  }>;
+}
+
+/**
+ * 导出整个提示词库为 JSON 文件，会弹出保存对话框，取消时返回 null
+ */
+export async function exportLibrary(includeTrash: boolean): Promise<ExportSummary | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<ExportSummary | null>('export_library', { path: null, includeTrash });
+}
+
+/**
+ * 从 JSON 文件导入提示词库，会弹出打开对话框，取消时返回 null
+ */
+export async function importLibrary(strategy: ImportStrategy): Promise<ImportReport | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<ImportReport | null>('import_library', { path: null, strategy });
+}
+
+export interface MarkdownExportSummary {
+  path: string;
+  written: number;
+  unchanged: number;
+  removed: number;
+}
+
+/**
+ * 导出为 Markdown 目录（每条提示词一个 .md 文件），会弹出选择目录对话框，取消时返回 null
+ */
+export async function exportMarkdown(prune: boolean): Promise<MarkdownExportSummary | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<MarkdownExportSummary | null>('export_markdown', { path: null, prune });
+}
+
+/**
+ * 从 Markdown 目录导入，会弹出选择目录对话框，取消时返回 null
+ */
+export async function importMarkdown(strategy: ImportStrategy): Promise<ImportReport | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<ImportReport | null>('import_markdown', { path: null, strategy });
+}
+
+// CSV 导入导出
+export type CsvEncoding = 'auto' | 'utf-8' | 'gbk';
+
+export interface CsvMapping {
+  id: string | null;
+  title: string | null;
+  content: string;
+  tags: string | null;
+  tag_delimiter: string;
+  favorite: string | null;
+  created_at: string | null;
+  updated_at: string | null;
+}
+
+export interface DraftPrompt {
+  id: string | null;
+  title: string;
+  content: string;
+  tags: string[];
+  is_favorite: boolean;
+  created_at: string | null;
+  updated_at: string | null;
+}
+
+export interface CsvPreview {
+  path: string;
+  encoding: CsvEncoding;
+  headers: string[];
+  mapping: CsvMapping;
+  total: number;
+  skipped: number;
+  prompts: DraftPrompt[];
+}
+
+export interface CsvExportSummary {
+  path: string;
+  prompts: number;
+  unmappable: boolean;
+}
+
+/**
+ * 导出为 CSV 文件，会弹出保存对话框，取消时返回 null
+ */
+export async function exportCsv(encoding: CsvEncoding, bom: boolean): Promise<CsvExportSummary | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<CsvExportSummary | null>('export_csv', { path: null, encoding, bom });
+}
+
+/**
+ * 预览 CSV 文件；path 为 null 时弹出选择文件对话框，取消时返回 null；mapping 为 null 时按列名猜测
+ */
+export async function previewCsv(
+  path: string | null,
+  encoding: CsvEncoding,
+  mapping: CsvMapping | null
+): Promise<CsvPreview | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<CsvPreview | null>('preview_csv', { path, encoding, mapping });
+}
+
+/**
+ * 按预览确认的映射导入 CSV 文件
+ */
+export async function importCsv(
+  path: string,
+  encoding: CsvEncoding,
+  mapping: CsvMapping,
+  strategy: ImportStrategy
+): Promise<ImportReport> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<ImportReport>('import_csv', { path, encoding, mapping, strategy });
+}
+
+// 从其他提示词工具导入
+export interface ImporterInfo {
+  id: string;
+  name: string;
+  extensions: string[];
+}
+
+export interface ImportSource {
+  path: string;
+  format: string;
+  format_name: string;
+  count: number;
+}
+
+export interface ImportPreview {
+  sources: ImportSource[];
+  total: number;
+  prompts: DraftPrompt[];
+}
+
+/**
+ * 支持导入的格式（Open WebUI、ChatGPT 自定义指令、Raycast、纯文本等）
+ */
+export async function listImporters(): Promise<ImporterInfo[]> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<ImporterInfo[]>('list_importers');
+}
+
+/**
+ * 选择文件（可多选）并预览，format 为 null 时逐个文件自动识别，取消时返回 null
+ */
+export async function previewImport(format: string | null): Promise<ImportPreview | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<ImportPreview | null>('preview_import', { paths: null, format });
+}
+
+/**
+ * 确认预览后导入
+ */
+export async function confirmImport(
+  paths: string[],
+  format: string | null,
+  strategy: ImportStrategy
+): Promise<ImportReport> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<ImportReport>('confirm_import', { paths, format, strategy });
+}
+
+// Markdown 目录同步
+export interface FolderSyncResult {
+  local_created: number;
+  local_updated: number;
+  local_deleted: number;
+  files_written: number;
+  files_removed: number;
+}
+
+/**
+ * 按设置中的目录立即双向同步一次
+ */
+export async function syncMarkdownFolder(): Promise<FolderSyncResult> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<FolderSyncResult>('sync_markdown_folder');
+}
+
+/**
+ * 弹出选择同步目录对话框，取消时返回 null
+ */
+export async function pickSyncFolder(): Promise<string | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<string | null>('pick_sync_folder');
+}
+
+// 快捷搜索面板
+export interface PaletteItem {
+  id: string;
+  title: string;
+  preview: string;
+  tags: string[];
+  is_favorite: boolean;
+  score: number;
+}
+
+/**
+ * 模糊搜索提示词（标题、标签和内容）
+ */
+export async function searchPrompts(query: string, limit?: number): Promise<PaletteItem[]> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<PaletteItem[]>('search_prompts', { query, limit: limit ?? null });
+}
+
+/**
+ * 复制面板中选中的提示词；含有需要填写的变量时会打开填写窗口并返回 false
+ */
+export async function paletteCopyPrompt(promptId: string): Promise<boolean> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<boolean>('palette_copy_prompt', { promptId });
+}
+
+// 使用统计
+export interface UsageStats {
+  days: number;
+  total: number;
+  prompts: Array<{
+    prompt_id: string;
+    title: string;
+    count: number;
+    by_source: Partial<Record<UsageSource, number>>;
+    last_used_at: string | null;
+  }>;
+  trend: Array<{ date: string; count: number }>;
+  tags: Array<{ tag_id: string; name: string; color: string; count: number }>;
+  unused: Array<{ prompt_id: string; title: string; created_at: string; last_used_at: string | null }>;
+}
+
+/**
+ * 获取最近 days 天的使用统计（0 表示全部记录）
+ */
+export async function getUsageStats(days?: number, limit?: number): Promise<UsageStats> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<UsageStats>('usage_stats', { days: days ?? null, limit: limit ?? null });
+}
+
+// .pgenie 分享文件
+export interface BundlePrompt {
+  id: string;
+  title: string;
+  content: string;
+  tags: string[];
+  variables: TemplateVariable[];
+  created_at: string;
+  updated_at: string;
+}
+
+export interface BundlePreview {
+  path: string;
+  created_at: string;
+  prompts: BundlePrompt[];
+  existing: number;
+  snippets: Array<{ name: string; status: 'new' | 'same' | 'conflict' }>;
+  signer: string | null;
+  signed_by_me: boolean;
+}
+
+export interface BundleImportReport extends ImportReport {
+  snippets_created: number;
+  snippets_kept: string[];
+}
+
+export interface BundleExportSummary {
+  path: string;
+  prompts: number;
+  snippets: number;
+  signed: boolean;
+}
+
+/**
+ * 把提示词导出为 .pgenie 文件（弹出保存对话框），取消时返回 null
+ */
+export async function exportBundle(promptIds: string[], sign: boolean): Promise<BundleExportSummary | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<BundleExportSummary | null>('export_bundle', { promptIds, path: null, sign });
+}
+
+/**
+ * 预览 .pgenie 文件；未指定路径时弹出打开对话框，取消时返回 null
+ */
+export async function previewBundle(path?: string): Promise<BundlePreview | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<BundlePreview | null>('preview_bundle', { path: path ?? null });
+}
+
+/**
+ * 导入预览过的 .pgenie 文件
+ */
+export async function importBundle(path: string, strategy: ImportStrategy): Promise<BundleImportReport> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<BundleImportReport>('import_bundle', { path, strategy });
+}
+
+/**
+ * 本机签名公钥的指纹，对方预览签名文件时会看到同样的指纹
+ */
+export async function getBundleFingerprint(): Promise<string> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<string>('get_bundle_fingerprint');
+}
+
+/**
+ * 取出双击打开的 .pgenie 文件路径
+ */
+export async function takePendingBundle(): Promise<string | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<string | null>('take_pending_bundle');
+}
+
+// 数据库备份
+export type BackupReason = 'startup' | 'daily' | 'sync' | 'import' | 'manual' | 'pre_restore' | 'pre_migration' | 'pre_repair';
+
+export interface BackupInfo {
+  file_name: string;
+  reason: BackupReason;
+  created_at: string;
+  size: number;
+}
+
+export interface BackupVerification {
+  file_name: string;
+  ok: boolean;
+  problems: string[];
+  prompts: number | null;
+}
+
+/**
+ * 获取备份列表，最新的在前
+ */
+export async function listBackups(): Promise<BackupInfo[]> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<BackupInfo[]>('list_backups');
+}
+
+/**
+ * 立即备份数据库
+ */
+export async function createBackup(): Promise<BackupInfo> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<BackupInfo>('create_backup');
+}
+
+/**
+ * 校验备份是否完整
+ */
+export async function verifyBackup(fileName: string): Promise<BackupVerification> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<BackupVerification>('verify_backup', { fileName });
+}
+
+/**
+ * 从备份恢复数据库，返回恢复前自动生成的备份
+ */
+export async function restoreBackup(fileName: string): Promise<BackupInfo | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<BackupInfo | null>('restore_backup', { fileName });
+}
+
+/**
+ * 启动时数据库升级失败的原因，升级成功时返回 null
+ */
+export async function getMigrationError(): Promise<string | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<string | null>('get_migration_error');
+}
+
+export type DatabaseIssueKind =
+  | 'integrity'
+  | 'foreign_key'
+  | 'duplicate_tag'
+  | 'invalid_timestamp'
+  | 'empty_title';
+
+export interface DatabaseIssue {
+  kind: DatabaseIssueKind;
+  message: string;
+  // 能否通过 repairDatabase 自动修复
+  repairable: boolean;
+}
+
+export interface DatabaseCheck {
+  ok: boolean;
+  issues: DatabaseIssue[];
+}
+
+export interface RepairReport {
+  repaired: string[];
+  remaining: DatabaseIssue[];
+  backup: string | null;
+}
+
+/**
+ * 检查数据库完整性、外键以及重复标签、时间格式、空标题等数据问题
+ */
+export async function checkDatabase(): Promise<DatabaseCheck> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<DatabaseCheck>('check_database');
+}
+
+/**
+ * 修复能安全修复的问题，返回修复内容和仍需手动处理的问题
+ */
+export async function repairDatabase(): Promise<RepairReport> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<RepairReport>('repair_database');
+}